@@ -1,42 +1,113 @@
 use crate::{Config, StatefulList, TabsState};
 
+// The word frontier rankings that can be cycled through.
+fn selectable_frontier_ranking_v() -> Vec<wordfrontier::FrontierRanking> {
+    use wordfrontier::FrontierRanking;
+    vec![
+        FrontierRanking::MinUnknownWordFreq,
+        FrontierRanking::SumUnknownWordFreq,
+        FrontierRanking::MaxUnknownWordFreq,
+        FrontierRanking::SentenceLength,
+        FrontierRanking::Comprehensibility { top_word_count: 1000 },
+        FrontierRanking::UnknownWordRecency,
+        FrontierRanking::Weighted(vec![
+            (1.0, FrontierRanking::MinUnknownWordFreq),
+            (1.0, FrontierRanking::Comprehensibility { top_word_count: 1000 }),
+            (-0.5, FrontierRanking::SentenceLength),
+        ]),
+    ]
+}
+
+const NEXT_WORDS_TAB_INDEX: usize = 2;
+// How many words the Next Words tab recommends.
+const NEXT_WORD_COUNT: u32 = 50;
+
 pub struct App<'a> {
     pub config: Config,
     pub title: &'a str,
     pub should_quit: bool,
     pub tabs: TabsState<'a>,
     pub db_hub: wordfrontier::DbHub,
+    pub frontier_query: wordfrontier::FrontierQuery,
+    pub frontier_ranking_index: usize,
+    pub word_frontier_count: i64,
     pub word_frontier: StatefulList<wordfrontier::WordFrontierMember>,
     pub translations: StatefulList<wordfrontier::TranslationWithText>,
     pub sentence_memberships: StatefulList<wordfrontier::SentenceMembershipWithTextEtc>,
+    pub sentence_ngram_v: Vec<wordfrontier::NgramWithKnown>,
+    pub sentence_span_v: Vec<wordfrontier::SentenceSpan>,
     pub known_words: StatefulList<wordfrontier::KnownWordWithText>,
+    /// The most recent known-word change, which 'u' undoes (unless it's already undone).
+    pub last_journal_entry_o: Option<wordfrontier::JournalEntry>,
+    pub concordance_word_text: String,
+    pub concordance: StatefulList<wordfrontier::ConcordanceMember>,
+    pub next_words: StatefulList<wordfrontier::NextWordRecommendation>,
+    pub next_words_word_set_name_o: Option<String>,
+    /// The number of sentences 'x' last exported to Anki, or why the export failed, until the
+    /// frontier is recomputed.
+    pub anki_export_result_o: Option<wordfrontier::Result<usize>>,
+    /// The number of sentences 'p' last wrote to a reading packet, or why writing it failed,
+    /// until the frontier is recomputed.
+    pub reading_packet_result_o: Option<wordfrontier::Result<usize>>,
 }
 
 impl<'a> App<'a> {
     pub fn new(config: Config, db_hub: wordfrontier::DbHub) -> App<'a> {
+        let mut frontier_query = wordfrontier::FrontierQuery::new(wordfrontier::Range(1, 1))
+            .order(wordfrontier::Order::Descending)
+            .page(0, config.page_size);
+        if let Some(shuffle_seed) = config.shuffle_seed {
+            frontier_query = frontier_query.shuffled(shuffle_seed);
+        }
+        if let Some(search) = &config.search {
+            frontier_query = frontier_query.text_filter(wordfrontier::TextFilter::Search(search.clone()));
+        }
+        if config.min_sentence_words.is_some() || config.max_sentence_words.is_some() {
+            frontier_query = frontier_query.token_count_range(wordfrontier::Range(
+                config.min_sentence_words.unwrap_or(0) as i32,
+                config.max_sentence_words.map(|n| n as i32).unwrap_or(i32::MAX),
+            ));
+        }
         let mut app = App {
             config,
             title: " Word Frontier ",
             should_quit: false,
-            tabs: TabsState::new(vec!["Sentence Learning", "Tab1", "Tab2"]),
+            tabs: TabsState::new(vec!["Sentence Learning", "Word Concordance", "Next Words"]),
             db_hub,
+            frontier_query,
+            frontier_ranking_index: 0,
+            word_frontier_count: 0,
             word_frontier: StatefulList::new(),
             translations: StatefulList::new(),
             sentence_memberships: StatefulList::new(),
+            sentence_ngram_v: Vec::new(),
+            sentence_span_v: Vec::new(),
             known_words: StatefulList::new(),
+            last_journal_entry_o: None,
+            concordance_word_text: String::new(),
+            concordance: StatefulList::new(),
+            next_words: StatefulList::new(),
+            next_words_word_set_name_o: None,
+            anki_export_result_o: None,
+            reading_packet_result_o: None,
         };
         app.update_word_frontier();
         app.update_translations();
         app.update_sentence_membership();
         app.update_known_words();
+        app.record_progress_snapshot();
         app
     }
 
     fn update_word_frontier(&mut self) {
+        self.word_frontier_count = self.db_hub
+            .query_word_frontier_count(&self.frontier_query).expect("uh-oh!");
         self.word_frontier = StatefulList::with_items(
             self.db_hub
-                .query_word_frontier_v(wordfrontier::Range(1, 1), wordfrontier::Order::Descending).expect("uh-oh!")
+                .query_word_frontier_v(&self.frontier_query).expect("uh-oh!")
         );
+        self.anki_export_result_o = None;
+        self.reading_packet_result_o = None;
         // Set the cursor to the 0th element.
         self.word_frontier.next();
         // Reset the cursor for sentence memberships to the 0th element.
@@ -44,6 +115,13 @@ impl<'a> App<'a> {
         self.sentence_memberships.next();
     }
 
+    fn mark_selected_sentence_seen(&mut self) {
+        if let Some(selected_index) = self.word_frontier.state.selected() {
+            let sentence_row = &self.word_frontier.items[selected_index];
+            self.db_hub.mark_sentence_seen(sentence_row.sentences_rowid).expect("uh-oh!");
+        }
+    }
+
     fn update_translations(&mut self) {
         self.translations = if let Some(selected_index) = self.word_frontier.state.selected() {
             let sentence_row = &self.word_frontier.items[selected_index];
@@ -67,6 +145,15 @@ impl<'a> App<'a> {
         } else {
             StatefulList::new()
         };
+        (self.sentence_ngram_v, self.sentence_span_v) = if let Some(selected_index) = self.word_frontier.state.selected() {
+            let sentence_row = &self.word_frontier.items[selected_index];
+            (
+                self.db_hub.query_sentence_ngram_v(sentence_row.sentences_rowid).expect("uh-oh!"),
+                self.db_hub.query_sentence_tokens(sentence_row.sentences_rowid).expect("uh-oh!"),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
         match previous_selection {
             Some(i) => {
                 self.sentence_memberships.state.select(Some(i));
@@ -76,6 +163,82 @@ impl<'a> App<'a> {
                 self.sentence_memberships.next();
             },
         };
+        self.update_concordance();
+    }
+
+    // Shows the easiest sentences containing the selected sentence word.
+    fn update_concordance(&mut self) {
+        let selected_sentence_membership_o = self.sentence_memberships.state.selected()
+            .and_then(|selected_index| self.sentence_memberships.items.get(selected_index));
+        if let Some(sentence_membership_with_text_etc) = selected_sentence_membership_o {
+            self.concordance_word_text = sentence_membership_with_text_etc.word_text.clone();
+            self.concordance = StatefulList::with_items(
+                self.db_hub
+                    .query_sentences_containing_word(
+                        sentence_membership_with_text_etc.word_rowid,
+                        &wordfrontier::ConcordanceQuery::new().limit(self.config.page_size).with_translations(),
+                    ).expect("uh-oh!")
+            );
+        } else {
+            self.concordance_word_text.clear();
+            self.concordance = StatefulList::new();
+        }
+    }
+
+    // This is a relatively expensive query, so it's only done when the Next Words tab is shown.
+    fn update_next_words(&mut self) {
+        if self.tabs.index == NEXT_WORDS_TAB_INDEX {
+            self.next_words = StatefulList::with_items(
+                self.db_hub
+                    .recommend_next_words(NEXT_WORD_COUNT, wordfrontier::NextWordRanking::UnlockedSentenceCount).expect("uh-oh!")
+            );
+            self.next_words_word_set_name_o = None;
+        }
+    }
+
+    fn save_next_words_as_word_set(&mut self) {
+        if self.next_words.items.is_empty() || self.next_words_word_set_name_o.is_some() {
+            return;
+        }
+        let word_set_name = format!(
+            "Next Words {}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0)
+        );
+        let word_rowid_v: Vec<i32> = self.next_words.items.iter().map(|next_word| next_word.word_rowid).collect();
+        self.db_hub.create_word_set(&word_set_name, &word_rowid_v).expect("uh-oh!");
+        self.next_words_word_set_name_o = Some(word_set_name);
+    }
+
+    fn export_word_frontier_to_anki(&mut self) {
+        self.anki_export_result_o = Some(
+            self.db_hub
+                .export_anki_tsv(&self.config.anki_export_file, &self.frontier_query, &self.word_frontier.items)
+                .map(|()| self.word_frontier.items.len()),
+        );
+    }
+
+    fn write_reading_packet(&mut self) {
+        let reading_packet_p = &self.config.reading_packet_file;
+        let format = match reading_packet_p.extension().and_then(|extension| extension.to_str()) {
+            Some("md") => wordfrontier::ReadingPacketFormat::Markdown,
+            _ => wordfrontier::ReadingPacketFormat::Html,
+        };
+        let title = format!("Reading Packet ({})", self.config.target_lang_short_name);
+        self.reading_packet_result_o = Some(
+            self.db_hub
+                .render_reading_packet(&title, &self.frontier_query, &self.word_frontier.items, format)
+                .and_then(|reading_packet| Ok(std::fs::write(reading_packet_p, reading_packet)?))
+                .map(|()| self.word_frontier.items.len()),
+        );
+    }
+
+    // Keeps today's progress snapshot up to date; this happens whenever the frontier is
+    // explicitly recomputed, rather than on each known word change, since it's not cheap.
+    fn record_progress_snapshot(&mut self) {
+        self.db_hub.record_progress_snapshot().expect("uh-oh!");
     }
 
     fn update_known_words(&mut self) {
@@ -83,6 +246,20 @@ impl<'a> App<'a> {
             self.db_hub
                 .query_known_word_with_text_v().expect("uh-oh!")
         );
+        self.last_journal_entry_o = self.db_hub.query_journal_entry_v(1).expect("uh-oh!").pop();
+    }
+
+    fn undo(&mut self) {
+        if self.db_hub.undo().expect("uh-oh!").is_some() {
+            self.update_sentence_membership();
+            self.update_known_words();
+        }
+    }
+    fn redo(&mut self) {
+        if self.db_hub.redo().expect("uh-oh!").is_some() {
+            self.update_sentence_membership();
+            self.update_known_words();
+        }
     }
 
     fn add_selected_sentence_member_to_known_words(&mut self) {
@@ -93,6 +270,14 @@ impl<'a> App<'a> {
             self.update_known_words();
         }
     }
+    fn add_selected_sentence_words_to_known_words(&mut self) {
+        if let Some(selected_index) = self.word_frontier.state.selected() {
+            let sentence_row = &self.word_frontier.items[selected_index];
+            self.db_hub.add_sentence_words_to_known_words(sentence_row.sentences_rowid).expect("uh-oh!");
+            self.update_sentence_membership();
+            self.update_known_words();
+        }
+    }
     fn remove_selected_sentence_member_from_known_words(&mut self) {
         if let Some(selected_index) = self.sentence_memberships.state.selected() {
             let sentence_membership_with_text_etc = &self.sentence_memberships.items[selected_index];
@@ -105,6 +290,7 @@ impl<'a> App<'a> {
     pub fn on_up(&mut self) {
         // TODO: Use the currently focused list
         self.word_frontier.previous();
+        self.mark_selected_sentence_seen();
         self.update_translations();
         self.update_sentence_membership();
     }
@@ -112,6 +298,7 @@ impl<'a> App<'a> {
     pub fn on_down(&mut self) {
         // TODO: Use the currently focused list
         self.word_frontier.next();
+        self.mark_selected_sentence_seen();
         self.update_translations();
         self.update_sentence_membership();
     }
@@ -119,19 +306,23 @@ impl<'a> App<'a> {
     pub fn on_right(&mut self) {
         // TODO: Change the currently focused list
         self.sentence_memberships.next();
+        self.update_concordance();
     }
 
     pub fn on_left(&mut self) {
         // TODO: Change the currently focused list
         self.sentence_memberships.previous();
+        self.update_concordance();
     }
 
     pub fn on_tab(&mut self) {
         self.tabs.next();
+        self.update_next_words();
     }
 
     pub fn on_back_tab(&mut self) {
         self.tabs.previous();
+        self.update_next_words();
     }
 
     pub fn on_enter(&mut self) {
@@ -142,11 +333,165 @@ impl<'a> App<'a> {
         self.remove_selected_sentence_member_from_known_words();
     }
 
+    pub fn on_page_down(&mut self) {
+        let page_size = self.config.page_size;
+        if (self.frontier_query.offset as i64) + (page_size as i64) < self.word_frontier_count {
+            self.frontier_query.offset += page_size;
+            self.on_reload();
+        }
+    }
+
+    pub fn on_page_up(&mut self) {
+        if self.frontier_query.offset > 0 {
+            self.frontier_query.offset = self.frontier_query.offset.saturating_sub(self.config.page_size);
+            self.on_reload();
+        }
+    }
+
+    fn toggle_shuffle(&mut self) {
+        self.frontier_query.shuffle_seed_o = match self.frontier_query.shuffle_seed_o {
+            Some(_) => None,
+            // Use the configured seed if there is one, otherwise use a new one each time.
+            None => Some(self.config.shuffle_seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0)
+            })),
+        };
+        // Go back to the first page, since the pages have a different ordering now.
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    fn cycle_ranking(&mut self) {
+        let frontier_ranking_v = selectable_frontier_ranking_v();
+        self.frontier_ranking_index = (self.frontier_ranking_index + 1) % frontier_ranking_v.len();
+        self.frontier_query.ranking = frontier_ranking_v[self.frontier_ranking_index].clone();
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    fn toggle_order(&mut self) {
+        self.frontier_query.order = match self.frontier_query.order {
+            wordfrontier::Order::Ascending => wordfrontier::Order::Descending,
+            _ => wordfrontier::Order::Ascending,
+        };
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    // Toggles whether the selected sentence's longest n-gram is known.
+    fn toggle_sentence_ngram_known(&mut self) {
+        if let Some(ngram_with_known) = self.sentence_ngram_v.first() {
+            if ngram_with_known.ngram_is_known {
+                self.db_hub.remove_known_ngram(ngram_with_known.ngram_row.ngrams_rowid).expect("uh-oh!");
+            } else {
+                self.db_hub.add_known_ngram(ngram_with_known.ngram_row.ngrams_rowid).expect("uh-oh!");
+            }
+            self.update_sentence_membership();
+        }
+    }
+
+    // Toggles whether the selected sentence word is in the user's list of names.
+    fn toggle_selected_sentence_member_name(&mut self) {
+        if let Some(selected_index) = self.sentence_memberships.state.selected() {
+            let sentence_membership_with_text_etc = &self.sentence_memberships.items[selected_index];
+            if sentence_membership_with_text_etc.word_class_o == Some(wordfrontier::WordClass::Name) {
+                self.db_hub.remove_name(sentence_membership_with_text_etc.word_rowid).expect("uh-oh!");
+            } else {
+                self.db_hub.add_name(sentence_membership_with_text_etc.word_rowid).expect("uh-oh!");
+            }
+            self.update_sentence_membership();
+        }
+    }
+
+    // Toggles treating numbers, symbols, likely proper nouns and names as known.
+    fn toggle_auto_known_word_classes(&mut self) {
+        use wordfrontier::WordClass;
+        self.frontier_query.auto_known_word_class_v = if self.frontier_query.auto_known_word_class_v.is_empty() {
+            vec![WordClass::Number, WordClass::Symbol, WordClass::LikelyProperNoun, WordClass::Name]
+        } else {
+            Vec::new()
+        };
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    fn toggle_compounds_known_by_components(&mut self) {
+        self.frontier_query.compounds_known_by_components = !self.frontier_query.compounds_known_by_components;
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    fn toggle_known_ngrams_cover_words(&mut self) {
+        self.frontier_query.known_ngrams_cover_words = !self.frontier_query.known_ngrams_cover_words;
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
+    fn toggle_diversification(&mut self) {
+        self.frontier_query.diversification_o = match self.frontier_query.diversification_o {
+            Some(_) => None,
+            None => Some(wordfrontier::FrontierDiversification {
+                max_sentences_per_group: self.config.max_sentences_per_word,
+                round_robin: true,
+            }),
+        };
+        self.frontier_query.offset = 0;
+        self.on_reload();
+    }
+
     pub fn on_key(&mut self, c: char) {
         match c {
             'q' => {
                 self.should_quit = true;
             }
+            's' => {
+                self.toggle_shuffle();
+            }
+            'r' => {
+                self.cycle_ranking();
+            }
+            'o' => {
+                self.toggle_order();
+            }
+            'd' => {
+                self.toggle_diversification();
+            }
+            'n' => {
+                self.toggle_sentence_ngram_known();
+            }
+            'g' => {
+                self.toggle_known_ngrams_cover_words();
+            }
+            'm' => {
+                self.toggle_selected_sentence_member_name();
+            }
+            'a' => {
+                self.toggle_auto_known_word_classes();
+            }
+            'c' => {
+                self.toggle_compounds_known_by_components();
+            }
+            'k' => {
+                self.add_selected_sentence_words_to_known_words();
+            }
+            'x' => {
+                self.export_word_frontier_to_anki();
+            }
+            'p' => {
+                self.write_reading_packet();
+            }
+            'u' => {
+                self.undo();
+            }
+            'U' => {
+                self.redo();
+            }
+            'w' if self.tabs.index == NEXT_WORDS_TAB_INDEX => {
+                self.save_next_words_as_word_set();
+            }
             _ => {}
         }
     }
@@ -157,5 +502,11 @@ impl<'a> App<'a> {
         self.update_translations();
         self.update_sentence_membership();
         self.update_known_words();
+        self.update_next_words();
+    }
+
+    pub fn on_refresh(&mut self) {
+        self.on_reload();
+        self.record_progress_snapshot();
     }
 }
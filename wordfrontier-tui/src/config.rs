@@ -7,4 +7,91 @@ pub struct Config {
     #[argh(option, default = "\"eng\".to_string()", short = 'r')]
     /// short name of the reference language, i.e. the language that translations will be provided in.
     pub reference_lang_short_name: String,
+    #[argh(option, default = "200", short = 'n')]
+    /// maximum number of word frontier sentences to show per page.
+    pub page_size: u32,
+    #[argh(option, short = 's')]
+    /// if specified, shuffle the word frontier using this random seed.  Shuffling can also be
+    /// toggled from within the app.
+    pub shuffle_seed: Option<u64>,
+    #[argh(option, default = "3")]
+    /// maximum number of sentences per set of unknown words when the word frontier is
+    /// diversified (toggled from within the app).
+    pub max_sentences_per_word: u32,
+    #[argh(option)]
+    /// only show word frontier sentences containing this text, ignoring case and accents.
+    pub search: Option<String>,
+    #[argh(option)]
+    /// only show word frontier sentences with at least this many words.
+    pub min_sentence_words: Option<u32>,
+    #[argh(option)]
+    /// only show word frontier sentences with at most this many words.
+    pub max_sentence_words: Option<u32>,
+    #[argh(option)]
+    /// import a bilingual dictionary from this file before starting, replacing any dictionary
+    /// previously imported from a file with the same name.
+    pub import_dictionary: Option<std::path::PathBuf>,
+    #[argh(option, default = "wordfrontier::DictionaryFormat::Tsv")]
+    /// format of the dictionary given by --import-dictionary: tsv, freedict-tei, stardict, or
+    /// wiktionary-jsonl.
+    pub dictionary_format: wordfrontier::DictionaryFormat,
+    #[argh(option)]
+    /// before starting, add the words of this word list (an Anki .apkg/.colpkg package, or a CSV
+    /// or TSV file) that are in the corpus to the known words, and report the rest.
+    pub import_word_list: Option<std::path::PathBuf>,
+    #[argh(option)]
+    /// format of the word list given by --import-word-list: anki, csv, or tsv.  Defaults to
+    /// the one indicated by its file extension.
+    pub word_list_format: Option<wordfrontier::WordListFormat>,
+    #[argh(option, default = "wordfrontier::WordListField::Index(0)")]
+    /// the field (Anki) or column (CSV/TSV) of the word list that has the target language word,
+    /// either as a 0-based index or a name.  Defaults to the first one.
+    pub word_list_field: wordfrontier::WordListField,
+    #[argh(switch)]
+    /// the first row of the CSV/TSV word list is a header row naming the columns.
+    pub word_list_header_row: bool,
+    #[argh(switch)]
+    /// word list entries of several words that aren't in the corpus as a whole match each of
+    /// their words instead.
+    pub word_list_match_each_word: bool,
+    #[argh(option)]
+    /// add the words of the word list to the word set with this name (creating it if needed)
+    /// instead of to the known words.
+    pub word_list_word_set: Option<String>,
+    #[argh(option, default = "std::path::PathBuf::from(\"wordfrontier-anki.txt\")")]
+    /// file that 'x' exports the shown word frontier sentences to, as Anki-importable notes.
+    /// Re-exporting the same sentences updates their notes rather than duplicating them.
+    pub anki_export_file: std::path::PathBuf,
+    #[argh(option, default = "std::path::PathBuf::from(\"wordfrontier-reading-packet.html\")")]
+    /// file that 'p' writes a printable reading packet of the shown word frontier sentences to;
+    /// it's Markdown if the file extension is .md, otherwise HTML.
+    pub reading_packet_file: std::path::PathBuf,
+    #[argh(switch)]
+    /// split compound words (e.g. German "Krankenhausaufenthalt") into components, if not done
+    /// already, so that the word frontier can count compounds with known components as known
+    /// (toggled from within the app).
+    pub split_compounds: bool,
+    #[argh(switch)]
+    /// before starting, take a placement test that asks whether you know a sample of words,
+    /// and mark the words it estimates you know as known (undoable from within the app).
+    pub placement_test: bool,
+    #[argh(option)]
+    /// before starting, import (add to) user data (known words, word sets, notes, etc.)
+    /// exported by --export-user-data from this JSON file, matching words by their text.
+    pub import_user_data: Option<std::path::PathBuf>,
+    #[argh(option)]
+    /// before starting, export the user data of the target language to this JSON file.
+    pub export_user_data: Option<std::path::PathBuf>,
+    #[argh(option)]
+    /// before starting, merge the user data of another machine from this user DB file, or JSON
+    /// file exported by --export-user-data, where for conflicting known word changes the later
+    /// one wins.
+    pub merge_user_data: Option<std::path::PathBuf>,
+    #[argh(option)]
+    /// the other machine's target language corpus DB file, which --merge-user-data needs to
+    /// look up the words of a user DB file.
+    pub merge_corpus_db: Option<std::path::PathBuf>,
+    #[argh(switch)]
+    /// only report what --merge-user-data would change, then exit.
+    pub merge_dry_run: bool,
 }
@@ -17,7 +17,7 @@ use crossterm::{
 };
 use std::{
     error::Error,
-    io::stdout,
+    io::{stdout, BufRead, Write},
     sync::mpsc,
     thread,
     time::Duration,
@@ -40,7 +40,95 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Some("http://localhost:7000".into()),
     )?;
     wordfrontier::DbHub::create_and_populate_missing_databases(&db_hub_config).await?;
-    let db_hub = wordfrontier::DbHub::from_config(db_hub_config)?;
+    let mut db_hub = wordfrontier::DbHub::from_config(db_hub_config)?;
+    if !db_hub.has_glosses()? {
+        log::info!("inducing word glosses from translations; this only happens once");
+        db_hub.induce_glosses(&wordfrontier::GlossInduction::default())?;
+    }
+    if !db_hub.has_sentence_tokens()? {
+        log::info!("computing sentence token positions; this only happens once");
+        db_hub.compute_sentence_tokens()?;
+    }
+    if !db_hub.has_word_classes()? {
+        log::info!("classifying words as numbers, symbols and proper nouns; this only happens once");
+        db_hub.compute_word_classes()?;
+    }
+    if config.split_compounds && !db_hub.has_compounds()? {
+        log::info!("splitting compound words");
+        db_hub.split_compounds(&wordfrontier::CompoundSplitting::default())?;
+    }
+    if let Some(dictionary_p) = config.import_dictionary.as_ref() {
+        let dictionary_name = dictionary_p
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dictionary_p.to_string_lossy().into_owned());
+        let report = db_hub.import_dictionary(&dictionary_name, dictionary_p, config.dictionary_format)?;
+        log::info!(
+            "imported dictionary {:?}: {} entries, {} matched to {} corpus words",
+            dictionary_name,
+            report.entry_count,
+            report.matched_entry_count,
+            report.matched_word_count,
+        );
+    }
+    if let Some(word_list_p) = config.import_word_list.as_ref() {
+        let word_list_format = match config.word_list_format {
+            Some(word_list_format) => word_list_format,
+            None => match word_list_p.extension().and_then(|extension| extension.to_str()) {
+                Some("apkg") | Some("colpkg") => wordfrontier::WordListFormat::Anki,
+                Some("csv") => wordfrontier::WordListFormat::Csv,
+                _ => wordfrontier::WordListFormat::Tsv,
+            },
+        };
+        let mut word_list_import = wordfrontier::WordListImport::new(word_list_format, config.word_list_field.clone())
+            .header_row(config.word_list_header_row)
+            .match_each_word(config.word_list_match_each_word);
+        if let Some(word_set_name) = config.word_list_word_set.as_ref() {
+            word_list_import = word_list_import.word_set(word_set_name);
+        }
+        let report = db_hub.import_word_list(word_list_p, &word_list_import)?;
+        log::info!(
+            "imported word list {:?}: {} entries, {} matched, {} words added",
+            word_list_p,
+            report.entry_count,
+            report.matched_entry_count,
+            report.added_word_count,
+        );
+        if !report.unmatched_entry_v.is_empty() {
+            log::warn!("{} entries aren't in the corpus: {:?}", report.unmatched_entry_v.len(), report.unmatched_entry_v);
+        }
+    }
+
+    if let Some(user_data_p) = config.import_user_data.as_ref() {
+        let report = db_hub.import_user_data(&wordfrontier::UserDataBundle::read_from_file(user_data_p)?)?;
+        log::info!("imported user data from {:?}: {:#?}", user_data_p, report);
+        if !report.unmatched_word_text_v.is_empty() {
+            log::warn!("{} words aren't in the corpus: {:?}", report.unmatched_word_text_v.len(), report.unmatched_word_text_v);
+        }
+    }
+    if let Some(user_data_p) = config.merge_user_data.as_ref() {
+        let merge_report = if user_data_p.extension().map(|extension| extension == "json").unwrap_or(false) {
+            db_hub.merge_user_data(&wordfrontier::UserDataBundle::read_from_file(user_data_p)?, config.merge_dry_run)?
+        } else if let Some(corpus_db_p) = config.merge_corpus_db.as_ref() {
+            db_hub.merge_user_db(user_data_p, corpus_db_p, config.merge_dry_run)?
+        } else {
+            return Err("merging a user DB file needs the corpus DB file it was used with (--merge-corpus-db)".into());
+        };
+        println!("{}merged user data from {:?}", if config.merge_dry_run { "dry run: would have " } else { "" }, user_data_p);
+        println!("words made known: {:?}", merge_report.added_known_word_text_v);
+        println!("words made unknown: {:?}", merge_report.removed_known_word_text_v);
+        println!("other changes: {:#?}", merge_report.import_report);
+        if config.merge_dry_run {
+            return Ok(());
+        }
+    }
+    if let Some(user_data_p) = config.export_user_data.as_ref() {
+        db_hub.export_user_data()?.write_to_file(user_data_p)?;
+        log::info!("exported user data to {:?}", user_data_p);
+    }
+    if config.placement_test {
+        run_placement_test(&db_hub)?;
+    }
 
     enable_raw_mode()?;
 
@@ -95,7 +183,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 KeyCode::Down => app.on_down(),
                 KeyCode::Tab => app.on_tab(),
                 KeyCode::BackTab => app.on_back_tab(),
-                KeyCode::F(5) => app.on_reload(),
+                KeyCode::PageDown => app.on_page_down(),
+                KeyCode::PageUp => app.on_page_up(),
+                KeyCode::F(5) => app.on_refresh(),
                 _ => {}
             },
         }
@@ -106,3 +196,54 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+// Asks the placement test's questions on the console, before the TUI takes over the terminal.
+fn run_placement_test(db_hub: &wordfrontier::DbHub) -> Result<(), Box<dyn Error>> {
+    let mut placement_test = db_hub.start_placement_test(wordfrontier::PlacementTestConfig::default())?;
+    println!("Placement test: answer y if you know the word, n if not, or q to stop early.");
+    let stdin = std::io::stdin();
+    let mut line_i = stdin.lock().lines();
+    'questions: while let Some(question) = placement_test.next_question_o() {
+        loop {
+            print!("{}? [y/n/q] ", question.word_text);
+            stdout().flush()?;
+            let line = match line_i.next() {
+                Some(line) => line?,
+                None => break 'questions,
+            };
+            match line.trim() {
+                "y" => {
+                    placement_test.answer(true);
+                    break;
+                }
+                "n" => {
+                    placement_test.answer(false);
+                    break;
+                }
+                "q" => break 'questions,
+                _ => {}
+            }
+        }
+    }
+    let placement_estimate = placement_test.estimate();
+    for band_estimate in placement_estimate.band_estimate_v.iter() {
+        println!(
+            "words {}-{}: {:.0}% known (95% confidence: {:.0}%-{:.0}%, from {} answers)",
+            band_estimate.rank_range.0,
+            band_estimate.rank_range.1,
+            100.0 * band_estimate.known_fraction,
+            100.0 * band_estimate.known_fraction_low,
+            100.0 * band_estimate.known_fraction_high,
+            band_estimate.answer_count,
+        );
+    }
+    println!(
+        "estimated vocabulary: {} words (95% confidence: {}-{})",
+        placement_estimate.estimated_known_word_count,
+        placement_estimate.estimated_known_word_count_low,
+        placement_estimate.estimated_known_word_count_high,
+    );
+    let added_word_count = db_hub.apply_placement_test(&placement_test)?;
+    println!("marked {} words as known", added_word_count);
+    Ok(())
+}
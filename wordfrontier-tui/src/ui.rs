@@ -126,15 +126,68 @@ where
             app.word_frontier
                 .items
                 .iter()
-                .map(|word_frontier_member| {
+                .enumerate()
+                .map(|(index, word_frontier_member)| {
 //                     ListItem::new(vec![Spans::from(Span::raw(&word_frontier_member.text))])
+                    // Only the selected sentence has its words highlighted, since that takes a
+                    // query per sentence.
+                    if Some(index) == app.word_frontier.state.selected() && !app.sentence_span_v.is_empty() {
+                        let mut span_v: Vec<Span> = app.sentence_span_v
+                            .iter()
+                            .map(|sentence_span| match &sentence_span.token_o {
+                                Some(token) if !token.word_is_known => {
+                                    Span::styled(sentence_span.text.as_str(), Style::default().fg(Color::Yellow))
+                                }
+                                _ => Span::raw(sentence_span.text.as_str()),
+                            })
+                            .collect();
+                        span_v.push(Span::raw(format!(" : {}", word_frontier_member.unknown_word_count)));
+                        return ListItem::new(vec![Spans::from(span_v)]);
+                    }
                     ListItem::new(vec![Spans::from(Span::raw(format!("{} : {}", word_frontier_member.text, word_frontier_member.unknown_word_count)))])
 //                     ListItem::new(vec![Spans::from(Span::raw(format!("{} : {} : (sentences_rowid: {})", word_frontier_member.text, word_frontier_member.unknown_word_count, word_frontier_member.sentences_rowid)))])
                 })
                 .collect();
-        let word_frontier_size = word_frontier_list_item_v.len();
+        let word_frontier_title = {
+            let page_size = app.config.page_size.max(1) as i64;
+            let page_count = (app.word_frontier_count + page_size - 1) / page_size;
+            let mut ordering = if app.frontier_query.shuffle_seed_o.is_some() {
+                "Shuffled".to_string()
+            } else {
+                format!("{} {:?}", app.frontier_query.ranking, app.frontier_query.order)
+            };
+            if let Some(diversification) = app.frontier_query.diversification_o {
+                ordering.push_str(&format!(", Max {} Per Word", diversification.max_sentences_per_group));
+            }
+            if !app.frontier_query.auto_known_word_class_v.is_empty() {
+                ordering.push_str(", Names Etc. Known");
+            }
+            if app.frontier_query.compounds_known_by_components {
+                ordering.push_str(", Compounds Known By Parts");
+            }
+            if app.frontier_query.known_ngrams_cover_words {
+                ordering.push_str(", Known N-grams Cover Words");
+            }
+            match &app.anki_export_result_o {
+                Some(Ok(anki_export_count)) => ordering.push_str(&format!(", {} Exported To Anki", anki_export_count)),
+                Some(Err(error)) => ordering.push_str(&format!(", Anki Export Failed: {}", error)),
+                None => {}
+            }
+            match &app.reading_packet_result_o {
+                Some(Ok(reading_packet_count)) => ordering.push_str(&format!(", {} In Reading Packet", reading_packet_count)),
+                Some(Err(error)) => ordering.push_str(&format!(", Reading Packet Failed: {}", error)),
+                None => {}
+            }
+            format!(
+                " Word Frontier ({} Sentences, Page {} of {}, {}) ",
+                app.word_frontier_count,
+                app.frontier_query.offset as i64 / page_size + 1,
+                page_count.max(1),
+                ordering,
+            )
+        };
         let word_frontier_list = List::new(word_frontier_list_item_v)
-            .block(Block::default().borders(Borders::ALL).title(format!(" Word Frontier ({} Sentences) ", word_frontier_size)))
+            .block(Block::default().borders(Borders::ALL).title(word_frontier_title))
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
             .highlight_symbol("-> ");
         f.render_stateful_widget(word_frontier_list, subchunks[0], &mut app.word_frontier.state);
@@ -167,19 +220,43 @@ where
                 .items
                 .iter()
                 .map(|sentence_membership_with_text_etc| {
+                    let gloss_str = sentence_membership_with_text_etc.preferred_gloss();
                     ListItem::new(vec![Spans::from(Span::raw(
                         format!(
-                            "{} : {} : {}",
+                            "{} : {} : {} : {}",
                             sentence_membership_with_text_etc.word_text,
                             sentence_membership_with_text_etc.word_freq,
-                            if sentence_membership_with_text_etc.word_is_known { "Known" } else { "Unknown" },
+                            match (sentence_membership_with_text_etc.word_is_known, sentence_membership_with_text_etc.word_class_o) {
+                                (true, _) => "Known".to_string(),
+                                (false, Some(word_class)) => format!("Unknown {}", word_class),
+                                (false, None) => "Unknown".to_string(),
+                            },
+                            gloss_str,
                         )
                     ))])
                 })
                 .collect();
         let sentence_membership_count = sentence_membership_list_item_v.len();
+        // The sentence's n-grams are listed in the title, with known ones marked by '*'.
+        let sentence_words_title = if app.sentence_ngram_v.is_empty() {
+            format!(" Sentence Words ({}) ", sentence_membership_count)
+        } else {
+            format!(
+                " Sentence Words ({}), N-grams: {} ",
+                sentence_membership_count,
+                app.sentence_ngram_v
+                    .iter()
+                    .map(|ngram_with_known| format!(
+                        "{}{}",
+                        ngram_with_known.ngram_row.text,
+                        if ngram_with_known.ngram_is_known { "*" } else { "" },
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        };
         let sentence_membership_list = List::new(sentence_membership_list_item_v)
-            .block(Block::default().borders(Borders::ALL).title(format!(" Sentence Words ({}) ", sentence_membership_count)))
+            .block(Block::default().borders(Borders::ALL).title(sentence_words_title))
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
             .highlight_symbol("-> ");
         f.render_stateful_widget(sentence_membership_list, chunks[1], &mut app.sentence_memberships.state);
@@ -197,14 +274,27 @@ where
                 })
                 .collect();
         let known_word_count = known_word_list_item_v.len();
+        let known_word_title = match &app.last_journal_entry_o {
+            Some(journal_entry) if journal_entry.is_undone => format!(" Known Words ({}, Press 'U' To Redo: {}) ", known_word_count, journal_entry_summary(journal_entry)),
+            Some(journal_entry) => format!(" Known Words ({}, Press 'u' To Undo: {}) ", known_word_count, journal_entry_summary(journal_entry)),
+            None => format!(" Known Words ({}) ", known_word_count),
+        };
         let known_word_list = List::new(known_word_list_item_v)
-            .block(Block::default().borders(Borders::ALL).title(format!(" Known Words ({}) ", known_word_count)))
+            .block(Block::default().borders(Borders::ALL).title(known_word_title))
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
             .highlight_symbol("-> ");
         f.render_stateful_widget(known_word_list, chunks[2], &mut app.known_words.state);
     }
 }
 
+// E.g. "Add known word Haus", or "Add sentence words (3)" for batch operations.
+fn journal_entry_summary(journal_entry: &wordfrontier::JournalEntry) -> String {
+    match journal_entry.change_v.as_slice() {
+        [journal_change] => format!("{} {}", journal_entry.description, journal_change.word_text),
+        journal_change_v => format!("{} ({})", journal_entry.description, journal_change_v.len()),
+    }
+}
+
 // fn draw_text<B>(f: &mut Frame<B>, area: Rect)
 // where
 //     B: Backend,
@@ -246,129 +336,83 @@ where
 //     f.render_widget(paragraph, area);
 // }
 
-fn draw_second_tab<B>(_f: &mut Frame<B>, _app: &mut App, _area: Rect)
+fn draw_second_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
-//     let chunks = Layout::default()
-//         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-//         .direction(Direction::Horizontal)
-//         .split(area);
-//     let up_style = Style::default().fg(Color::Green);
-//     let failure_style = Style::default()
-//         .fg(Color::Red)
-//         .add_modifier(Modifier::RAPID_BLINK | Modifier::CROSSED_OUT);
-//     let rows = app.servers.iter().map(|s| {
-//         let style = if s.status == "Up" {
-//             up_style
-//         } else {
-//             failure_style
-//         };
-//         Row::new(vec![s.name, s.location, s.status]).style(style)
-//     });
-//     let table = Table::new(rows)
-//         .header(
-//             Row::new(vec!["Server", "Location", "Status"])
-//                 .style(Style::default().fg(Color::Yellow))
-//                 .bottom_margin(1),
-//         )
-//         .block(Block::default().title("Servers").borders(Borders::ALL))
-//         .widths(&[
-//             Constraint::Length(15),
-//             Constraint::Length(15),
-//             Constraint::Length(10),
-//         ]);
-//     f.render_widget(table, chunks[0]);
-//
-//     let map = Canvas::default()
-//         .block(Block::default().title("World").borders(Borders::ALL))
-//         .paint(|ctx| {
-//             ctx.draw(&Map {
-//                 color: Color::White,
-//                 resolution: MapResolution::High,
-//             });
-//             ctx.layer();
-//             ctx.draw(&Rectangle {
-//                 x: 0.0,
-//                 y: 30.0,
-//                 width: 10.0,
-//                 height: 10.0,
-//                 color: Color::Yellow,
-//             });
-//             for (i, s1) in app.servers.iter().enumerate() {
-//                 for s2 in &app.servers[i + 1..] {
-//                     ctx.draw(&Line {
-//                         x1: s1.coords.1,
-//                         y1: s1.coords.0,
-//                         y2: s2.coords.0,
-//                         x2: s2.coords.1,
-//                         color: Color::Yellow,
-//                     });
-//                 }
-//             }
-//             for server in &app.servers {
-//                 let color = if server.status == "Up" {
-//                     Color::Green
-//                 } else {
-//                     Color::Red
-//                 };
-//                 ctx.print(server.coords.1, server.coords.0, "X", color);
-//             }
-//         })
-//         .marker(if app.enhanced_graphics {
-//             symbols::Marker::Braille
-//         } else {
-//             symbols::Marker::Dot
-//         })
-//         .x_bounds([-180.0, 180.0])
-//         .y_bounds([-90.0, 90.0]);
-//     f.render_widget(map, chunks[1]);
+    // Draw the concordance of the selected sentence word, keyword-in-context style, with the
+    // easiest sentences first.
+    let context_char_count = (area.width as usize / 2).saturating_sub(8).max(8);
+    // It seems dumb to be creating a new Vec here each render.
+    let concordance_list_item_v: Vec<ListItem> =
+        app.concordance
+            .items
+            .iter()
+            .map(|concordance_member| {
+                let mut spans_v = match wordfrontier::KeywordInContext::new(&concordance_member.text, &app.concordance_word_text, context_char_count) {
+                    Some(keyword_in_context) => vec![Spans::from(vec![
+                        Span::raw(format!("{:>width$}", keyword_in_context.left, width = context_char_count + 1)),
+                        Span::styled(keyword_in_context.keyword, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                        Span::raw(format!("{} : {}", keyword_in_context.right, concordance_member.unknown_word_count)),
+                    ])],
+                    None => vec![Spans::from(Span::raw(&concordance_member.text))],
+                };
+                for translation_with_text in concordance_member.translation_with_text_v.iter() {
+                    spans_v.push(Spans::from(Span::styled(
+                        format!("    {}", translation_with_text.reference_lang_sentence_text),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+                if let Some(aligned_gloss) = concordance_member.aligned_gloss_v.first() {
+                    spans_v.push(Spans::from(Span::styled(
+                        format!("    {} = {}", app.concordance_word_text, aligned_gloss.reference_word_text),
+                        Style::default().fg(Color::Cyan),
+                    )));
+                }
+                ListItem::new(spans_v)
+            })
+            .collect();
+    let concordance_count = concordance_list_item_v.len();
+    let concordance_list = List::new(concordance_list_item_v)
+        .block(Block::default().borders(Borders::ALL).title(format!(" Sentences Containing {:?} ({}) ", app.concordance_word_text, concordance_count)))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("-> ");
+    f.render_stateful_widget(concordance_list, area, &mut app.concordance.state);
 }
 
-fn draw_third_tab<B>(_f: &mut Frame<B>, _app: &mut App, _area: Rect)
+fn draw_third_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
-//     let chunks = Layout::default()
-//         .direction(Direction::Horizontal)
-//         .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
-//         .split(area);
-//     let colors = [
-//         Color::Reset,
-//         Color::Black,
-//         Color::Red,
-//         Color::Green,
-//         Color::Yellow,
-//         Color::Blue,
-//         Color::Magenta,
-//         Color::Cyan,
-//         Color::Gray,
-//         Color::DarkGray,
-//         Color::LightRed,
-//         Color::LightGreen,
-//         Color::LightYellow,
-//         Color::LightBlue,
-//         Color::LightMagenta,
-//         Color::LightCyan,
-//         Color::White,
-//     ];
-//     let items: Vec<Row> = colors
-//         .iter()
-//         .map(|c| {
-//             let cells = vec![
-//                 Cell::from(Span::raw(format!("{:?}: ", c))),
-//                 Cell::from(Span::styled("Foreground", Style::default().fg(*c))),
-//                 Cell::from(Span::styled("Background", Style::default().bg(*c))),
-//             ];
-//             Row::new(cells)
-//         })
-//         .collect();
-//     let table = Table::new(items)
-//         .block(Block::default().title("Colors").borders(Borders::ALL))
-//         .widths(&[
-//             Constraint::Ratio(1, 3),
-//             Constraint::Ratio(1, 3),
-//             Constraint::Ratio(1, 3),
-//         ]);
-//     f.render_widget(table, chunks[0]);
+    // Draw the recommended next words, each with its easiest example sentence.
+    // It seems dumb to be creating a new Vec here each render.
+    let next_word_list_item_v: Vec<ListItem> =
+        app.next_words
+            .items
+            .iter()
+            .map(|next_word| {
+                let mut spans_v = vec![Spans::from(Span::raw(format!(
+                    "{} : {} : unlocks {}",
+                    next_word.word_text,
+                    next_word.word_freq,
+                    next_word.unlocked_sentence_count,
+                )))];
+                if let Some(example_sentence) = next_word.example_sentence_v.first() {
+                    spans_v.push(Spans::from(Span::styled(
+                        format!("    {}", example_sentence.text),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+                ListItem::new(spans_v)
+            })
+            .collect();
+    let next_word_title = match &app.next_words_word_set_name_o {
+        Some(word_set_name) => format!(" Next Words To Learn ({}, Saved As {:?}) ", next_word_list_item_v.len(), word_set_name),
+        None => format!(" Next Words To Learn ({}, Press 'w' To Save As Word Set) ", next_word_list_item_v.len()),
+    };
+    let next_word_list = List::new(next_word_list_item_v)
+        .block(Block::default().borders(Borders::ALL).title(next_word_title))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("-> ");
+    f.render_stateful_widget(next_word_list, area, &mut app.next_words.state);
 }
@@ -0,0 +1,206 @@
+use crate::Result;
+use std::{collections::HashMap, convert::TryFrom};
+
+/// Parameters for splitting compound words (e.g. German "Krankenhausaufenthalt") into
+/// components, using the corpus's own words as the lexicon.  See DbHub::split_compounds.
+#[derive(Clone, Debug)]
+pub struct CompoundSplitting {
+    /// Components must have at least this many characters.
+    pub min_component_len: usize,
+    /// Components must occur in the corpus at least this many times on their own.
+    pub min_component_freq: i32,
+    /// Linking elements (Fugenelemente) that may join components, e.g. the "s" of
+    /// "Arbeitszeit".
+    pub linking_element_v: Vec<String>,
+}
+
+impl Default for CompoundSplitting {
+    fn default() -> Self {
+        Self {
+            min_component_len: 3,
+            min_component_freq: 2,
+            linking_element_v: ["s", "es", "n", "en", "e", "er"].iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// The schema name is substituted for {}.  Corpus DBs created by older versions don't have
+/// this table yet.
+pub(crate) const CREATE_WORD_COMPONENTS_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.word_components (
+        word_components_rowid INTEGER PRIMARY KEY,
+        word_rowid INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        component_word_rowid INTEGER NOT NULL,
+        UNIQUE(word_rowid, position)
+    )
+";
+
+#[derive(Debug, PartialEq)]
+pub struct CompoundComponentWithText {
+    /// 0-based position of the component within the compound.
+    pub position: i32,
+    pub component_word_rowid: i32,
+    pub component_word_text: String,
+    pub component_is_known: bool,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for CompoundComponentWithText {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(CompoundComponentWithText {
+            position: row.get(0)?,
+            component_word_rowid: row.get(1)?,
+            component_word_text: row.get(2)?,
+            component_is_known: row.get(3)?,
+        })
+    }
+}
+
+// A split of (a suffix of) a word: the number of components, the sum of the logs of their
+// corpus frequencies, and their words_rowid values.
+#[derive(Clone)]
+struct Split {
+    component_count: usize,
+    log_freq_sum: f64,
+    word_rowid_v: Vec<i32>,
+}
+
+impl Split {
+    // Fewer components are better, then a higher geometric mean frequency, which favors
+    // common words over rare (and often spurious) ones.
+    fn is_better_than(&self, other: &Split) -> bool {
+        if self.component_count != other.component_count {
+            return self.component_count < other.component_count;
+        }
+        self.log_freq_sum > other.log_freq_sum
+    }
+}
+
+/// Splits the lowercased word into at least 2 components from the lexicon (lowercased text
+/// -> (words_rowid, freq)), returning their words_rowid values, or None if it's not a compound.
+pub(crate) fn split_compound(
+    word_lowercase: &str,
+    lexicon_m: &HashMap<String, (i32, i32)>,
+    compound_splitting: &CompoundSplitting,
+) -> Option<Vec<i32>> {
+    // memo[i] is the best split of word_lowercase[i..] into 1 or more components.
+    fn best_split(
+        s: &str,
+        start: usize,
+        allow_whole: bool,
+        lexicon_m: &HashMap<String, (i32, i32)>,
+        compound_splitting: &CompoundSplitting,
+        memo_m: &mut HashMap<usize, Option<Split>>,
+    ) -> Option<Split> {
+        if allow_whole {
+            if let Some(split_o) = memo_m.get(&start) {
+                return split_o.clone();
+            }
+        }
+        let mut best_split_o: Option<Split> = None;
+        let char_boundary_v: Vec<usize> = s[start..].char_indices().map(|(i, _)| start + i).skip(1).chain(std::iter::once(s.len())).collect();
+        for (char_count, &end) in char_boundary_v.iter().enumerate() {
+            if char_count + 1 < compound_splitting.min_component_len {
+                continue;
+            }
+            let (word_rowid, freq) = match lexicon_m.get(&s[start..end]) {
+                Some(&(word_rowid, freq)) if freq >= compound_splitting.min_component_freq => (word_rowid, freq),
+                _ => continue,
+            };
+            let mut candidate_v = Vec::new();
+            if end == s.len() {
+                if allow_whole {
+                    candidate_v.push(Split { component_count: 1, log_freq_sum: (freq as f64).ln(), word_rowid_v: vec![word_rowid] });
+                }
+            } else {
+                let rest_start_v = std::iter::once(end).chain(
+                    compound_splitting.linking_element_v
+                        .iter()
+                        .filter(|linking_element| s[end..].starts_with(linking_element.as_str()))
+                        .map(|linking_element| end + linking_element.len()),
+                );
+                for rest_start in rest_start_v {
+                    if rest_start >= s.len() {
+                        continue;
+                    }
+                    if let Some(rest_split) = best_split(s, rest_start, true, lexicon_m, compound_splitting, memo_m) {
+                        let mut word_rowid_v = vec![word_rowid];
+                        word_rowid_v.extend_from_slice(&rest_split.word_rowid_v);
+                        candidate_v.push(Split {
+                            component_count: 1 + rest_split.component_count,
+                            log_freq_sum: (freq as f64).ln() + rest_split.log_freq_sum,
+                            word_rowid_v,
+                        });
+                    }
+                }
+            }
+            for candidate in candidate_v.into_iter() {
+                if best_split_o.as_ref().map(|best_split| candidate.is_better_than(best_split)).unwrap_or(true) {
+                    best_split_o = Some(candidate);
+                }
+            }
+        }
+        if allow_whole {
+            memo_m.insert(start, best_split_o.clone());
+        }
+        best_split_o
+    }
+
+    if word_lowercase.chars().count() < 2 * compound_splitting.min_component_len {
+        return None;
+    }
+    let mut memo_m = HashMap::new();
+    best_split(word_lowercase, 0, false, lexicon_m, compound_splitting, &mut memo_m).map(|split| split.word_rowid_v)
+}
+
+/// (Re)computes the word_components table of the corpus in the given schema from its words
+/// table, returning the number of compounds found.  Matching is case-insensitive, and each
+/// component is the most frequent word with that lowercase text.  This should be run in a
+/// transaction.
+pub(crate) fn populate_word_components(
+    conn: &rusqlite::Connection,
+    schema: &str,
+    lang_rowid: i32,
+    compound_splitting: &CompoundSplitting,
+) -> Result<usize> {
+    conn.execute(&CREATE_WORD_COMPONENTS_TABLE_SQL.replace("{}", schema), [])?;
+    conn.execute(
+        &format!(
+            "
+            DELETE FROM {schema}.word_components
+            WHERE word_rowid IN (SELECT words_rowid FROM {schema}.words WHERE lang_rowid = ?1)
+            ",
+            schema = schema,
+        ),
+        [lang_rowid],
+    )?;
+
+    let word_row_v: Vec<(String, i32, i32)> = conn
+        .prepare(&format!("SELECT text, words_rowid, freq FROM {}.words WHERE lang_rowid = ?1", schema))?
+        .query_map([lang_rowid], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut lexicon_m: HashMap<String, (i32, i32)> = HashMap::new();
+    for (text, words_rowid, freq) in word_row_v.iter() {
+        let entry = lexicon_m.entry(text.to_lowercase()).or_insert((*words_rowid, *freq));
+        if *freq > entry.1 {
+            *entry = (*words_rowid, *freq);
+        }
+    }
+
+    let mut insert_word_component = conn.prepare(&format!(
+        "INSERT INTO {}.word_components (word_rowid, position, component_word_rowid) VALUES (?1, ?2, ?3)",
+        schema,
+    ))?;
+    let mut compound_count = 0;
+    for (text, words_rowid, _) in word_row_v.iter() {
+        if let Some(component_word_rowid_v) = split_compound(&text.to_lowercase(), &lexicon_m, compound_splitting) {
+            for (position, component_word_rowid) in component_word_rowid_v.iter().enumerate() {
+                insert_word_component.execute([*words_rowid, position as i32, *component_word_rowid])?;
+            }
+            compound_count += 1;
+        }
+    }
+    log::debug!("split {} compounds", compound_count);
+    Ok(compound_count)
+}
@@ -0,0 +1,30 @@
+use crate::Result;
+
+/// Records which derived tables (e.g. glosses) have been computed, so that a computed
+/// table that happens to be empty isn't mistaken for one that still needs computing.  The
+/// schema name is substituted for {}.  DBs created by older versions don't have this table
+/// yet, so their derived tables get computed once more.
+pub(crate) const CREATE_COMPUTED_TABLES_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.computed_tables (
+        computed_tables_rowid INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        UNIQUE(name)
+    )
+";
+
+/// Records that the given table of the given schema has been computed.
+pub(crate) fn mark_table_computed(conn: &rusqlite::Connection, schema: &str, table_name: &str) -> Result<()> {
+    conn.execute(&CREATE_COMPUTED_TABLES_TABLE_SQL.replace("{}", schema), [])?;
+    conn.execute(&format!("INSERT OR IGNORE INTO {}.computed_tables (name) VALUES (?1)", schema), [table_name])?;
+    Ok(())
+}
+
+/// Returns whether mark_table_computed has been called for the given table of the given
+/// schema, which must have the computed_tables table.
+pub(crate) fn table_is_computed(conn: &rusqlite::Connection, schema: &str, table_name: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM {}.computed_tables WHERE name = ?1)", schema),
+        [table_name],
+        |row| row.get(0),
+    )?)
+}
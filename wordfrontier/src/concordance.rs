@@ -0,0 +1,202 @@
+use crate::{tokenize_with_byte_offsets, GlossWithText, Order, TranslationWithText};
+use rusqlite::types::Value;
+use std::convert::TryFrom;
+
+/// Parameters for DbHub::query_sentences_containing_word.
+#[derive(Clone, Debug)]
+pub struct ConcordanceQuery {
+    /// Ordering by the number of unknown words in each sentence, so Order::Ascending puts the
+    /// easiest contexts first.  Ignored if shuffle_seed_o is set.
+    pub order: Order,
+    /// Maximum number of sentences to return.  None means no limit.
+    pub limit_o: Option<u32>,
+    /// Number of sentences to skip (after ordering) before returning any.
+    pub offset: u32,
+    /// If set, the sentences are returned in a pseudo-random order determined by this seed,
+    /// so combined with limit_o this gives a reproducible random sample.
+    pub shuffle_seed_o: Option<u64>,
+    /// If true, the translations of each sentence are also retrieved.
+    pub include_translations: bool,
+    /// If true, only sentences that have translations are returned.
+    pub translated_only: bool,
+}
+
+impl Default for ConcordanceQuery {
+    fn default() -> Self {
+        Self {
+            order: Order::Ascending,
+            limit_o: None,
+            offset: 0,
+            shuffle_seed_o: None,
+            include_translations: false,
+            translated_only: false,
+        }
+    }
+}
+
+impl ConcordanceQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit_o = Some(limit);
+        self
+    }
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+    /// Sets limit and offset to select the given 0-based page.
+    pub fn page(self, page_index: u32, page_size: u32) -> Self {
+        self.limit(page_size).offset(page_index.saturating_mul(page_size))
+    }
+    pub fn shuffled(mut self, seed: u64) -> Self {
+        self.shuffle_seed_o = Some(seed);
+        self
+    }
+    pub fn with_translations(mut self) -> Self {
+        self.include_translations = true;
+        self
+    }
+    pub fn translated_only(mut self) -> Self {
+        self.translated_only = true;
+        self
+    }
+
+    pub(crate) fn to_sql(&self, target_lang_rowid: i32, word_rowid: i32) -> (String, Vec<Value>) {
+        let mut param_v = vec![
+            Value::Integer(target_lang_rowid as i64),
+            Value::Integer(word_rowid as i64),
+        ];
+        // The sentences_rowid tie-breaker keeps the order total, so that paging is stable.
+        let ordering_str = match (self.shuffle_seed_o, self.order) {
+            (Some(shuffle_seed), _) => {
+                param_v.push(Value::Integer(shuffle_seed as i64));
+                format!("ORDER BY shuffle_key(?{}, target_corpus_db.sentences.sentences_rowid)", param_v.len())
+            }
+            (None, Order::Ascending) => "ORDER BY unknown_word_count ASC, length(target_corpus_db.sentences.text) ASC, target_corpus_db.sentences.sentences_rowid ASC".into(),
+            (None, Order::Descending) => "ORDER BY unknown_word_count DESC, length(target_corpus_db.sentences.text) DESC, target_corpus_db.sentences.sentences_rowid ASC".into(),
+            (None, Order::Unordered) => "".into(),
+        };
+        let translated_only_sql = if self.translated_only {
+            "
+                AND
+                target_corpus_db.sentences.sentences_rowid IN (
+                    SELECT target_lang_sentence_rowid FROM translations_db.translations
+                )"
+        } else {
+            ""
+        };
+        // In SQLite, a negative LIMIT means no limit.
+        param_v.push(Value::Integer(self.limit_o.map(i64::from).unwrap_or(-1)));
+        param_v.push(Value::Integer(self.offset as i64));
+        let sql = format!("
+            -- This selects the sentences containing a given word, along with their unknown word counts.
+            SELECT
+                target_corpus_db.sentences.sentences_rowid,
+                target_corpus_db.sentences.lang_rowid,
+                target_corpus_db.sentences.text,
+                COUNT(CASE WHEN user_db.known_words.word_rowid IS NULL THEN 1 END) AS unknown_word_count
+            FROM target_corpus_db.sentence_memberships AS word_memberships
+            INNER JOIN target_corpus_db.sentences ON target_corpus_db.sentences.sentences_rowid = word_memberships.sentence_rowid
+            INNER JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE
+                word_memberships.word_rowid = ?2
+                AND
+                target_corpus_db.sentences.lang_rowid = ?1{}
+            GROUP BY target_corpus_db.sentences.sentences_rowid
+            {}
+            LIMIT ?{} OFFSET ?{}
+        ",
+            translated_only_sql,
+            ordering_str,
+            param_v.len() - 1,
+            param_v.len(),
+        );
+        (sql, param_v)
+    }
+}
+
+#[derive(Debug)]
+pub struct ConcordanceMember {
+    pub sentences_rowid: i32,
+    pub lang_rowid: i32,
+    pub text: String,
+    pub unknown_word_count: i32,
+    /// Only populated if ConcordanceQuery::include_translations was set.
+    pub translation_with_text_v: Vec<TranslationWithText>,
+    /// The glosses of the word that occur in this sentence's translations, best first, i.e.
+    /// the word's likely translations in this particular context.  Only populated if
+    /// ConcordanceQuery::include_translations was set.
+    pub aligned_gloss_v: Vec<GlossWithText>,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for ConcordanceMember {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(ConcordanceMember {
+            sentences_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            text: row.get(2)?,
+            unknown_word_count: row.get(3)?,
+            translation_with_text_v: Vec::new(),
+            aligned_gloss_v: Vec::new(),
+        })
+    }
+}
+
+/// A keyword-in-context rendering of a sentence, i.e. the keyword along with (at most) a
+/// fixed amount of the text to either side of it, for displaying concordances in columns.
+#[derive(Debug, PartialEq)]
+pub struct KeywordInContext {
+    pub left: String,
+    pub keyword: String,
+    pub right: String,
+}
+
+impl KeywordInContext {
+    /// Finds the first occurrence of word in text (as a whole word, as determined by
+    /// tokenize) and keeps at most context_char_count chars of text on either side of it.
+    /// Truncated context is marked with an ellipsis.  Returns None if word isn't in text.
+    pub fn new(text: &str, word: &str, context_char_count: usize) -> Option<Self> {
+        let (byte_offset, word_str) = tokenize_with_byte_offsets(text)
+            .find(|(_, word_str)| *word_str == word)?;
+        let left_str = &text[..byte_offset];
+        let right_str = &text[byte_offset + word_str.len()..];
+
+        let left_char_count = left_str.chars().count();
+        let left = if left_char_count > context_char_count {
+            let skip_count = left_char_count - context_char_count;
+            format!("…{}", left_str.chars().skip(skip_count).collect::<String>())
+        } else {
+            left_str.to_string()
+        };
+        let right = if right_str.chars().count() > context_char_count {
+            format!("{}…", right_str.chars().take(context_char_count).collect::<String>())
+        } else {
+            right_str.to_string()
+        };
+        Some(Self { left, keyword: word_str.to_string(), right })
+    }
+    /// Renders the keyword in context on a single line, with the left context right-aligned to
+    /// the given width so that the keywords of consecutive lines line up.
+    pub fn to_aligned_line(&self, left_width: usize) -> String {
+        format!("{:>width$}[{}]{}", self.left, self.keyword, self.right, width = left_width)
+    }
+}
+
+impl std::fmt::Display for KeywordInContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}[{}]{}", self.left, self.keyword, self.right)
+    }
+}
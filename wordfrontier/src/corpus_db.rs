@@ -1,4 +1,7 @@
-use crate::{LANG_M, LangRow, OnConflict, Result};
+use crate::{
+    ngrams, sentence_tokens, tokenize, word_classes, LangRow, NgramSelection, OnConflict, Result,
+    LANG_M,
+};
 use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
@@ -23,11 +26,11 @@ impl SentenceRow {
         ) {
             // We expect exactly 3 tab-separated strings (None indicates the end of strings)
             (Some(sentences_rowid_str), Some(_lang_short), Some(text), None) => {
-                let sentences_rowid = str::parse::<i32>(sentences_rowid_str).or_else(
-                    |e| Err(anyhow::anyhow!(
+                let sentences_rowid = str::parse::<i32>(sentences_rowid_str).map_err(
+                    |e| anyhow::anyhow!(
                         "Parse error {} in translations TSV data; expected integer rowid value, but got {:#?}",
                         e,  sentences_rowid_str
-                    ))
+                    )
                 )?;
                 Ok(SentenceRow { sentences_rowid, lang_rowid, text: text.into() })
             },
@@ -37,6 +40,11 @@ impl SentenceRow {
     }
 }
 
+/// This makes looking up the sentences containing a given word (the concordance) fast.  The
+/// schema name is substituted for {}.  Corpus DBs created by older versions don't have it yet.
+pub(crate) const CREATE_SENTENCE_MEMBERSHIPS_WORD_ROWID_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS {}.sentence_memberships_word_rowid ON sentence_memberships (word_rowid)";
+
 pub struct WordRow {
     pub words_rowid: i32,
     pub lang_rowid: i32,
@@ -152,6 +160,31 @@ impl CorpusDb {
         &mut self,
         override_base_url_o: Option<&str>,
     ) -> Result<()> {
+        // Download the content
+
+        let default_base_url = "https://downloads.tatoeba.org/exports/per_language";
+        let base_url = override_base_url_o.unwrap_or(default_base_url);
+
+        // TODO: Figure out how to do this in a streaming way
+        let sentences_tsv_string = {
+            let compressed_bytes =
+                reqwest::get(format!("{}/{}/{}_sentences.tsv.bz2", base_url, self.lang_row.short, self.lang_row.short))
+                .await?
+                // TODO: Streaming into bzip2 decompression
+                .bytes()
+                .await?;
+            let mut bz2_decoder = bzip2::bufread::BzDecoder::new(compressed_bytes.as_ref());
+            // TODO: Try to pre-allocate capacity
+            let mut sentences_tsv_string = String::new();
+            use std::io::Read;
+            bz2_decoder.read_to_string(&mut sentences_tsv_string)?;
+            sentences_tsv_string
+        };
+        self.populate_from_sentences_tsv(&sentences_tsv_string)
+    }
+    /// Ingests sentences in the tatoeba.org per-language TSV format (sentence id, lang short, text),
+    /// which is also what `populate` downloads.
+    pub fn populate_from_sentences_tsv(&mut self, sentences_tsv_string: &str) -> Result<()> {
         let tx = self.conn.transaction()?;
 
         // Create all the tables
@@ -182,27 +215,10 @@ impl CorpusDb {
             )",
             [],
         )?;
+        tx.execute(&CREATE_SENTENCE_MEMBERSHIPS_WORD_ROWID_INDEX_SQL.replace("{}", "main"), [])?;
 
-        // Now download and ingest the content
+        // Now ingest the content
 
-        let default_base_url = "https://downloads.tatoeba.org/exports/per_language";
-        let base_url = override_base_url_o.unwrap_or(default_base_url);
-
-        // TODO: Figure out how to do this in a streaming way
-        let sentences_tsv_string = {
-            let compressed_bytes =
-                reqwest::get(format!("{}/{}/{}_sentences.tsv.bz2", base_url, self.lang_row.short, self.lang_row.short))
-                .await?
-                // TODO: Streaming into bzip2 decompression
-                .bytes()
-                .await?;
-            let mut bz2_decoder = bzip2::bufread::BzDecoder::new(compressed_bytes.as_ref());
-            // TODO: Try to pre-allocate capacity
-            let mut sentences_tsv_string = String::new();
-            use std::io::Read;
-            bz2_decoder.read_to_string(&mut sentences_tsv_string)?;
-            sentences_tsv_string
-        };
         let line_count = sentences_tsv_string.split('\n').count();
         log::debug!("sentences TSV data had {} lines", line_count);
 
@@ -237,12 +253,7 @@ impl CorpusDb {
                 let mut sentence_words_rowid_s: HashSet<i32> = HashSet::new();
 
                 // Now also parse the sentence and gather words.
-                for word_str in sentence_row.text.split_whitespace() {
-                    // Clean punctuation off of the word.
-                    let trim_pattern: &[char] = &['.', ',', '!', '¡', '?', '¿', '"', '\''];
-                    let word_str = word_str
-                        .trim_start_matches(trim_pattern)
-                        .trim_end_matches(trim_pattern);
+                for word_str in tokenize(&sentence_row.text) {
                     match word_row_m.get_mut(word_str) {
                         Some(word_row) => {
                             // If the word existed already, bump freq up by 1.
@@ -322,6 +333,12 @@ impl CorpusDb {
             }
         }
 
+        sentence_tokens::populate_sentence_tokens(&tx, "main", self.lang_row.langs_rowid)?;
+        word_classes::populate_word_classes(&tx, "main", self.lang_row.langs_rowid, &self.lang_row.short, None)?;
+        // Select frequent fixed expressions as knowable n-grams.  DbHub::compute_ngrams can
+        // redo this with a different NgramSelection.
+        ngrams::populate_ngrams(&tx, "main", self.lang_row.langs_rowid, &NgramSelection::default())?;
+
         tx.commit()?;
 
         Ok(())
@@ -1,5 +1,26 @@
-use crate::{CorpusDb, CorpusPurpose, Lang, LangsDb, Order, LANG_M, Range, Result, TranslationsDb, UserDb};
-use std::convert::TryFrom;
+use crate::{
+    compounds, computed_tables, corpus_db, glosses, journal, learning_path, next_words, ngrams,
+    normalize_word, placement::{self, PlacementBand}, progress, read_dictionary_entry_v,
+    read_word_list_entry_v, reading_packet, sentence_export, sentence_tokens, split_sentences,
+    sql_functions, stats, text_analysis, tokenize, tokenize_with_byte_offsets, translations_db,
+    word_classes, word_notes, BundleKnownWordEvent, BundleSeenWord, BundleWordNote, BundleWordSet,
+    CompoundComponentWithText, CompoundSplitting, ConcordanceMember, ConcordanceQuery, CorpusDb,
+    CorpusPurpose, DictionaryDb, DictionaryFormat, DictionaryGloss, DictionaryImportReport,
+    DictionaryRow, ExportSentence, ExportWord, FrontierQuery, GlossInduction, GlossWithText,
+    JournalChange, JournalEntry, KnownWordTimelinePoint, Lang, LangsDb, LearningPathStepWithText,
+    NextWordRanking, NextWordRecommendation, NgramSelection, NgramWithKnown, PlacementQuestion,
+    PlacementTest, PlacementTestConfig, ProgressSnapshotRow, Range, ReadingPacketFormat, Result,
+    SentenceSpan, SentenceSpanToken, SentenceTokenRow, TextAnalysis, TranslationsDb, UserDataBundle,
+    UserDataImportReport, UserDataMergeReport, UserDb, VocabularyStats, WordClass, WordKnowledge,
+    WordListImport, WordListImportReport, WordListTarget, WordNote, WordNoteRow, WordNoteWithText,
+    WordSetRow, FREQUENCY_BAND_TOP_WORD_COUNT_V, LANG_M,
+};
+use rusqlite::OptionalExtension;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    convert::TryFrom,
+    path::Path,
+};
 
 pub struct SentenceMembershipWithTextEtc {
     pub sentence_memberships_rowid: i32,
@@ -8,6 +29,35 @@ pub struct SentenceMembershipWithTextEtc {
     pub word_text: String,
     pub word_freq: i32,
     pub word_is_known: bool,
+    /// Induced glosses of the word, best first.
+    pub gloss_v: Vec<GlossWithText>,
+    /// Glosses from imported dictionaries.
+    pub dictionary_gloss_v: Vec<DictionaryGloss>,
+    /// The user's own note on the word, if any.
+    pub word_note_row_o: Option<WordNoteRow>,
+    /// The word's class, if any; the user's names take precedence over the corpus's classes.
+    pub word_class_o: Option<WordClass>,
+}
+
+impl SentenceMembershipWithTextEtc {
+    /// Returns the user's own gloss if any, otherwise the first dictionary gloss if any,
+    /// otherwise the best few induced glosses; empty if there are none.
+    pub fn preferred_gloss(&self) -> String {
+        let user_gloss_o = self.word_note_row_o
+            .as_ref()
+            .map(|word_note_row| word_note_row.word_note.user_gloss.as_str())
+            .filter(|user_gloss| !user_gloss.is_empty());
+        match (user_gloss_o, self.dictionary_gloss_v.first()) {
+            (Some(user_gloss), _) => user_gloss.to_string(),
+            (None, Some(dictionary_gloss)) => dictionary_gloss.gloss.clone(),
+            (None, None) => self.gloss_v
+                .iter()
+                .take(3)
+                .map(|gloss| gloss.reference_word_text.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
 }
 
 impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for SentenceMembershipWithTextEtc {
@@ -20,6 +70,10 @@ impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for SentenceMembershipWithTextEtc {
             word_text: row.get(3)?,
             word_freq: row.get(4)?,
             word_is_known: row.get(5)?,
+            gloss_v: Vec::new(),
+            dictionary_gloss_v: Vec::new(),
+            word_note_row_o: None,
+            word_class_o: row.get(6)?,
         })
     }
 }
@@ -30,6 +84,10 @@ pub struct KnownWordWithText {
     pub lang_rowid: i32,
     pub word_rowid: i32,
     pub word_text: String,
+    /// Glosses from imported dictionaries.
+    pub dictionary_gloss_v: Vec<DictionaryGloss>,
+    /// The user's own note on the word, if any.
+    pub word_note_row_o: Option<WordNoteRow>,
 }
 
 impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for KnownWordWithText {
@@ -40,10 +98,51 @@ impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for KnownWordWithText {
             lang_rowid: row.get(1)?,
             word_rowid: row.get(2)?,
             word_text: row.get(3)?,
+            dictionary_gloss_v: Vec::new(),
+            word_note_row_o: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct WordSetMemberWithText {
+    pub word_set_members_rowid: i32,
+    pub word_set_rowid: i32,
+    pub word_rowid: i32,
+    pub word_text: String,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordSetMemberWithText {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(WordSetMemberWithText {
+            word_set_members_rowid: row.get(0)?,
+            word_set_rowid: row.get(1)?,
+            word_rowid: row.get(2)?,
+            word_text: row.get(3)?,
         })
     }
 }
 
+#[derive(Debug)]
+pub struct NameWithText {
+    pub names_rowid: i32,
+    pub word_rowid: i32,
+    pub word_text: String,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for NameWithText {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(NameWithText {
+            names_rowid: row.get(0)?,
+            word_rowid: row.get(1)?,
+            word_text: row.get(2)?,
+        })
+    }
+}
+
+#[derive(Debug)]
 pub struct TranslationWithText {
     pub translations_rowid: i32,
     pub target_lang_sentence_rowid: i32,
@@ -70,6 +169,8 @@ pub struct WordFrontierMember {
     pub text: String,
     pub unknown_word_count: i32,
     pub unknown_word_freq: i32,
+    /// The score that the frontier was ordered by (see FrontierRanking).
+    pub rank_score: f64,
 }
 
 impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordFrontierMember {
@@ -81,6 +182,7 @@ impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordFrontierMember {
             text: row.get(2)?,
             unknown_word_count: row.get(3)?,
             unknown_word_freq: row.get(4)?,
+            rank_score: row.get(5)?,
         })
     }
 }
@@ -129,32 +231,24 @@ impl DbHub {
         LangsDb::create_and_populate_if_missing()?;
         let (target_lang_row, reference_lang_row) = {
             let langs_db = LangsDb::open()?;
-            let target_lang_row = langs_db.query_lang_row(&db_hub_config.target_lang.short)?;
-            let reference_lang_row = langs_db.query_lang_row(&db_hub_config.reference_lang.short)?;
+            let target_lang_row = langs_db.query_lang_row(db_hub_config.target_lang.short)?;
+            let reference_lang_row = langs_db.query_lang_row(db_hub_config.reference_lang.short)?;
             (target_lang_row, reference_lang_row)
         };
         UserDb::create_and_populate_if_missing()?;
+        DictionaryDb::create_and_populate_if_missing()?;
         CorpusDb::create_and_populate_if_missing(
             target_lang_row,
-            db_hub_config
-                .override_base_url_o
-                .as_ref()
-                .map(|s| s.as_str())
+            db_hub_config.override_base_url_o.as_deref()
         ).await?;
         CorpusDb::create_and_populate_if_missing(
             reference_lang_row,
-            db_hub_config
-                .override_base_url_o
-                .as_ref()
-                .map(|s| s.as_str()),
+            db_hub_config.override_base_url_o.as_deref(),
         ).await?;
         TranslationsDb::create_and_populate_if_missing(
-            &db_hub_config.target_lang.short,
-            &db_hub_config.reference_lang.short,
-            db_hub_config
-                .override_base_url_o
-                .as_ref()
-                .map(|s| s.as_str()),
+            db_hub_config.target_lang.short,
+            db_hub_config.reference_lang.short,
+            db_hub_config.override_base_url_o.as_deref(),
         ).await?;
         Ok(())
     }
@@ -163,15 +257,27 @@ impl DbHub {
 
         // TODO: Is opening an in-memory DB and attaching the file-backed ones a dumb idea?
         let conn = rusqlite::Connection::open(":memory:")?;
+        sql_functions::register(&conn)?;
 
         LangsDb::attach(&conn)?;
         UserDb::attach(&conn)?;
+        DictionaryDb::attach(&conn)?;
         CorpusDb::attach(&conn, db_hub_config.target_lang.short, CorpusPurpose::TargetLang)?;
         CorpusDb::attach(&conn, db_hub_config.reference_lang.short, CorpusPurpose::ReferenceLang)?;
         TranslationsDb::attach(&conn, db_hub_config.target_lang.short, db_hub_config.reference_lang.short)?;
+        // Translations DBs created by older versions don't have this table yet.
+        conn.execute(&translations_db::CREATE_GLOSSES_TABLE_SQL.replace("{}", "translations_db"), [])?;
+        // Likewise for the n-gram tables of corpus DBs (see compute_ngrams).
+        conn.execute_batch(&ngrams::CREATE_NGRAM_TABLES_SQL.replace("{}", "target_corpus_db"))?;
+        conn.execute(&sentence_tokens::CREATE_SENTENCE_TOKENS_TABLE_SQL.replace("{}", "target_corpus_db"), [])?;
+        conn.execute(&word_classes::CREATE_WORD_CLASSES_TABLE_SQL.replace("{}", "target_corpus_db"), [])?;
+        conn.execute(&compounds::CREATE_WORD_COMPONENTS_TABLE_SQL.replace("{}", "target_corpus_db"), [])?;
+        conn.execute(&corpus_db::CREATE_SENTENCE_MEMBERSHIPS_WORD_ROWID_INDEX_SQL.replace("{}", "target_corpus_db"), [])?;
+        conn.execute(&computed_tables::CREATE_COMPUTED_TABLES_TABLE_SQL.replace("{}", "target_corpus_db"), [])?;
+        conn.execute(&computed_tables::CREATE_COMPUTED_TABLES_TABLE_SQL.replace("{}", "translations_db"), [])?;
 
-        let target_lang_rowid = Self::query_langs_rowid(&conn, &db_hub_config.target_lang.short)?;
-        let reference_lang_rowid = Self::query_langs_rowid(&conn, &db_hub_config.reference_lang.short)?;
+        let target_lang_rowid = Self::query_langs_rowid(&conn, db_hub_config.target_lang.short)?;
+        let reference_lang_rowid = Self::query_langs_rowid(&conn, db_hub_config.reference_lang.short)?;
 
         Ok(DbHub { db_hub_config, conn, target_lang_rowid, reference_lang_rowid })
     }
@@ -199,13 +305,17 @@ impl DbHub {
                 AND
                 user_db.known_words.lang_rowid = ?1
         ")?;
-        let known_word_with_text_v = stmt
+        let mut known_word_with_text_v: Vec<KnownWordWithText> = stmt
             .query_map(
                 rusqlite::params![self.target_lang_rowid],
                 |row| KnownWordWithText::try_from(row),
             )?
             .map(|known_word_with_text_r| known_word_with_text_r.unwrap())
             .collect();
+        for known_word_with_text in known_word_with_text_v.iter_mut() {
+            known_word_with_text.dictionary_gloss_v = self.query_dictionary_gloss_v(known_word_with_text.word_rowid)?;
+            known_word_with_text.word_note_row_o = self.query_word_note_o(known_word_with_text.word_rowid)?;
+        }
         Ok(known_word_with_text_v)
     }
     pub fn query_translation_with_text_v(
@@ -255,116 +365,1864 @@ impl DbHub {
                         FROM user_db.known_words
                         WHERE user_db.known_words.lang_rowid = ?1
                     )
-                ) AS word_is_known
+                ) AS word_is_known,
+                CASE
+                    WHEN user_db.names.word_rowid IS NOT NULL THEN 'name'
+                    ELSE target_corpus_db.word_classes.class
+                END AS word_class
             FROM target_corpus_db.sentence_memberships
             INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = target_corpus_db.sentence_memberships.word_rowid
+            LEFT JOIN target_corpus_db.word_classes ON target_corpus_db.word_classes.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+            LEFT JOIN
+                user_db.names
+                ON
+                user_db.names.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                AND
+                user_db.names.lang_rowid = ?1
             WHERE target_corpus_db.sentence_memberships.sentence_rowid = ?2
             ORDER BY word_is_known ASC
         ")?;
-        let sentence_membership_with_text_etc_v = stmt
+        let mut sentence_membership_with_text_etc_v: Vec<SentenceMembershipWithTextEtc> = stmt
             .query_map(
                 rusqlite::params![self.target_lang_rowid, sentence_rowid],
                 |row| SentenceMembershipWithTextEtc::try_from(row),
             )?
             .map(|sentence_membership_with_text_etc_r| sentence_membership_with_text_etc_r.unwrap())
             .collect();
+        for sentence_membership_with_text_etc in sentence_membership_with_text_etc_v.iter_mut() {
+            sentence_membership_with_text_etc.gloss_v = self.query_gloss_v(sentence_membership_with_text_etc.word_rowid)?;
+            sentence_membership_with_text_etc.dictionary_gloss_v = self.query_dictionary_gloss_v(sentence_membership_with_text_etc.word_rowid)?;
+            sentence_membership_with_text_etc.word_note_row_o = self.query_word_note_o(sentence_membership_with_text_etc.word_rowid)?;
+        }
         Ok(sentence_membership_with_text_etc_v)
     }
+    /// Returns false for corpus DBs created by older versions, which need compute_sentence_tokens.
+    pub fn has_sentence_tokens(&self) -> Result<bool> {
+        computed_tables::table_is_computed(&self.conn, "target_corpus_db", "sentence_tokens")
+    }
+    /// (Re)computes the token positions of the target language corpus's sentences.  The corpus
+    /// import already does this.
+    pub fn compute_sentence_tokens(&mut self) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        sentence_tokens::populate_sentence_tokens(&tx, "target_corpus_db", self.target_lang_rowid)?;
+        tx.commit()?;
+        Ok(())
+    }
+    /// Returns the tokens of the given sentence, in order.
+    pub fn query_sentence_token_v(&self, sentence_rowid: i32) -> Result<Vec<SentenceTokenRow>> {
+        let mut stmt = self.conn.prepare_cached("
+            SELECT
+                target_corpus_db.sentence_tokens.sentence_tokens_rowid,
+                target_corpus_db.sentence_tokens.sentence_rowid,
+                target_corpus_db.sentence_tokens.token_index,
+                target_corpus_db.sentence_tokens.byte_offset,
+                target_corpus_db.sentence_tokens.surface,
+                target_corpus_db.sentence_tokens.word_rowid
+            FROM target_corpus_db.sentence_tokens
+            WHERE target_corpus_db.sentence_tokens.sentence_rowid = ?1
+            ORDER BY target_corpus_db.sentence_tokens.token_index
+        ")?;
+        let sentence_token_row_v = stmt
+            .query_map([sentence_rowid], |row| SentenceTokenRow::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sentence_token_row_v)
+    }
+    /// Returns the text of the given sentence as a sequence of spans, each of which is either a
+    /// token (annotated with whether its word is known) or the text between tokens, so that
+    /// clients can highlight words within the sentence.
+    pub fn query_sentence_tokens(&self, sentence_rowid: i32) -> Result<Vec<SentenceSpan>> {
+        let text: String = self.conn.query_row(
+            "SELECT text FROM target_corpus_db.sentences WHERE sentences_rowid = ?1",
+            [sentence_rowid],
+            |row| row.get(0),
+        )?;
+        let mut stmt = self.conn.prepare_cached("
+            SELECT
+                target_corpus_db.sentence_tokens.byte_offset,
+                length(CAST(target_corpus_db.sentence_tokens.surface AS BLOB)),
+                target_corpus_db.sentence_tokens.token_index,
+                target_corpus_db.sentence_tokens.word_rowid,
+                user_db.known_words.word_rowid IS NOT NULL AS word_is_known
+            FROM target_corpus_db.sentence_tokens
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.sentence_tokens.word_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE target_corpus_db.sentence_tokens.sentence_rowid = ?2
+            ORDER BY target_corpus_db.sentence_tokens.token_index
+        ")?;
+        let token_v = stmt
+            .query_map([self.target_lang_rowid, sentence_rowid], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, i64>(1)? as usize,
+                    SentenceSpanToken {
+                        token_index: row.get(2)?,
+                        word_rowid: row.get(3)?,
+                        word_is_known: row.get(4)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sentence_tokens::sentence_span_v(&text, token_v))
+    }
     pub fn query_word_frontier_v(
         &self,
-        known_word_count_range: Range,
-        order: Order,
+        frontier_query: &FrontierQuery,
     ) -> Result<Vec<WordFrontierMember>> {
-        let ordering_str = match order {
-            Order::Ascending => "ORDER BY unknown_word_freq ASC",
-            Order::Descending => "ORDER BY unknown_word_freq DESC",
-            Order::Unordered => "",
+        self.with_word_frontier_iter(frontier_query, |word_frontier_member_i| {
+            word_frontier_member_i.collect()
+        })?
+    }
+    /// Streaming variant of query_word_frontier_v.  The iterator passed to f yields the frontier
+    /// members as SQLite produces them, so the whole frontier never has to be held in memory.
+    pub fn with_word_frontier_iter<F, T>(&self, frontier_query: &FrontierQuery, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut dyn Iterator<Item = Result<WordFrontierMember>>) -> T,
+    {
+        let (sql, param_v) = frontier_query.to_sql(self.target_lang_rowid, false);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut word_frontier_member_i = stmt
+            .query_map(
+                rusqlite::params_from_iter(param_v.iter()),
+                |row| WordFrontierMember::try_from(row),
+            )?
+            .map(|word_frontier_member_r| Ok(word_frontier_member_r?));
+        Ok(f(&mut word_frontier_member_i))
+    }
+    /// Gathers the highlighted text, unknown words with their glosses, and translations of the
+    /// given word frontier sentences, for exporting them (e.g. via export_anki_tsv).  Words
+    /// count as unknown as they do for frontier_query, which should be the query that produced
+    /// the sentences, so that e.g. auto-known word classes aren't highlighted.
+    pub fn query_export_sentence_v(&self, frontier_query: &FrontierQuery, word_frontier_member_v: &[WordFrontierMember]) -> Result<Vec<ExportSentence>> {
+        let mut query_unknown_word_rowid_s = self.conn.prepare(&frontier_query.sentence_unknown_word_rowid_sql())?;
+        let mut export_sentence_v = Vec::with_capacity(word_frontier_member_v.len());
+        for word_frontier_member in word_frontier_member_v.iter() {
+            let unknown_word_rowid_s: HashSet<i32> = query_unknown_word_rowid_s
+                .query_map([self.target_lang_rowid, word_frontier_member.sentences_rowid], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+            let mut span_v = self.query_sentence_tokens(word_frontier_member.sentences_rowid)?;
+            for token in span_v.iter_mut().filter_map(|span| span.token_o.as_mut()) {
+                token.word_is_known = !unknown_word_rowid_s.contains(&token.word_rowid);
+            }
+            let mut unknown_word_m: HashMap<i32, ExportWord> = self
+                .query_sentence_membership_with_text_etc_v(word_frontier_member.sentences_rowid)?
+                .into_iter()
+                .filter(|sentence_membership_with_text_etc| unknown_word_rowid_s.contains(&sentence_membership_with_text_etc.word_rowid))
+                .map(|sentence_membership_with_text_etc| {
+                    (sentence_membership_with_text_etc.word_rowid, ExportWord {
+                        word_rowid: sentence_membership_with_text_etc.word_rowid,
+                        gloss: sentence_membership_with_text_etc.preferred_gloss(),
+                        word_text: sentence_membership_with_text_etc.word_text,
+                    })
+                })
+                .collect();
+            let unknown_word_v = span_v
+                .iter()
+                .filter_map(|span| span.token_o.as_ref())
+                .filter_map(|token| unknown_word_m.remove(&token.word_rowid))
+                .collect();
+            export_sentence_v.push(ExportSentence {
+                sentence_rowid: word_frontier_member.sentences_rowid,
+                text: word_frontier_member.text.clone(),
+                span_v,
+                unknown_word_v,
+                translation_v: self.query_translation_with_text_v(word_frontier_member.sentences_rowid)?,
+            });
+        }
+        Ok(export_sentence_v)
+    }
+    /// Writes the given word frontier sentences of frontier_query to an Anki-importable TSV file
+    /// of notes (see sentence_export::write_anki_tsv), whose GUIDs are stable across exports.
+    pub fn export_anki_tsv(&self, anki_tsv_p: &Path, frontier_query: &FrontierQuery, word_frontier_member_v: &[WordFrontierMember]) -> Result<()> {
+        let export_sentence_v = self.query_export_sentence_v(frontier_query, word_frontier_member_v)?;
+        sentence_export::write_anki_tsv(anki_tsv_p, self.db_hub_config.target_lang.short, &export_sentence_v)
+    }
+    /// Renders the given word frontier sentences of frontier_query as a printable reading packet
+    /// (see reading_packet::render_reading_packet).
+    pub fn render_reading_packet(
+        &self,
+        title: &str,
+        frontier_query: &FrontierQuery,
+        word_frontier_member_v: &[WordFrontierMember],
+        format: ReadingPacketFormat,
+    ) -> Result<String> {
+        let export_sentence_v = self.query_export_sentence_v(frontier_query, word_frontier_member_v)?;
+        Ok(reading_packet::render_reading_packet(title, &export_sentence_v, format))
+    }
+    /// Returns the total number of sentences in the frontier, disregarding the limit and offset
+    /// of frontier_query.  This is useful for presenting pages.
+    pub fn query_word_frontier_count(&self, frontier_query: &FrontierQuery) -> Result<i64> {
+        let (sql, param_v) = frontier_query.to_sql(self.target_lang_rowid, true);
+        Ok(self.conn.query_row(
+            &sql,
+            rusqlite::params_from_iter(param_v.iter()),
+            |row| row.get(0),
+        )?)
+    }
+    /// Concordance lookup: returns the target-language sentences containing the given word,
+    /// each with its number of unknown words (including the given word if it's unknown).
+    pub fn query_sentences_containing_word(
+        &self,
+        word_rowid: i32,
+        concordance_query: &ConcordanceQuery,
+    ) -> Result<Vec<ConcordanceMember>> {
+        let (sql, param_v) = concordance_query.to_sql(self.target_lang_rowid, word_rowid);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut concordance_member_v = stmt
+            .query_map(
+                rusqlite::params_from_iter(param_v.iter()),
+                |row| ConcordanceMember::try_from(row),
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if concordance_query.include_translations {
+            let gloss_v = self.query_gloss_v(word_rowid)?;
+            for concordance_member in concordance_member_v.iter_mut() {
+                concordance_member.translation_with_text_v =
+                    self.query_translation_with_text_v(concordance_member.sentences_rowid)?;
+                concordance_member.aligned_gloss_v = gloss_v
+                    .iter()
+                    .filter(|gloss| {
+                        concordance_member.translation_with_text_v.iter().any(|translation_with_text| {
+                            tokenize_with_byte_offsets(&translation_with_text.reference_lang_sentence_text)
+                                .any(|(_, word_str)| word_str == gloss.reference_word_text)
+                        })
+                    })
+                    .cloned()
+                    .collect();
+            }
+        }
+        Ok(concordance_member_v)
+    }
+    /// Recommends the n unknown words most worth learning next, each with a few of the easiest
+    /// example sentences containing it.  Pass the result's word_rowid-s to create_word_set to
+    /// keep it as a study list.
+    pub fn recommend_next_words(
+        &self,
+        n: u32,
+        ranking: NextWordRanking,
+    ) -> Result<Vec<NextWordRecommendation>> {
+        let mut stmt = self.conn.prepare(&next_words::next_words_sql(ranking))?;
+        let mut next_word_recommendation_v = stmt
+            .query_map(
+                rusqlite::params![self.target_lang_rowid, n],
+                |row| NextWordRecommendation::try_from(row),
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let example_concordance_query = ConcordanceQuery::new().limit(3).with_translations();
+        for next_word_recommendation in next_word_recommendation_v.iter_mut() {
+            next_word_recommendation.example_sentence_v = self.query_sentences_containing_word(
+                next_word_recommendation.word_rowid,
+                &example_concordance_query,
+            )?;
+        }
+        Ok(next_word_recommendation_v)
+    }
+    /// Computes how much of the target language corpus the known words cover.
+    pub fn stats(&self) -> Result<VocabularyStats> {
+        let query_coverage_counts = |top_word_count: i64| -> Result<stats::CoverageCounts> {
+            Ok(self.conn.query_row(
+                stats::COVERAGE_COUNTS_SQL,
+                rusqlite::params![self.target_lang_rowid, top_word_count],
+                |row| stats::CoverageCounts::try_from(row),
+            )?)
         };
-        let mut stmt = self.conn.prepare(&format!("
-            -- This selects sentence_rowid for sentences having a number of unknown words in a certain range.
+
+        let coverage_counts = query_coverage_counts(-1)?;
+        let frequency_band_coverage_v = FREQUENCY_BAND_TOP_WORD_COUNT_V
+            .iter()
+            .map(|&top_word_count| {
+                let band_coverage_counts = query_coverage_counts(top_word_count as i64)?;
+                Ok(stats::FrequencyBandCoverage {
+                    top_word_count,
+                    word_count: band_coverage_counts.word_count,
+                    known_word_count: band_coverage_counts.known_word_count,
+                    type_coverage: stats::ratio(band_coverage_counts.known_word_count, band_coverage_counts.word_count),
+                    token_coverage: stats::ratio(band_coverage_counts.known_token_count, band_coverage_counts.token_count),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut sentence_count_by_unknown_word_count = [0i64; 4];
+        {
+            let mut stmt = self.conn.prepare(stats::SENTENCE_COUNT_BY_UNKNOWN_WORD_COUNT_SQL)?;
+            let mut rows = stmt.query([self.target_lang_rowid])?;
+            while let Some(row) = rows.next()? {
+                let bucket: usize = row.get(0)?;
+                sentence_count_by_unknown_word_count[bucket] = row.get(1)?;
+            }
+        }
+
+        Ok(VocabularyStats {
+            known_word_count: coverage_counts.known_word_count,
+            word_count: coverage_counts.word_count,
+            token_count: coverage_counts.token_count,
+            known_token_count: coverage_counts.known_token_count,
+            type_coverage: stats::ratio(coverage_counts.known_word_count, coverage_counts.word_count),
+            token_coverage: stats::ratio(coverage_counts.known_token_count, coverage_counts.token_count),
+            sentence_count: sentence_count_by_unknown_word_count.iter().sum(),
+            sentence_count_by_unknown_word_count,
+            frequency_band_coverage_v,
+        })
+    }
+    /// (Re)computes the glosses of all target language words from the translations, replacing
+    /// any previously computed ones.  This is expensive for large corpora, so it's meant to be
+    /// run once after the DBs are populated.
+    pub fn induce_glosses(&mut self, gloss_induction: &GlossInduction) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM translations_db.glosses", [])?;
+        tx.execute(
+            glosses::INDUCE_GLOSSES_SQL,
+            rusqlite::params![
+                gloss_induction.min_cooccurrence_count,
+                gloss_induction.max_glosses_per_word,
+                gloss_induction.min_score,
+            ],
+        )?;
+        computed_tables::mark_table_computed(&tx, "translations_db", "glosses")?;
+        tx.commit()?;
+        Ok(())
+    }
+    /// Returns true if induce_glosses has been run, even if it found no glosses.
+    pub fn has_glosses(&self) -> Result<bool> {
+        computed_tables::table_is_computed(&self.conn, "translations_db", "glosses")
+    }
+    /// Returns the induced glosses of the given target language word, best first.
+    pub fn query_gloss_v(&self, word_rowid: i32) -> Result<Vec<GlossWithText>> {
+        let mut stmt = self.conn.prepare_cached("
             SELECT
-                target_corpus_db.sentences.sentences_rowid,
-                target_corpus_db.sentences.lang_rowid,
-                target_corpus_db.sentences.text,
-                (
-                    SELECT COUNT(*)
-                    FROM target_corpus_db.sentence_memberships
-                    WHERE
-                        target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
-                        AND
-                        target_corpus_db.sentence_memberships.word_rowid NOT IN (
-                            SELECT user_db.known_words.word_rowid
-                            FROM user_db.known_words
-                            WHERE user_db.known_words.lang_rowid = ?1
-                        )
-                    GROUP BY target_corpus_db.sentence_memberships.sentence_rowid
-                    ORDER BY target_corpus_db.sentence_memberships.sentence_rowid
-                ),
-                (
-                    SELECT MIN(target_corpus_db.words.freq)
-                    FROM target_corpus_db.sentence_memberships
-                    INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = target_corpus_db.sentence_memberships.word_rowid
-                    WHERE
-                        target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
-                        AND
-                        target_corpus_db.sentence_memberships.word_rowid NOT IN (
-                            SELECT user_db.known_words.word_rowid
-                            FROM user_db.known_words
-                            WHERE user_db.known_words.lang_rowid = ?1
-                        )
-                    GROUP BY target_corpus_db.sentence_memberships.sentence_rowid
-                    ORDER BY target_corpus_db.sentence_memberships.sentence_rowid
-                ) as unknown_word_freq
+                translations_db.glosses.glosses_rowid,
+                translations_db.glosses.target_word_rowid,
+                translations_db.glosses.reference_word_rowid,
+                reference_corpus_db.words.text,
+                translations_db.glosses.score,
+                translations_db.glosses.cooccurrence_count,
+                translations_db.glosses.rank
+            FROM translations_db.glosses
+            INNER JOIN reference_corpus_db.words ON reference_corpus_db.words.words_rowid = translations_db.glosses.reference_word_rowid
+            WHERE translations_db.glosses.target_word_rowid = ?1
+            ORDER BY translations_db.glosses.rank
+        ")?;
+        let gloss_with_text_v = stmt
+            .query_map([word_rowid], |row| GlossWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(gloss_with_text_v)
+    }
+    /// Imports a target language dictionary from a local file under the given name, replacing
+    /// any dictionary previously imported under that name.  Each entry is matched to the corpus
+    /// words equal to its headword or one of its listed forms, compared via normalize_word.
+    pub fn import_dictionary(&mut self, name: &str, dictionary_p: &Path, format: DictionaryFormat) -> Result<DictionaryImportReport> {
+        let dictionary_entry_v = read_dictionary_entry_v(dictionary_p, format)?;
+
+        let word_rowid_vm = self.query_normalized_word_rowid_vm()?;
+
+        let tx = self.conn.transaction()?;
+        let existing_dictionaries_rowid_o: Option<i32> = tx.query_row(
+            "SELECT dictionaries_rowid FROM dictionary_db.dictionaries WHERE lang_rowid = ?1 AND name = ?2",
+            rusqlite::params![self.target_lang_rowid, name],
+            |row| row.get(0),
+        ).map(Some).or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+        if let Some(existing_dictionaries_rowid) = existing_dictionaries_rowid_o {
+            Self::delete_dictionary_impl(&tx, existing_dictionaries_rowid)?;
+        }
+        tx.execute(
+            "
+            INSERT INTO dictionary_db.dictionaries (lang_rowid, name, format, imported_at)
+            VALUES (?1, ?2, ?3, CAST(strftime('%s', 'now') AS INTEGER))
+            ",
+            rusqlite::params![self.target_lang_rowid, name, format.to_string()],
+        )?;
+        let dictionaries_rowid = tx.last_insert_rowid() as i32;
+
+        let mut matched_entry_count = 0;
+        let mut matched_word_rowid_s: HashSet<i32> = HashSet::new();
+        {
+            let mut insert_entry = tx.prepare(
+                "INSERT INTO dictionary_db.dictionary_entries (dictionary_rowid, headword, gloss) VALUES (?1, ?2, ?3)"
+            )?;
+            let mut insert_match = tx.prepare(
+                "INSERT OR IGNORE INTO dictionary_db.dictionary_word_matches (dictionary_entry_rowid, word_rowid) VALUES (?1, ?2)"
+            )?;
+            for dictionary_entry in dictionary_entry_v.iter() {
+                insert_entry.execute(rusqlite::params![
+                    dictionaries_rowid,
+                    dictionary_entry.headword,
+                    dictionary_entry.gloss_v.join("; "),
+                ])?;
+                let dictionary_entries_rowid = tx.last_insert_rowid();
+                let mut entry_is_matched = false;
+                for word_str in std::iter::once(&dictionary_entry.headword).chain(dictionary_entry.form_v.iter()) {
+                    for &word_rowid in word_rowid_vm.get(&normalize_word(word_str)).into_iter().flatten() {
+                        insert_match.execute(rusqlite::params![dictionary_entries_rowid, word_rowid])?;
+                        matched_word_rowid_s.insert(word_rowid);
+                        entry_is_matched = true;
+                    }
+                }
+                if entry_is_matched {
+                    matched_entry_count += 1;
+                }
+            }
+        }
+        tx.commit()?;
+
+        Ok(DictionaryImportReport {
+            dictionaries_rowid,
+            entry_count: dictionary_entry_v.len(),
+            matched_entry_count,
+            matched_word_count: matched_word_rowid_s.len(),
+        })
+    }
+    /// Maps each normalize_word form to the target corpus words having it; e.g. "haus" maps to
+    /// the words "Haus" and "haus" if both occur.
+    fn query_normalized_word_rowid_vm(&self) -> Result<HashMap<String, Vec<i32>>> {
+        let mut word_rowid_vm: HashMap<String, Vec<i32>> = HashMap::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT words_rowid, text FROM target_corpus_db.words WHERE lang_rowid = ?1"
+        )?;
+        let mut rows = stmt.query([self.target_lang_rowid])?;
+        while let Some(row) = rows.next()? {
+            let word_text: String = row.get(1)?;
+            word_rowid_vm.entry(normalize_word(&word_text)).or_default().push(row.get(0)?);
+        }
+        Ok(word_rowid_vm)
+    }
+    pub fn delete_dictionary(&mut self, dictionaries_rowid: i32) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        Self::delete_dictionary_impl(&tx, dictionaries_rowid)?;
+        tx.commit()?;
+        Ok(())
+    }
+    fn delete_dictionary_impl(conn: &rusqlite::Connection, dictionaries_rowid: i32) -> Result<()> {
+        conn.execute(
+            "
+            DELETE FROM dictionary_db.dictionary_word_matches
+            WHERE dictionary_entry_rowid IN (
+                SELECT dictionary_entries_rowid FROM dictionary_db.dictionary_entries WHERE dictionary_rowid = ?1
+            )
+            ",
+            [dictionaries_rowid],
+        )?;
+        conn.execute("DELETE FROM dictionary_db.dictionary_entries WHERE dictionary_rowid = ?1", [dictionaries_rowid])?;
+        conn.execute("DELETE FROM dictionary_db.dictionaries WHERE dictionaries_rowid = ?1", [dictionaries_rowid])?;
+        Ok(())
+    }
+    pub fn query_dictionary_v(&self) -> Result<Vec<DictionaryRow>> {
+        let mut stmt = self.conn.prepare("
+            SELECT dictionaries_rowid, lang_rowid, name, format, imported_at
+            FROM dictionary_db.dictionaries
+            WHERE lang_rowid = ?1
+            ORDER BY name
+        ")?;
+        let dictionary_row_v = stmt
+            .query_map([self.target_lang_rowid], |row| DictionaryRow::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(dictionary_row_v)
+    }
+    /// Returns the imported dictionary entries matching the given target language word.
+    pub fn query_dictionary_gloss_v(&self, word_rowid: i32) -> Result<Vec<DictionaryGloss>> {
+        let mut stmt = self.conn.prepare_cached("
+            SELECT
+                dictionary_db.dictionary_entries.dictionary_entries_rowid,
+                dictionary_db.dictionaries.name,
+                dictionary_db.dictionary_entries.headword,
+                dictionary_db.dictionary_entries.gloss
+            FROM dictionary_db.dictionary_word_matches
+            INNER JOIN dictionary_db.dictionary_entries ON dictionary_db.dictionary_entries.dictionary_entries_rowid = dictionary_db.dictionary_word_matches.dictionary_entry_rowid
+            INNER JOIN dictionary_db.dictionaries ON dictionary_db.dictionaries.dictionaries_rowid = dictionary_db.dictionary_entries.dictionary_rowid
+            WHERE
+                dictionary_db.dictionary_word_matches.word_rowid = ?1
+                AND
+                dictionary_db.dictionaries.lang_rowid = ?2
+            ORDER BY dictionary_db.dictionaries.name, dictionary_db.dictionary_entries.dictionary_entries_rowid
+        ")?;
+        let dictionary_gloss_v = stmt
+            .query_map([word_rowid, self.target_lang_rowid], |row| DictionaryGloss::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(dictionary_gloss_v)
+    }
+    /// Analyzes arbitrary target language text (e.g. an article) against the known words, to
+    /// tell how readable it is.  Words are looked up in the corpus as tokenized by the corpus
+    /// import, also trying the word with its first letter's case swapped.
+    pub fn analyze_text(&self, text: &str) -> Result<TextAnalysis> {
+        let mut lookup_word = self.conn.prepare_cached("
+            SELECT
+                target_corpus_db.words.words_rowid,
+                target_corpus_db.words.text,
+                target_corpus_db.words.freq,
+                user_db.known_words.word_rowid IS NOT NULL
+            FROM target_corpus_db.words
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.words.words_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE
+                target_corpus_db.words.lang_rowid = ?1
+                AND
+                target_corpus_db.words.text = ?2
+        ")?;
+
+        let mut word_v: Vec<text_analysis::AnalyzedWord> = Vec::new();
+        // Maps each token spelling to its index in word_v.
+        let mut word_index_m: HashMap<String, usize> = HashMap::new();
+        // Maps each corpus word to its index in word_v, so differently capitalized tokens of
+        // the same word are merged.
+        let mut word_rowid_index_m: HashMap<i32, usize> = HashMap::new();
+        let mut sentence_v = Vec::new();
+        let mut token_count = 0;
+        for sentence_str in split_sentences(text) {
+            let mut sentence_word_index_v: Vec<usize> = Vec::new();
+            let mut sentence_token_count = 0;
+            for word_str in tokenize(sentence_str) {
+                sentence_token_count += 1;
+                let word_index = match word_index_m.get(word_str) {
+                    Some(&word_index) => word_index,
+                    None => {
+                        let mut found_o: Option<(i32, String, i32, bool)> = None;
+                        for candidate in text_analysis::lookup_candidate_v(word_str) {
+                            let mut rows = lookup_word.query(rusqlite::params![self.target_lang_rowid, candidate])?;
+                            if let Some(row) = rows.next()? {
+                                found_o = Some((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?));
+                                break;
+                            }
+                        }
+                        let word_index = match found_o {
+                            Some((word_rowid, corpus_text, word_freq, word_is_known)) => {
+                                *word_rowid_index_m.entry(word_rowid).or_insert_with(|| {
+                                    word_v.push(text_analysis::AnalyzedWord {
+                                        text: corpus_text,
+                                        word_rowid_o: Some(word_rowid),
+                                        word_freq,
+                                        knowledge: if word_is_known { WordKnowledge::Known } else { WordKnowledge::Unknown },
+                                        occurrence_count: 0,
+                                    });
+                                    word_v.len() - 1
+                                })
+                            }
+                            None => {
+                                word_v.push(text_analysis::AnalyzedWord {
+                                    text: word_str.to_string(),
+                                    word_rowid_o: None,
+                                    word_freq: 0,
+                                    knowledge: WordKnowledge::OutOfCorpus,
+                                    occurrence_count: 0,
+                                });
+                                word_v.len() - 1
+                            }
+                        };
+                        word_index_m.insert(word_str.to_string(), word_index);
+                        word_index
+                    }
+                };
+                word_v[word_index].occurrence_count += 1;
+                if !sentence_word_index_v.contains(&word_index) {
+                    sentence_word_index_v.push(word_index);
+                }
+            }
+            token_count += sentence_token_count;
+            let count_with_knowledge = |knowledge| {
+                sentence_word_index_v.iter().filter(|&&word_index| word_v[word_index].knowledge == knowledge).count()
+            };
+            sentence_v.push(text_analysis::AnalyzedSentence {
+                text: sentence_str.to_string(),
+                token_count: sentence_token_count,
+                unknown_word_count: count_with_knowledge(WordKnowledge::Unknown),
+                out_of_corpus_word_count: count_with_knowledge(WordKnowledge::OutOfCorpus),
+            });
+        }
+
+        let token_count_with_knowledge = |knowledge| -> usize {
+            word_v.iter().filter(|word| word.knowledge == knowledge).map(|word| word.occurrence_count).sum()
+        };
+        let known_token_count = token_count_with_knowledge(WordKnowledge::Known);
+        let unknown_token_count = token_count_with_knowledge(WordKnowledge::Unknown);
+        let out_of_corpus_token_count = token_count_with_knowledge(WordKnowledge::OutOfCorpus);
+
+        // The example sentence is the easiest translated one, if there's any translated one.
+        let translated_example_concordance_query = ConcordanceQuery::new().limit(1).with_translations().translated_only();
+        let example_concordance_query = ConcordanceQuery::new().limit(1);
+        let mut glossary_v = Vec::new();
+        for word in word_v.iter().filter(|word| word.knowledge == WordKnowledge::Unknown) {
+            let word_rowid = word.word_rowid_o.unwrap();
+            let mut example_sentence_o = self.query_sentences_containing_word(word_rowid, &translated_example_concordance_query)?.pop();
+            if example_sentence_o.is_none() {
+                example_sentence_o = self.query_sentences_containing_word(word_rowid, &example_concordance_query)?.pop();
+            }
+            glossary_v.push(text_analysis::GlossaryEntry {
+                word_rowid,
+                word_text: word.text.clone(),
+                word_freq: word.word_freq,
+                example_sentence_o,
+            });
+        }
+
+        Ok(TextAnalysis {
+            token_count,
+            known_token_count,
+            unknown_token_count,
+            out_of_corpus_token_count,
+            coverage: stats::ratio(known_token_count as i64, token_count as i64),
+            word_v,
+            sentence_v,
+            glossary_v,
+        })
+    }
+    /// Plans the next step_count words to learn (see learning_path::plan_learning_path) over
+    /// the translated sentences, which are the ones the word frontier draws from.
+    pub fn plan_learning_path(&self, step_count: usize) -> Result<Vec<LearningPathStepWithText>> {
+        let sentence_count: i64 = self.conn.query_row(
+            "
+            SELECT COUNT(*)
             FROM target_corpus_db.sentences
             WHERE
                 target_corpus_db.sentences.sentences_rowid IN (
                     SELECT target_lang_sentence_rowid FROM translations_db.translations
                 )
                 AND
-                (
-                    SELECT COUNT(*)
-                    FROM target_corpus_db.sentence_memberships
-                    WHERE
-                        target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
-                        AND
-                        target_corpus_db.sentence_memberships.word_rowid NOT IN (
-                            SELECT user_db.known_words.word_rowid
-                            FROM user_db.known_words
-                            WHERE user_db.known_words.lang_rowid = ?1
-                        )
-                    GROUP BY target_corpus_db.sentence_memberships.sentence_rowid
-                    ORDER BY target_corpus_db.sentence_memberships.sentence_rowid
-                ) BETWEEN ?2 AND ?3
-                AND
                 target_corpus_db.sentences.lang_rowid = ?1
-            -- GROUP BY target_corpus_db.sentences.sentences_rowid
-            {}
-        ", ordering_str))?;
-        let word_frontier_member_v = stmt
-            .query_map(
-                rusqlite::params![
-                    self.target_lang_rowid,
-                    known_word_count_range.0,
-                    known_word_count_range.1,
-                ],
-                |row| WordFrontierMember::try_from(row),
-            )?
-            .map(|word_frontier_member_r| word_frontier_member_r.unwrap())
-            .collect();
-        Ok(word_frontier_member_v)
+            ",
+            [self.target_lang_rowid],
+            |row| row.get(0),
+        )?;
+
+        // Gather the unknown words of each sentence that has any.
+        let mut sentence_unknown_word_vv: Vec<Vec<i32>> = Vec::new();
+        let mut word_text_m: HashMap<i32, String> = HashMap::new();
+        let mut word_freq_m: HashMap<i32, i32> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare("
+                SELECT
+                    target_corpus_db.sentence_memberships.sentence_rowid,
+                    target_corpus_db.words.words_rowid,
+                    target_corpus_db.words.text,
+                    target_corpus_db.words.freq
+                FROM target_corpus_db.sentences
+                INNER JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+                INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = target_corpus_db.sentence_memberships.word_rowid
+                LEFT JOIN
+                    user_db.known_words
+                    ON
+                    user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                    AND
+                    user_db.known_words.lang_rowid = ?1
+                WHERE
+                    target_corpus_db.sentences.sentences_rowid IN (
+                        SELECT target_lang_sentence_rowid FROM translations_db.translations
+                    )
+                    AND
+                    target_corpus_db.sentences.lang_rowid = ?1
+                    AND
+                    user_db.known_words.word_rowid IS NULL
+                ORDER BY target_corpus_db.sentence_memberships.sentence_rowid
+            ")?;
+            let mut rows = stmt.query([self.target_lang_rowid])?;
+            let mut current_sentence_rowid_o: Option<i32> = None;
+            while let Some(row) = rows.next()? {
+                let sentence_rowid: i32 = row.get(0)?;
+                let word_rowid: i32 = row.get(1)?;
+                if current_sentence_rowid_o != Some(sentence_rowid) {
+                    current_sentence_rowid_o = Some(sentence_rowid);
+                    sentence_unknown_word_vv.push(Vec::new());
+                }
+                sentence_unknown_word_vv.last_mut().unwrap().push(word_rowid);
+                if let std::collections::hash_map::Entry::Vacant(entry) = word_text_m.entry(word_rowid) {
+                    entry.insert(row.get(2)?);
+                    word_freq_m.insert(word_rowid, row.get(3)?);
+                }
+            }
+        }
+        // The remaining sentences are already fully known.
+        let known_sentence_count = (sentence_count as usize).saturating_sub(sentence_unknown_word_vv.len());
+        sentence_unknown_word_vv.resize(sentence_unknown_word_vv.len() + known_sentence_count, Vec::new());
+
+        let learning_path_step_v = learning_path::plan_learning_path(&sentence_unknown_word_vv, &word_freq_m, step_count);
+        Ok(learning_path_step_v
+            .into_iter()
+            .map(|learning_path_step| LearningPathStepWithText {
+                word_rowid: learning_path_step.word_rowid,
+                word_text: word_text_m.remove(&learning_path_step.word_rowid).unwrap_or_default(),
+                word_freq: word_freq_m[&learning_path_step.word_rowid],
+                newly_comprehensible_sentence_count: learning_path_step.newly_comprehensible_sentence_count,
+                comprehensible_sentence_count: learning_path_step.comprehensible_sentence_count,
+                coverage: learning_path_step.coverage,
+            })
+            .collect())
+    }
+    /// Creates a new word set with the given name and words, returning its word_sets_rowid.
+    /// It's an error if a word set with that name already exists.
+    pub fn create_word_set(&mut self, name: &str, word_rowid_v: &[i32]) -> Result<i32> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO user_db.word_sets (lang_rowid, name) VALUES (?1, ?2)",
+            rusqlite::params![self.target_lang_rowid, name],
+        )?;
+        let word_sets_rowid = tx.last_insert_rowid() as i32;
+        {
+            let mut insert_word_set_member = tx.prepare(
+                "INSERT OR IGNORE INTO user_db.word_set_members (word_set_rowid, word_rowid) VALUES (?1, ?2)"
+            )?;
+            for word_rowid in word_rowid_v.iter() {
+                insert_word_set_member.execute([word_sets_rowid, *word_rowid])?;
+            }
+        }
+        tx.commit()?;
+        Ok(word_sets_rowid)
+    }
+    pub fn delete_word_set(&mut self, word_sets_rowid: i32) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM user_db.word_set_members WHERE word_set_rowid = ?1", [word_sets_rowid])?;
+        tx.execute("DELETE FROM user_db.word_sets WHERE word_sets_rowid = ?1", [word_sets_rowid])?;
+        tx.commit()?;
+        Ok(())
+    }
+    pub fn query_word_set_v(&self) -> Result<Vec<WordSetRow>> {
+        let mut stmt = self.conn.prepare("
+            SELECT word_sets_rowid, lang_rowid, name
+            FROM user_db.word_sets
+            WHERE lang_rowid = ?1
+            ORDER BY name
+        ")?;
+        let word_set_row_v = stmt
+            .query_map([self.target_lang_rowid], |row| WordSetRow::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_set_row_v)
+    }
+    pub fn query_word_set_member_with_text_v(&self, word_set_rowid: i32) -> Result<Vec<WordSetMemberWithText>> {
+        let mut stmt = self.conn.prepare("
+            SELECT
+                user_db.word_set_members.word_set_members_rowid,
+                user_db.word_set_members.word_set_rowid,
+                user_db.word_set_members.word_rowid,
+                target_corpus_db.words.text
+            FROM user_db.word_set_members
+            INNER JOIN
+                target_corpus_db.words
+                ON
+                target_corpus_db.words.words_rowid = user_db.word_set_members.word_rowid
+            WHERE user_db.word_set_members.word_set_rowid = ?1
+            ORDER BY user_db.word_set_members.word_set_members_rowid
+        ")?;
+        let word_set_member_with_text_v = stmt
+            .query_map([word_set_rowid], |row| WordSetMemberWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_set_member_with_text_v)
     }
     pub fn add_known_word(&self, word_rowid: i32) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if Self::set_word_known(&tx, self.target_lang_rowid, word_rowid, true)? {
+            Self::record_journal_entry(&tx, self.target_lang_rowid, "Add known word", &[(word_rowid, true)])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+    pub fn remove_known_word(&self, word_rowid: i32) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if Self::set_word_known(&tx, self.target_lang_rowid, word_rowid, false)? {
+            Self::record_journal_entry(&tx, self.target_lang_rowid, "Remove known word", &[(word_rowid, false)])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+    /// Adds all words of the given sentence to known_words, returning how many weren't already known.
+    pub fn add_sentence_words_to_known_words(&self, sentence_rowid: i32) -> Result<usize> {
+        let word_rowid_v = self.query_sentence_word_rowid_v(sentence_rowid)?;
+        self.set_words_known("Add sentence words", &word_rowid_v, true)
+    }
+    /// Removes all words of the given sentence from known_words, returning how many were known.
+    pub fn remove_sentence_words_from_known_words(&self, sentence_rowid: i32) -> Result<usize> {
+        let word_rowid_v = self.query_sentence_word_rowid_v(sentence_rowid)?;
+        self.set_words_known("Remove sentence words", &word_rowid_v, false)
+    }
+    /// Adds the word_count most frequent words of the target corpus to known_words, returning
+    /// how many weren't already known.  This is a quick way to bootstrap a non-beginner.
+    pub fn add_most_frequent_words_to_known_words(&self, word_count: u32) -> Result<usize> {
+        let word_rowid_v = self.query_most_frequent_word_rowid_v(word_count)?;
+        self.set_words_known("Add most frequent words", &word_rowid_v, true)
+    }
+    /// Removes the word_count most frequent words of the target corpus from known_words,
+    /// returning how many were known.
+    pub fn remove_most_frequent_words_from_known_words(&self, word_count: u32) -> Result<usize> {
+        let word_rowid_v = self.query_most_frequent_word_rowid_v(word_count)?;
+        self.set_words_known("Remove most frequent words", &word_rowid_v, false)
+    }
+    /// Adds the target corpus words matching any of the given words (compared via
+    /// normalize_word, so e.g. "haus" matches "Haus") to known_words, returning how many weren't
+    /// already known.  Words not in the corpus are ignored.
+    pub fn add_listed_words_to_known_words(&self, word_str_v: &[&str]) -> Result<usize> {
+        let word_rowid_v = self.query_listed_word_rowid_v(word_str_v)?;
+        self.set_words_known("Add listed words", &word_rowid_v, true)
+    }
+    /// Removes the target corpus words matching any of the given words (compared via
+    /// normalize_word) from known_words, returning how many were known.
+    pub fn remove_listed_words_from_known_words(&self, word_str_v: &[&str]) -> Result<usize> {
+        let word_rowid_v = self.query_listed_word_rowid_v(word_str_v)?;
+        self.set_words_known("Remove listed words", &word_rowid_v, false)
+    }
+    /// Imports the words of a word list (e.g. an Anki deck) into known_words or a word set, per
+    /// word_list_import.  Each entry is matched to the target corpus words equal to it, compared
+    /// via normalize_word, or if word_list_import.match_each_word is set and there are none, to
+    /// those equal to any of its words.  Adding to known_words is a single journal entry.
+    pub fn import_word_list(&mut self, word_list_p: &Path, word_list_import: &WordListImport) -> Result<WordListImportReport> {
+        let entry_v = read_word_list_entry_v(word_list_p, word_list_import)?;
+        let word_rowid_vm = self.query_normalized_word_rowid_vm()?;
+
+        let mut report = WordListImportReport { entry_count: entry_v.len(), ..Default::default() };
+        let mut word_rowid_v: Vec<i32> = Vec::new();
+        let mut unmatched_entry_s: HashSet<&str> = HashSet::new();
+        for entry in entry_v.iter() {
+            let mut entry_word_rowid_v: Vec<i32> = word_rowid_vm.get(&normalize_word(entry)).cloned().unwrap_or_default();
+            if entry_word_rowid_v.is_empty() && word_list_import.match_each_word {
+                for word_str in tokenize(entry) {
+                    entry_word_rowid_v.extend(word_rowid_vm.get(&normalize_word(word_str)).into_iter().flatten());
+                }
+            }
+            if entry_word_rowid_v.is_empty() {
+                if unmatched_entry_s.insert(entry) {
+                    report.unmatched_entry_v.push(entry.clone());
+                }
+            } else {
+                report.matched_entry_count += 1;
+                word_rowid_v.extend(entry_word_rowid_v);
+            }
+        }
+        word_rowid_v.sort_unstable();
+        word_rowid_v.dedup();
+
+        report.added_word_count = match &word_list_import.target {
+            WordListTarget::KnownWords => self.set_words_known("Import word list", &word_rowid_v, true)?,
+            WordListTarget::WordSet(name) => {
+                let tx = self.conn.transaction()?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO user_db.word_sets (lang_rowid, name) VALUES (?1, ?2)",
+                    rusqlite::params![self.target_lang_rowid, name],
+                )?;
+                let word_sets_rowid: i32 = tx.query_row(
+                    "SELECT word_sets_rowid FROM user_db.word_sets WHERE lang_rowid = ?1 AND name = ?2",
+                    rusqlite::params![self.target_lang_rowid, name],
+                    |row| row.get(0),
+                )?;
+                let mut added_word_count = 0;
+                {
+                    let mut insert_word_set_member = tx.prepare(
+                        "INSERT OR IGNORE INTO user_db.word_set_members (word_set_rowid, word_rowid) VALUES (?1, ?2)"
+                    )?;
+                    for &word_rowid in word_rowid_v.iter() {
+                        added_word_count += insert_word_set_member.execute([word_sets_rowid, word_rowid])?;
+                    }
+                }
+                tx.commit()?;
+                added_word_count
+            }
+        };
+        Ok(report)
+    }
+    /// Starts a placement test, sampling up to config.max_questions_per_band words from each
+    /// frequency band of the target corpus.
+    pub fn start_placement_test(&self, config: PlacementTestConfig) -> Result<PlacementTest> {
+        let word_count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM target_corpus_db.words WHERE lang_rowid = ?1",
+            [self.target_lang_rowid],
+            |row| row.get(0),
+        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "{} ORDER BY shuffle_key(?4, words_rowid) LIMIT ?5",
+            placement::BAND_WORDS_SQL,
+        ))?;
+        let mut band_v = Vec::new();
+        for (band_index, rank_range) in placement::band_rank_range_v(&config.band_top_word_count_v, word_count).into_iter().enumerate() {
+            let question_v = stmt
+                .query_map(
+                    rusqlite::params![self.target_lang_rowid, rank_range.0, rank_range.1, config.seed as i64, config.max_questions_per_band as i64],
+                    |row| Ok(PlacementQuestion { band_index, word_rowid: row.get(0)?, word_text: row.get(1)? }),
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            band_v.push(PlacementBand { rank_range, question_v, answer_v: Vec::new() });
+        }
+        Ok(PlacementTest::new(config, band_v))
+    }
+    /// Adds the words that the placement test estimates the user knows to known_words: in each
+    /// band, the words the user said they know, then the band's most frequent words not answered
+    /// "no", up to the band's estimated known word count.  Returns how many words weren't already
+    /// known.  This is journaled as a single operation, so it can be undone.
+    pub fn apply_placement_test(&self, placement_test: &PlacementTest) -> Result<usize> {
+        let placement_estimate = placement_test.estimate();
+        let mut stmt = self.conn.prepare(&format!("{} ORDER BY freq_rank", placement::BAND_WORDS_SQL))?;
+        let mut word_rowid_v = Vec::new();
+        for (band, band_estimate) in placement_test.band_v.iter().zip(placement_estimate.band_estimate_v.iter()) {
+            let mut answer_m: HashMap<i32, bool> = HashMap::new();
+            for (question, &knows_word) in band.question_v.iter().zip(band.answer_v.iter()) {
+                answer_m.insert(question.word_rowid, knows_word);
+            }
+            let mut band_word_rowid_v: Vec<i32> = answer_m.iter().filter(|(_, &knows_word)| knows_word).map(|(&word_rowid, _)| word_rowid).collect();
+            band_word_rowid_v.sort_unstable();
+            let mut rows = stmt.query(rusqlite::params![self.target_lang_rowid, band.rank_range.0, band.rank_range.1])?;
+            while (band_word_rowid_v.len() as i64) < band_estimate.estimated_known_word_count {
+                let row = match rows.next()? {
+                    Some(row) => row,
+                    None => break,
+                };
+                let word_rowid: i32 = row.get(0)?;
+                if !answer_m.contains_key(&word_rowid) {
+                    band_word_rowid_v.push(word_rowid);
+                }
+            }
+            word_rowid_v.extend(band_word_rowid_v);
+        }
+        self.set_words_known("Apply placement test", &word_rowid_v, true)
+    }
+    fn query_sentence_word_rowid_v(&self, sentence_rowid: i32) -> Result<Vec<i32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT word_rowid FROM target_corpus_db.sentence_memberships WHERE sentence_rowid = ?1 ORDER BY word_rowid"
+        )?;
+        let word_rowid_v = stmt
+            .query_map([sentence_rowid], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_rowid_v)
+    }
+    fn query_most_frequent_word_rowid_v(&self, word_count: u32) -> Result<Vec<i32>> {
+        let mut stmt = self.conn.prepare(
+            "
+            SELECT words_rowid
+            FROM target_corpus_db.words
+            WHERE lang_rowid = ?1
+            ORDER BY freq DESC, words_rowid
+            LIMIT ?2
+            "
+        )?;
+        let word_rowid_v = stmt
+            .query_map([self.target_lang_rowid, word_count as i32], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_rowid_v)
+    }
+    fn query_listed_word_rowid_v(&self, word_str_v: &[&str]) -> Result<Vec<i32>> {
+        let word_rowid_vm = self.query_normalized_word_rowid_vm()?;
+        let mut word_rowid_v: Vec<i32> = word_str_v
+            .iter()
+            .filter_map(|word_str| word_rowid_vm.get(&normalize_word(word_str)))
+            .flatten()
+            .copied()
+            .collect();
+        word_rowid_v.sort_unstable();
+        word_rowid_v.dedup();
+        Ok(word_rowid_v)
+    }
+    /// Adds the words to or removes them from known_words in one transaction, journaling the
+    /// actual changes as a single operation, and returns their number.
+    fn set_words_known(&self, description: &str, word_rowid_v: &[i32], is_known: bool) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut change_v = Vec::new();
+        for &word_rowid in word_rowid_v.iter() {
+            if Self::set_word_known(&tx, self.target_lang_rowid, word_rowid, is_known)? {
+                change_v.push((word_rowid, is_known));
+            }
+        }
+        if !change_v.is_empty() {
+            Self::record_journal_entry(&tx, self.target_lang_rowid, description, &change_v)?;
+        }
+        tx.commit()?;
+        Ok(change_v.len())
+    }
+    /// Adds the word to or removes it from known_words, recording the event if that changed
+    /// anything, and returns whether it did.
+    fn set_word_known(conn: &rusqlite::Connection, lang_rowid: i32, word_rowid: i32, is_known: bool) -> Result<bool> {
+        let changed_count = if is_known {
+            conn.execute(
+                "INSERT OR IGNORE INTO user_db.known_words (lang_rowid, word_rowid) VALUES (?1, ?2)",
+                [lang_rowid, word_rowid],
+            )?
+        } else {
+            conn.execute(
+                "DELETE FROM user_db.known_words WHERE lang_rowid = ?1 AND word_rowid = ?2",
+                [lang_rowid, word_rowid],
+            )?
+        };
+        if changed_count > 0 {
+            Self::record_known_word_event(conn, lang_rowid, word_rowid, is_known)?;
+        }
+        Ok(changed_count > 0)
+    }
+    /// Records a user operation consisting of the given (word_rowid, is_added) changes to
+    /// known_words, so that it can be undone.  A new operation discards the undone ones, which
+    /// can then no longer be redone.
+    fn record_journal_entry(conn: &rusqlite::Connection, lang_rowid: i32, description: &str, change_v: &[(i32, bool)]) -> Result<()> {
+        conn.execute(
+            "
+            DELETE FROM user_db.journal_changes
+            WHERE journal_entry_rowid IN (
+                SELECT journal_entries_rowid FROM user_db.journal_entries WHERE lang_rowid = ?1 AND is_undone
+            )
+            ",
+            [lang_rowid],
+        )?;
+        conn.execute("DELETE FROM user_db.journal_entries WHERE lang_rowid = ?1 AND is_undone", [lang_rowid])?;
+        conn.execute(
+            "
+            INSERT INTO user_db.journal_entries (lang_rowid, description, occurred_at)
+            VALUES (?1, ?2, CAST(strftime('%s', 'now') AS INTEGER))
+            ",
+            rusqlite::params![lang_rowid, description],
+        )?;
+        let journal_entries_rowid = conn.last_insert_rowid();
+        let mut insert_journal_change = conn.prepare_cached(
+            "INSERT INTO user_db.journal_changes (journal_entry_rowid, word_rowid, is_added) VALUES (?1, ?2, ?3)"
+        )?;
+        for (word_rowid, is_added) in change_v.iter() {
+            insert_journal_change.execute(rusqlite::params![journal_entries_rowid, word_rowid, is_added])?;
+        }
+        Ok(())
+    }
+    fn query_journal_change_v(conn: &rusqlite::Connection, journal_entries_rowid: i32) -> Result<Vec<JournalChange>> {
+        let mut stmt = conn.prepare_cached(journal::JOURNAL_CHANGES_SQL)?;
+        let journal_change_v = stmt
+            .query_map([journal_entries_rowid], |row| JournalChange::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(journal_change_v)
+    }
+    /// Reverts the most recent operation that hasn't been undone, returning it, or None if
+    /// there's nothing to undo.  The journal is stored in user.db, so this works across sessions.
+    pub fn undo(&self) -> Result<Option<JournalEntry>> {
+        self.undo_or_redo(true)
+    }
+    /// Reapplies the most recently undone operation, returning it, or None if there's nothing
+    /// to redo.
+    pub fn redo(&self) -> Result<Option<JournalEntry>> {
+        self.undo_or_redo(false)
+    }
+    fn undo_or_redo(&self, is_undo: bool) -> Result<Option<JournalEntry>> {
+        let tx = self.conn.unchecked_transaction()?;
+        // Undone entries always come after the others (see record_journal_entry), so the
+        // entry to undo is the latest live one, and the entry to redo is the earliest undone one.
+        let journal_entry_o = tx.query_row(
+            &format!(
+                "
+                SELECT {}
+                FROM user_db.journal_entries
+                WHERE user_db.journal_entries.lang_rowid = ?1 AND user_db.journal_entries.is_undone = ?2
+                ORDER BY user_db.journal_entries.journal_entries_rowid {}
+                LIMIT 1
+                ",
+                journal::JOURNAL_ENTRY_COLUMNS_SQL,
+                if is_undo { "DESC" } else { "ASC" },
+            ),
+            rusqlite::params![self.target_lang_rowid, !is_undo],
+            |row| JournalEntry::try_from(row),
+        ).optional()?;
+        let mut journal_entry = match journal_entry_o {
+            Some(journal_entry) => journal_entry,
+            None => return Ok(None),
+        };
+        journal_entry.change_v = Self::query_journal_change_v(&tx, journal_entry.journal_entries_rowid)?;
+        if is_undo {
+            for journal_change in journal_entry.change_v.iter().rev() {
+                Self::set_word_known(&tx, self.target_lang_rowid, journal_change.word_rowid, !journal_change.is_added)?;
+            }
+        } else {
+            for journal_change in journal_entry.change_v.iter() {
+                Self::set_word_known(&tx, self.target_lang_rowid, journal_change.word_rowid, journal_change.is_added)?;
+            }
+        }
+        tx.execute(
+            "UPDATE user_db.journal_entries SET is_undone = ?1 WHERE journal_entries_rowid = ?2",
+            rusqlite::params![is_undo, journal_entry.journal_entries_rowid],
+        )?;
+        tx.commit()?;
+        journal_entry.is_undone = is_undo;
+        Ok(Some(journal_entry))
+    }
+    /// Returns the most recent operations (including undone ones that can still be redone),
+    /// most recent first.
+    pub fn query_journal_entry_v(&self, limit: u32) -> Result<Vec<JournalEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT {}
+            FROM user_db.journal_entries
+            WHERE user_db.journal_entries.lang_rowid = ?1
+            ORDER BY user_db.journal_entries.journal_entries_rowid DESC
+            LIMIT ?2
+            ",
+            journal::JOURNAL_ENTRY_COLUMNS_SQL,
+        ))?;
+        let mut journal_entry_v = stmt
+            .query_map([self.target_lang_rowid, limit as i32], |row| JournalEntry::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for journal_entry in journal_entry_v.iter_mut() {
+            journal_entry.change_v = Self::query_journal_change_v(&self.conn, journal_entry.journal_entries_rowid)?;
+        }
+        Ok(journal_entry_v)
+    }
+    /// Every actual change to known_words should be recorded with this, so that the history
+    /// of the user's vocabulary can be reconstructed.
+    fn record_known_word_event(conn: &rusqlite::Connection, lang_rowid: i32, word_rowid: i32, is_added: bool) -> Result<()> {
+        conn.execute(
+            "
+            INSERT INTO user_db.known_word_events (lang_rowid, word_rowid, is_added, occurred_at)
+            VALUES (?1, ?2, ?3, CAST(strftime('%s', 'now') AS INTEGER))
+            ",
+            rusqlite::params![lang_rowid, word_rowid, is_added],
+        )?;
+        Ok(())
+    }
+    /// (Re)computes the target language corpus's n-grams, returning the number selected.  The
+    /// corpus import already does this with the default NgramSelection.
+    pub fn compute_ngrams(&mut self, ngram_selection: &NgramSelection) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let ngram_count = ngrams::populate_ngrams(&tx, "target_corpus_db", self.target_lang_rowid, ngram_selection)?;
+        tx.commit()?;
+        Ok(ngram_count)
+    }
+    /// Returns the most frequent n-grams, with whether each is known.
+    pub fn query_ngram_v(&self, limit: u32) -> Result<Vec<NgramWithKnown>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT
+                {},
+                target_corpus_db.ngrams.ngrams_rowid IN (
+                    SELECT user_db.known_ngrams.ngram_rowid
+                    FROM user_db.known_ngrams
+                    WHERE user_db.known_ngrams.lang_rowid = ?1
+                ) AS ngram_is_known
+            FROM target_corpus_db.ngrams
+            WHERE target_corpus_db.ngrams.lang_rowid = ?1 AND target_corpus_db.ngrams.freq > 0
+            ORDER BY target_corpus_db.ngrams.freq DESC, target_corpus_db.ngrams.ngrams_rowid ASC
+            LIMIT ?2
+            ",
+            ngrams::NGRAM_COLUMNS_SQL,
+        ))?;
+        let ngram_with_known_v = stmt
+            .query_map([self.target_lang_rowid, limit as i32], |row| NgramWithKnown::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ngram_with_known_v)
+    }
+    /// Returns the n-grams occurring in the given sentence, longest first.
+    pub fn query_sentence_ngram_v(&self, sentence_rowid: i32) -> Result<Vec<NgramWithKnown>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT DISTINCT
+                {},
+                target_corpus_db.ngrams.ngrams_rowid IN (
+                    SELECT user_db.known_ngrams.ngram_rowid
+                    FROM user_db.known_ngrams
+                    WHERE user_db.known_ngrams.lang_rowid = ?1
+                ) AS ngram_is_known
+            FROM target_corpus_db.ngram_sentence_memberships
+            INNER JOIN target_corpus_db.ngrams ON target_corpus_db.ngrams.ngrams_rowid = target_corpus_db.ngram_sentence_memberships.ngram_rowid
+            WHERE target_corpus_db.ngram_sentence_memberships.sentence_rowid = ?2
+            ORDER BY target_corpus_db.ngrams.word_count DESC, target_corpus_db.ngrams.freq DESC
+            ",
+            ngrams::NGRAM_COLUMNS_SQL,
+        ))?;
+        let ngram_with_known_v = stmt
+            .query_map([self.target_lang_rowid, sentence_rowid], |row| NgramWithKnown::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ngram_with_known_v)
+    }
+    /// Returns the known n-grams, including ones that are no longer selected (with freq 0).
+    pub fn query_known_ngram_v(&self) -> Result<Vec<NgramWithKnown>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT {}, 1
+            FROM user_db.known_ngrams
+            INNER JOIN target_corpus_db.ngrams ON target_corpus_db.ngrams.ngrams_rowid = user_db.known_ngrams.ngram_rowid
+            WHERE user_db.known_ngrams.lang_rowid = ?1
+            ORDER BY user_db.known_ngrams.known_ngrams_rowid
+            ",
+            ngrams::NGRAM_COLUMNS_SQL,
+        ))?;
+        let ngram_with_known_v = stmt
+            .query_map([self.target_lang_rowid], |row| NgramWithKnown::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ngram_with_known_v)
+    }
+    pub fn add_known_ngram(&self, ngram_rowid: i32) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO user_db.known_ngrams (lang_rowid, ngram_rowid) VALUES (?1, ?2)",
+            [self.target_lang_rowid, ngram_rowid],
+        )?;
+        Ok(())
+    }
+    pub fn remove_known_ngram(&self, ngram_rowid: i32) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM user_db.known_ngrams WHERE lang_rowid = ?1 AND ngram_rowid = ?2",
+            [self.target_lang_rowid, ngram_rowid],
+        )?;
+        Ok(())
+    }
+    /// Returns whether the words of the target language corpus have been classified, even if
+    /// none turned out to belong to a class.  Returns false for corpus DBs created by older
+    /// versions, which need compute_word_classes.
+    pub fn has_word_classes(&self) -> Result<bool> {
+        computed_tables::table_is_computed(&self.conn, "target_corpus_db", "word_classes")
+    }
+    /// (Re)classifies the words of the target language corpus (see WordClass).  The corpus
+    /// import already does this, except for the proper nouns of languages that capitalize all
+    /// nouns, which are found via the translations.
+    pub fn compute_word_classes(&mut self) -> Result<()> {
+        let mut translation_text_vm: HashMap<i32, Vec<String>> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare("
+                SELECT translations_db.translations.target_lang_sentence_rowid, reference_corpus_db.sentences.text
+                FROM translations_db.translations
+                INNER JOIN reference_corpus_db.sentences ON reference_corpus_db.sentences.sentences_rowid = translations_db.translations.reference_lang_sentence_rowid
+            ")?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                translation_text_vm.entry(row.get(0)?).or_default().push(row.get(1)?);
+            }
+        }
+        let tx = self.conn.transaction()?;
+        word_classes::populate_word_classes(
+            &tx,
+            "target_corpus_db",
+            self.target_lang_rowid,
+            self.db_hub_config.target_lang.short,
+            Some(&translation_text_vm),
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+    /// Returns the class of the given word, if any; the user's names take precedence over the
+    /// corpus's classes.
+    pub fn query_word_class_o(&self, word_rowid: i32) -> Result<Option<WordClass>> {
+        Ok(self.conn.query_row(
+            "
+            SELECT
+                CASE
+                    WHEN EXISTS (SELECT 1 FROM user_db.names WHERE lang_rowid = ?1 AND word_rowid = ?2) THEN 'name'
+                    ELSE (SELECT class FROM target_corpus_db.word_classes WHERE word_rowid = ?2)
+                END
+            ",
+            [self.target_lang_rowid, word_rowid],
+            |row| row.get(0),
+        )?)
+    }
+    /// (Re)computes the components of the target language corpus's compound words, returning
+    /// the number of compounds found.  This is mainly useful for languages like German that
+    /// write compounds as single words, so it isn't done by the corpus import.
+    pub fn split_compounds(&mut self, compound_splitting: &CompoundSplitting) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let compound_count = compounds::populate_word_components(&tx, "target_corpus_db", self.target_lang_rowid, compound_splitting)?;
+        computed_tables::mark_table_computed(&tx, "target_corpus_db", "word_components")?;
+        tx.commit()?;
+        Ok(compound_count)
+    }
+    /// Returns true if split_compounds has been run, even if it found no compounds.
+    pub fn has_compounds(&self) -> Result<bool> {
+        computed_tables::table_is_computed(&self.conn, "target_corpus_db", "word_components")
+    }
+    /// Returns the components of the given word in order, or nothing if it's not a compound.
+    pub fn query_compound_component_with_text_v(&self, word_rowid: i32) -> Result<Vec<CompoundComponentWithText>> {
+        let mut stmt = self.conn.prepare_cached("
+            SELECT
+                target_corpus_db.word_components.position,
+                target_corpus_db.word_components.component_word_rowid,
+                target_corpus_db.words.text,
+                user_db.known_words.word_rowid IS NOT NULL AS component_is_known
+            FROM target_corpus_db.word_components
+            INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = target_corpus_db.word_components.component_word_rowid
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.word_components.component_word_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE target_corpus_db.word_components.word_rowid = ?2
+            ORDER BY target_corpus_db.word_components.position
+        ")?;
+        let compound_component_with_text_v = stmt
+            .query_map([self.target_lang_rowid, word_rowid], |row| CompoundComponentWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(compound_component_with_text_v)
+    }
+    /// Adds a word to the user's list of names (see WordClass::Name).
+    pub fn add_name(&self, word_rowid: i32) -> Result<()> {
         self.conn.execute(
-            "INSERT OR IGNORE INTO user_db.known_words (lang_rowid, word_rowid) VALUES (?1, ?2)",
+            "INSERT OR IGNORE INTO user_db.names (lang_rowid, word_rowid) VALUES (?1, ?2)",
             [self.target_lang_rowid, word_rowid],
         )?;
         Ok(())
     }
-    pub fn remove_known_word(&self, word_rowid: i32) -> Result<()> {
+    pub fn remove_name(&self, word_rowid: i32) -> Result<()> {
         self.conn.execute(
-            "DELETE FROM user_db.known_words WHERE lang_rowid = ?1 AND word_rowid = ?2",
+            "DELETE FROM user_db.names WHERE lang_rowid = ?1 AND word_rowid = ?2",
             [self.target_lang_rowid, word_rowid],
         )?;
         Ok(())
     }
+    pub fn query_name_with_text_v(&self) -> Result<Vec<NameWithText>> {
+        let mut stmt = self.conn.prepare("
+            SELECT
+                user_db.names.names_rowid,
+                user_db.names.word_rowid,
+                target_corpus_db.words.text
+            FROM user_db.names
+            INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = user_db.names.word_rowid
+            WHERE user_db.names.lang_rowid = ?1
+            ORDER BY target_corpus_db.words.text
+        ")?;
+        let name_with_text_v = stmt
+            .query_map([self.target_lang_rowid], |row| NameWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(name_with_text_v)
+    }
+    /// Creates or replaces the user's note on the given word.
+    pub fn set_word_note(&self, word_rowid: i32, word_note: &WordNote) -> Result<WordNoteRow> {
+        Ok(self.conn.query_row(
+            &format!(
+                "
+                INSERT INTO user_db.word_notes (lang_rowid, word_rowid, note, user_gloss, tags, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, CAST(strftime('%s', 'now') AS INTEGER), CAST(strftime('%s', 'now') AS INTEGER))
+                ON CONFLICT (lang_rowid, word_rowid) DO UPDATE SET
+                    note = excluded.note,
+                    user_gloss = excluded.user_gloss,
+                    tags = excluded.tags,
+                    updated_at = excluded.updated_at
+                RETURNING {}
+                ",
+                word_notes::WORD_NOTE_COLUMNS_SQL.replace("user_db.word_notes.", ""),
+            ),
+            rusqlite::params![
+                self.target_lang_rowid,
+                word_rowid,
+                word_note.note,
+                word_note.user_gloss,
+                word_note.tags_text()?,
+            ],
+            |row| WordNoteRow::try_from(row),
+        )?)
+    }
+    /// Returns true iff there was a note to delete.
+    pub fn delete_word_note(&self, word_rowid: i32) -> Result<bool> {
+        let changed_count = self.conn.execute(
+            "DELETE FROM user_db.word_notes WHERE lang_rowid = ?1 AND word_rowid = ?2",
+            [self.target_lang_rowid, word_rowid],
+        )?;
+        Ok(changed_count > 0)
+    }
+    pub fn query_word_note_o(&self, word_rowid: i32) -> Result<Option<WordNoteRow>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "
+            SELECT {}
+            FROM user_db.word_notes
+            WHERE user_db.word_notes.lang_rowid = ?1 AND user_db.word_notes.word_rowid = ?2
+            ",
+            word_notes::WORD_NOTE_COLUMNS_SQL,
+        ))?;
+        Ok(stmt
+            .query_row([self.target_lang_rowid, word_rowid], |row| WordNoteRow::try_from(row))
+            .optional()?)
+    }
+    /// Returns all of the user's word notes, most recently updated first.  If tag_o is
+    /// specified, only notes with that tag are returned.
+    pub fn query_word_note_with_text_v(&self, tag_o: Option<&str>) -> Result<Vec<WordNoteWithText>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT {}, target_corpus_db.words.text
+            FROM user_db.word_notes
+            INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = user_db.word_notes.word_rowid
+            WHERE
+                user_db.word_notes.lang_rowid = ?1
+                AND
+                (?2 IS NULL OR instr(' ' || user_db.word_notes.tags || ' ', ' ' || ?2 || ' ') > 0)
+            ORDER BY user_db.word_notes.updated_at DESC, user_db.word_notes.word_notes_rowid DESC
+            ",
+            word_notes::WORD_NOTE_COLUMNS_SQL,
+        ))?;
+        let word_note_with_text_v = stmt
+            .query_map(rusqlite::params![self.target_lang_rowid, tag_o], |row| WordNoteWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_note_with_text_v)
+    }
+    /// Full-text search of notes, user glosses and tags, ignoring case and accents.  Notes
+    /// containing all of the words of text are returned, best match first; the last word also
+    /// matches as a prefix.
+    pub fn search_word_notes(&self, text: &str) -> Result<Vec<WordNoteWithText>> {
+        let fts_query = word_notes::word_note_fts_query(text);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "
+            SELECT {}, target_corpus_db.words.text
+            FROM user_db.word_notes_fts
+            INNER JOIN user_db.word_notes ON user_db.word_notes.word_notes_rowid = user_db.word_notes_fts.rowid
+            INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = user_db.word_notes.word_rowid
+            WHERE
+                word_notes_fts MATCH ?2
+                AND
+                user_db.word_notes.lang_rowid = ?1
+            ORDER BY bm25(word_notes_fts)
+            ",
+            word_notes::WORD_NOTE_COLUMNS_SQL,
+        ))?;
+        let word_note_with_text_v = stmt
+            .query_map(rusqlite::params![self.target_lang_rowid, fts_query], |row| WordNoteWithText::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(word_note_with_text_v)
+    }
+    /// Exports the user's data for the target language, referring to words by their text.
+    pub fn export_user_data(&self) -> Result<UserDataBundle> {
+        self.export_user_data_from("user_db", "target_corpus_db")
+    }
+    /// Exports the data for the target language from another user DB file, e.g. one copied from
+    /// another machine, so that it can be merged (see merge_user_data).  The other user DB
+    /// refers to words and n-grams by their rowids in its own target language corpus DB, which
+    /// may be a different version than this one, so its words are looked up in that file.
+    pub fn export_user_data_from_user_db(&self, user_db_p: &Path, corpus_db_p: &Path) -> Result<UserDataBundle> {
+        for p in [user_db_p, corpus_db_p].iter() {
+            if !p.exists() {
+                Err(anyhow::anyhow!("{:?} doesn't exist", p))?;
+            }
+        }
+        self.conn.execute("ATTACH DATABASE ?1 AS other_user_db", [user_db_p.to_string_lossy()])?;
+        if let Err(e) = self.conn.execute("ATTACH DATABASE ?1 AS other_corpus_db", [corpus_db_p.to_string_lossy()]) {
+            self.conn.execute("DETACH DATABASE other_user_db", [])?;
+            Err(e)?;
+        }
+        let user_data_bundle_r = self.export_user_data_from("other_user_db", "other_corpus_db");
+        self.conn.execute("DETACH DATABASE other_corpus_db", [])?;
+        self.conn.execute("DETACH DATABASE other_user_db", [])?;
+        user_data_bundle_r
+    }
+    // Exports from the user DB attached under the given schema name, whose words and n-grams
+    // are those of the corpus DB attached under the given schema name.
+    fn export_user_data_from(&self, user_schema: &str, corpus_schema: &str) -> Result<UserDataBundle> {
+        let prepare = |sql: &str| self.conn.prepare(&sql.replace("{user}", user_schema).replace("{corpus}", corpus_schema));
+        let known_word_text_v = prepare("
+                SELECT {corpus}.words.text
+                FROM {user}.known_words
+                INNER JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.known_words.word_rowid
+                WHERE {user}.known_words.lang_rowid = ?1
+                ORDER BY {corpus}.words.text
+            ")?
+            .query_map([self.target_lang_rowid], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let known_word_event_v = prepare("
+                SELECT
+                    {corpus}.words.text,
+                    {user}.known_word_events.is_added,
+                    {user}.known_word_events.occurred_at
+                FROM {user}.known_word_events
+                INNER JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.known_word_events.word_rowid
+                WHERE {user}.known_word_events.lang_rowid = ?1
+                ORDER BY {user}.known_word_events.occurred_at, {user}.known_word_events.known_word_events_rowid
+            ")?
+            .query_map([self.target_lang_rowid], |row| {
+                Ok(BundleKnownWordEvent { word_text: row.get(0)?, is_added: row.get(1)?, occurred_at: row.get(2)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let seen_word_v = prepare("
+                SELECT
+                    {corpus}.words.text,
+                    {user}.seen_words.last_seen_at,
+                    {user}.seen_words.seen_count
+                FROM {user}.seen_words
+                INNER JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.seen_words.word_rowid
+                WHERE {user}.seen_words.lang_rowid = ?1
+                ORDER BY {corpus}.words.text
+            ")?
+            .query_map([self.target_lang_rowid], |row| {
+                Ok(BundleSeenWord { word_text: row.get(0)?, last_seen_at: row.get(1)?, seen_count: row.get(2)? })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut word_set_v: Vec<BundleWordSet> = Vec::new();
+        {
+            let mut stmt = prepare("
+                SELECT {user}.word_sets.name, {corpus}.words.text
+                FROM {user}.word_sets
+                LEFT JOIN {user}.word_set_members ON {user}.word_set_members.word_set_rowid = {user}.word_sets.word_sets_rowid
+                LEFT JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.word_set_members.word_rowid
+                WHERE {user}.word_sets.lang_rowid = ?1
+                ORDER BY {user}.word_sets.name, {user}.word_set_members.word_set_members_rowid
+            ")?;
+            let mut rows = stmt.query([self.target_lang_rowid])?;
+            while let Some(row) = rows.next()? {
+                let name: String = row.get(0)?;
+                if word_set_v.last().map(|word_set| word_set.name != name).unwrap_or(true) {
+                    word_set_v.push(BundleWordSet { name, word_text_v: Vec::new() });
+                }
+                if let Some(word_text) = row.get::<_, Option<String>>(1)? {
+                    word_set_v.last_mut().unwrap().word_text_v.push(word_text);
+                }
+            }
+        }
+        let word_note_v = prepare("
+                SELECT
+                    {corpus}.words.text,
+                    {user}.word_notes.note,
+                    {user}.word_notes.user_gloss,
+                    {user}.word_notes.tags,
+                    {user}.word_notes.created_at,
+                    {user}.word_notes.updated_at
+                FROM {user}.word_notes
+                INNER JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.word_notes.word_rowid
+                WHERE {user}.word_notes.lang_rowid = ?1
+                ORDER BY {corpus}.words.text
+            ")?
+            .query_map([self.target_lang_rowid], |row| {
+                let tags: String = row.get(3)?;
+                Ok(BundleWordNote {
+                    word_text: row.get(0)?,
+                    word_note: WordNote {
+                        note: row.get(1)?,
+                        user_gloss: row.get(2)?,
+                        tag_v: tags.split_whitespace().map(str::to_string).collect(),
+                    },
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let name_word_text_v = prepare("
+                SELECT {corpus}.words.text
+                FROM {user}.names
+                INNER JOIN {corpus}.words ON {corpus}.words.words_rowid = {user}.names.word_rowid
+                WHERE {user}.names.lang_rowid = ?1
+                ORDER BY {corpus}.words.text
+            ")?
+            .query_map([self.target_lang_rowid], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // Corpus DBs created by older versions have no n-grams, and so no known n-grams either.
+        let has_ngrams: bool = self.conn.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = 'ngrams')", corpus_schema),
+            [],
+            |row| row.get(0),
+        )?;
+        let known_ngram_text_v = if has_ngrams {
+            prepare("
+                    SELECT {corpus}.ngrams.text
+                    FROM {user}.known_ngrams
+                    INNER JOIN {corpus}.ngrams ON {corpus}.ngrams.ngrams_rowid = {user}.known_ngrams.ngram_rowid
+                    WHERE {user}.known_ngrams.lang_rowid = ?1
+                    ORDER BY {corpus}.ngrams.text
+                ")?
+                .query_map([self.target_lang_rowid], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?
+        } else {
+            Vec::new()
+        };
+        let exported_at: i64 = self.conn.query_row("SELECT CAST(strftime('%s', 'now') AS INTEGER)", [], |row| row.get(0))?;
+
+        Ok(UserDataBundle {
+            lang_short: self.db_hub_config.target_lang.short.to_string(),
+            exported_at,
+            known_word_text_v,
+            known_word_event_v,
+            seen_word_v,
+            word_set_v,
+            word_note_v,
+            name_word_text_v,
+            known_ngram_text_v,
+        })
+    }
+    /// Merges exported user data into the user's data for the target language, resolving words
+    /// against the current corpus by their text (falling back to normalize_word, so e.g. "haus"
+    /// matches "Haus" if the corpus has no "haus").  Data already present is kept: known words,
+    /// names, known n-grams and word set members are unioned, events already present aren't
+    /// duplicated, seen word counts take the maximum, and notes are replaced only by more
+    /// recently updated ones.  The known words added are journaled as a single entry, so that
+    /// the import can be undone, but no known word events are recorded for them, since the
+    /// bundle brings its own known word history.
+    pub fn import_user_data(&mut self, user_data_bundle: &UserDataBundle) -> Result<UserDataImportReport> {
+        Ok(self.import_or_merge_user_data(user_data_bundle, false, false)?.import_report)
+    }
+    /// Like import_user_data, except that known words are merged last-writer-wins, e.g. to
+    /// combine the user data of two machines: where the bundle and the user's data disagree on
+    /// whether a word is known, the side whose known word history has the later event for that
+    /// word wins (without any history, knowing wins).  Removals of names, known n-grams and word
+    /// set members aren't recorded, so those are still unioned.  The known word changes are
+    /// journaled like import_user_data's.  A dry run only reports what would change.
+    pub fn merge_user_data(&mut self, user_data_bundle: &UserDataBundle, is_dry_run: bool) -> Result<UserDataMergeReport> {
+        self.import_or_merge_user_data(user_data_bundle, true, is_dry_run)
+    }
+    /// Merges the data of another user DB file, given the target language corpus DB file that
+    /// it was used with (see merge_user_data and export_user_data_from_user_db).
+    pub fn merge_user_db(&mut self, user_db_p: &Path, corpus_db_p: &Path, is_dry_run: bool) -> Result<UserDataMergeReport> {
+        let user_data_bundle = self.export_user_data_from_user_db(user_db_p, corpus_db_p)?;
+        self.merge_user_data(&user_data_bundle, is_dry_run)
+    }
+    fn import_or_merge_user_data(&mut self, user_data_bundle: &UserDataBundle, is_merge: bool, is_dry_run: bool) -> Result<UserDataMergeReport> {
+        if user_data_bundle.lang_short != self.db_hub_config.target_lang.short {
+            Err(anyhow::anyhow!(
+                "user data is for language {:?}, but the target language is {:?}",
+                user_data_bundle.lang_short,
+                self.db_hub_config.target_lang.short,
+            ))?;
+        }
+        let word_rowid_m: HashMap<String, i32> = self.conn
+            .prepare("SELECT text, words_rowid FROM target_corpus_db.words WHERE lang_rowid = ?1")?
+            .query_map([self.target_lang_rowid], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let normalized_word_rowid_vm = self.query_normalized_word_rowid_vm()?;
+        let mut unmatched_word_text_s: BTreeSet<String> = BTreeSet::new();
+        let mut resolve_word = |word_text: &str| -> Vec<i32> {
+            let word_rowid_v = match word_rowid_m.get(word_text) {
+                Some(&word_rowid) => vec![word_rowid],
+                None => normalized_word_rowid_vm.get(&normalize_word(word_text)).cloned().unwrap_or_default(),
+            };
+            if word_rowid_v.is_empty() {
+                unmatched_word_text_s.insert(word_text.to_string());
+            }
+            word_rowid_v
+        };
+
+        let word_text_m: HashMap<i32, String> = word_rowid_m.iter().map(|(word_text, &word_rowid)| (word_rowid, word_text.clone())).collect();
+
+        let mut merge_report = UserDataMergeReport { is_dry_run, ..UserDataMergeReport::default() };
+        let mut report = UserDataImportReport::default();
+        let mut known_word_change_v: Vec<(i32, bool)> = Vec::new();
+        let lang_rowid = self.target_lang_rowid;
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert_known_word = tx.prepare(
+                "INSERT OR IGNORE INTO user_db.known_words (lang_rowid, word_rowid) VALUES (?1, ?2)"
+            )?;
+            let bundle_known_word_rowid_s: HashSet<i32> = user_data_bundle.known_word_text_v
+                .iter()
+                .flat_map(|word_text| resolve_word(word_text))
+                .collect();
+            if is_merge {
+                // This has to happen before the bundle's events are added to the history.
+                let mut bundle_last_occurred_at_m: HashMap<i32, i64> = HashMap::new();
+                for known_word_event in user_data_bundle.known_word_event_v.iter() {
+                    for word_rowid in resolve_word(&known_word_event.word_text) {
+                        let last_occurred_at = bundle_last_occurred_at_m.entry(word_rowid).or_insert(known_word_event.occurred_at);
+                        *last_occurred_at = (*last_occurred_at).max(known_word_event.occurred_at);
+                    }
+                }
+                let mut word_rowid_v: Vec<i32> = bundle_known_word_rowid_s.iter().chain(bundle_last_occurred_at_m.keys()).copied().collect();
+                word_rowid_v.sort_unstable();
+                word_rowid_v.dedup();
+                let mut query_known_word_state = tx.prepare("
+                    SELECT
+                        EXISTS (SELECT 1 FROM user_db.known_words WHERE lang_rowid = ?1 AND word_rowid = ?2),
+                        (SELECT MAX(occurred_at) FROM user_db.known_word_events WHERE lang_rowid = ?1 AND word_rowid = ?2)
+                ")?;
+                let mut delete_known_word = tx.prepare(
+                    "DELETE FROM user_db.known_words WHERE lang_rowid = ?1 AND word_rowid = ?2"
+                )?;
+                for word_rowid in word_rowid_v.into_iter() {
+                    let (word_is_known, last_occurred_at_o): (bool, Option<i64>) =
+                        query_known_word_state.query_row([lang_rowid, word_rowid], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                    let bundle_word_is_known = bundle_known_word_rowid_s.contains(&word_rowid);
+                    if word_is_known == bundle_word_is_known {
+                        continue;
+                    }
+                    let bundle_wins = match (bundle_last_occurred_at_m.get(&word_rowid).copied(), last_occurred_at_o) {
+                        (None, None) => bundle_word_is_known,
+                        (bundle_last_occurred_at_o, last_occurred_at_o) => bundle_last_occurred_at_o > last_occurred_at_o,
+                    };
+                    if !bundle_wins {
+                        continue;
+                    }
+                    if bundle_word_is_known {
+                        insert_known_word.execute([lang_rowid, word_rowid])?;
+                        merge_report.added_known_word_text_v.push(word_text_m[&word_rowid].clone());
+                    } else {
+                        delete_known_word.execute([lang_rowid, word_rowid])?;
+                        merge_report.removed_known_word_text_v.push(word_text_m[&word_rowid].clone());
+                    }
+                    known_word_change_v.push((word_rowid, bundle_word_is_known));
+                }
+            } else {
+                for &word_rowid in bundle_known_word_rowid_s.iter() {
+                    if insert_known_word.execute([lang_rowid, word_rowid])? > 0 {
+                        merge_report.added_known_word_text_v.push(word_text_m[&word_rowid].clone());
+                        known_word_change_v.push((word_rowid, true));
+                    }
+                }
+            }
+            report.known_word_count = merge_report.added_known_word_text_v.len();
+            if !known_word_change_v.is_empty() {
+                known_word_change_v.sort_unstable();
+                let description = if is_merge { "Merge user data" } else { "Import user data" };
+                Self::record_journal_entry(&tx, lang_rowid, description, &known_word_change_v)?;
+            }
+            let mut insert_known_word_event = tx.prepare("
+                INSERT INTO user_db.known_word_events (lang_rowid, word_rowid, is_added, occurred_at)
+                SELECT ?1, ?2, ?3, ?4
+                WHERE NOT EXISTS (
+                    SELECT 1
+                    FROM user_db.known_word_events
+                    WHERE lang_rowid = ?1 AND word_rowid = ?2 AND is_added = ?3 AND occurred_at = ?4
+                )
+            ")?;
+            for known_word_event in user_data_bundle.known_word_event_v.iter() {
+                for word_rowid in resolve_word(&known_word_event.word_text) {
+                    report.known_word_event_count += insert_known_word_event.execute(rusqlite::params![
+                        lang_rowid,
+                        word_rowid,
+                        known_word_event.is_added,
+                        known_word_event.occurred_at,
+                    ])?;
+                }
+            }
+            let mut upsert_seen_word = tx.prepare("
+                INSERT INTO user_db.seen_words (lang_rowid, word_rowid, last_seen_at, seen_count)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT (lang_rowid, word_rowid) DO UPDATE SET
+                    last_seen_at = MAX(last_seen_at, excluded.last_seen_at),
+                    seen_count = MAX(seen_count, excluded.seen_count)
+                WHERE excluded.last_seen_at > last_seen_at OR excluded.seen_count > seen_count
+            ")?;
+            for seen_word in user_data_bundle.seen_word_v.iter() {
+                for word_rowid in resolve_word(&seen_word.word_text) {
+                    report.seen_word_count += upsert_seen_word.execute(rusqlite::params![
+                        lang_rowid,
+                        word_rowid,
+                        seen_word.last_seen_at,
+                        seen_word.seen_count,
+                    ])?;
+                }
+            }
+            let mut insert_word_set_member = tx.prepare(
+                "INSERT OR IGNORE INTO user_db.word_set_members (word_set_rowid, word_rowid) VALUES (?1, ?2)"
+            )?;
+            for word_set in user_data_bundle.word_set_v.iter() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO user_db.word_sets (lang_rowid, name) VALUES (?1, ?2)",
+                    rusqlite::params![lang_rowid, word_set.name],
+                )?;
+                let word_sets_rowid: i32 = tx.query_row(
+                    "SELECT word_sets_rowid FROM user_db.word_sets WHERE lang_rowid = ?1 AND name = ?2",
+                    rusqlite::params![lang_rowid, word_set.name],
+                    |row| row.get(0),
+                )?;
+                for word_text in word_set.word_text_v.iter() {
+                    for word_rowid in resolve_word(word_text) {
+                        report.word_set_member_count += insert_word_set_member.execute([word_sets_rowid, word_rowid])?;
+                    }
+                }
+            }
+            let mut upsert_word_note = tx.prepare("
+                INSERT INTO user_db.word_notes (lang_rowid, word_rowid, note, user_gloss, tags, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT (lang_rowid, word_rowid) DO UPDATE SET
+                    note = excluded.note,
+                    user_gloss = excluded.user_gloss,
+                    tags = excluded.tags,
+                    updated_at = excluded.updated_at
+                WHERE excluded.updated_at > updated_at
+            ")?;
+            for word_note in user_data_bundle.word_note_v.iter() {
+                for word_rowid in resolve_word(&word_note.word_text) {
+                    report.word_note_count += upsert_word_note.execute(rusqlite::params![
+                        lang_rowid,
+                        word_rowid,
+                        word_note.word_note.note,
+                        word_note.word_note.user_gloss,
+                        word_note.word_note.tags_text()?,
+                        word_note.created_at,
+                        word_note.updated_at,
+                    ])?;
+                }
+            }
+            let mut insert_name = tx.prepare(
+                "INSERT OR IGNORE INTO user_db.names (lang_rowid, word_rowid) VALUES (?1, ?2)"
+            )?;
+            for word_text in user_data_bundle.name_word_text_v.iter() {
+                for word_rowid in resolve_word(word_text) {
+                    report.name_count += insert_name.execute([lang_rowid, word_rowid])?;
+                }
+            }
+            let mut insert_known_ngram = tx.prepare("
+                INSERT OR IGNORE INTO user_db.known_ngrams (lang_rowid, ngram_rowid)
+                SELECT ?1, ngrams_rowid FROM target_corpus_db.ngrams WHERE lang_rowid = ?1 AND text = ?2
+            ")?;
+            let mut ngram_exists = tx.prepare(
+                "SELECT 1 FROM target_corpus_db.ngrams WHERE lang_rowid = ?1 AND text = ?2"
+            )?;
+            for ngram_text in user_data_bundle.known_ngram_text_v.iter() {
+                if ngram_exists.exists(rusqlite::params![lang_rowid, ngram_text])? {
+                    report.known_ngram_count += insert_known_ngram.execute(rusqlite::params![lang_rowid, ngram_text])?;
+                } else {
+                    report.unmatched_ngram_text_v.push(ngram_text.clone());
+                }
+            }
+        }
+        if is_dry_run {
+            tx.rollback()?;
+        } else {
+            tx.commit()?;
+        }
+
+        report.unmatched_word_text_v = unmatched_word_text_s.into_iter().collect();
+        report.unmatched_ngram_text_v.sort();
+        report.unmatched_ngram_text_v.dedup();
+        merge_report.added_known_word_text_v.sort();
+        merge_report.removed_known_word_text_v.sort();
+        merge_report.import_report = report;
+        Ok(merge_report)
+    }
+    /// Records today's statistics (see stats) and frontier size, replacing any snapshot
+    /// recorded earlier today.  Clients should call this e.g. once per session.
+    pub fn record_progress_snapshot(&self) -> Result<ProgressSnapshotRow> {
+        let stats = self.stats()?;
+        let frontier_sentence_count = self.query_word_frontier_count(&FrontierQuery::new(Range(1, 1)))?;
+        Ok(self.conn.query_row(
+            "
+            INSERT INTO user_db.progress_snapshots (lang_rowid, day, known_word_count, type_coverage, token_coverage, frontier_sentence_count, recorded_at)
+            VALUES (?1, date('now', 'localtime'), ?2, ?3, ?4, ?5, CAST(strftime('%s', 'now') AS INTEGER))
+            ON CONFLICT (lang_rowid, day) DO UPDATE SET
+                known_word_count = excluded.known_word_count,
+                type_coverage = excluded.type_coverage,
+                token_coverage = excluded.token_coverage,
+                frontier_sentence_count = excluded.frontier_sentence_count,
+                recorded_at = excluded.recorded_at
+            RETURNING progress_snapshots_rowid, lang_rowid, day, known_word_count, type_coverage, token_coverage, frontier_sentence_count, recorded_at
+            ",
+            rusqlite::params![
+                self.target_lang_rowid,
+                stats.known_word_count,
+                stats.type_coverage,
+                stats.token_coverage,
+                frontier_sentence_count,
+            ],
+            |row| ProgressSnapshotRow::try_from(row),
+        )?)
+    }
+    /// Returns the recorded daily snapshots, oldest first.
+    pub fn query_progress_snapshot_v(&self) -> Result<Vec<ProgressSnapshotRow>> {
+        let mut stmt = self.conn.prepare("
+            SELECT progress_snapshots_rowid, lang_rowid, day, known_word_count, type_coverage, token_coverage, frontier_sentence_count, recorded_at
+            FROM user_db.progress_snapshots
+            WHERE lang_rowid = ?1
+            ORDER BY day
+        ")?;
+        let progress_snapshot_row_v = stmt
+            .query_map([self.target_lang_rowid], |row| ProgressSnapshotRow::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(progress_snapshot_row_v)
+    }
+    /// Returns the number of known words added, removed and known on each day that had changes,
+    /// oldest first.
+    pub fn query_known_word_timeline_v(&self) -> Result<Vec<KnownWordTimelinePoint>> {
+        let mut stmt = self.conn.prepare(progress::KNOWN_WORD_TIMELINE_SQL)?;
+        let known_word_timeline_point_v = stmt
+            .query_map([self.target_lang_rowid], |row| KnownWordTimelinePoint::try_from(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(known_word_timeline_point_v)
+    }
+    /// Records that the words of the given sentence were just seen by the user, which is what
+    /// FrontierRanking::UnknownWordRecency ranks by.
+    pub fn mark_sentence_seen(&self, sentence_rowid: i32) -> Result<()> {
+        self.conn.execute(
+            "
+            INSERT INTO user_db.seen_words (lang_rowid, word_rowid, last_seen_at, seen_count)
+            SELECT ?1, target_corpus_db.sentence_memberships.word_rowid, CAST(strftime('%s', 'now') AS INTEGER), 1
+            FROM target_corpus_db.sentence_memberships
+            WHERE target_corpus_db.sentence_memberships.sentence_rowid = ?2
+            ON CONFLICT (lang_rowid, word_rowid) DO UPDATE SET
+                last_seen_at = excluded.last_seen_at,
+                seen_count = seen_count + 1
+            ",
+            [self.target_lang_rowid, sentence_rowid],
+        )?;
+        Ok(())
+    }
 }
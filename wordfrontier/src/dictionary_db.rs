@@ -0,0 +1,87 @@
+use crate::Result;
+use std::convert::TryFrom;
+
+#[derive(Debug)]
+pub struct DictionaryRow {
+    pub dictionaries_rowid: i32,
+    pub lang_rowid: i32,
+    pub name: String,
+    pub format: String,
+    pub imported_at: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for DictionaryRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(DictionaryRow {
+            dictionaries_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            name: row.get(2)?,
+            format: row.get(3)?,
+            imported_at: row.get(4)?,
+        })
+    }
+}
+
+/// Holds dictionaries imported from local files (see DbHub::import_dictionary).  This is kept
+/// separate from the user DB since it's typically large and can always be re-imported.
+pub struct DictionaryDb {
+    conn: rusqlite::Connection,
+}
+
+impl DictionaryDb {
+    pub fn create_and_populate_if_missing() -> Result<()> {
+        // populate only creates missing tables, so it's harmless to run on existing DBs.
+        Self::open()?.populate()?;
+        Ok(())
+    }
+    pub fn attach(conn: &rusqlite::Connection) -> Result<()> {
+        conn.execute("ATTACH DATABASE ?1 AS dictionary_db", rusqlite::params![Self::db_path()])?;
+        Ok(())
+    }
+    pub fn db_path() -> &'static str {
+        "dictionary.db"
+    }
+
+    pub fn open() -> Result<Self> {
+        let conn = rusqlite::Connection::open(Self::db_path())?;
+        Ok(Self { conn })
+    }
+    pub fn populate(&self) -> Result<()> {
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS dictionaries (
+                dictionaries_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                format TEXT NOT NULL,
+                imported_at INTEGER NOT NULL,
+                UNIQUE(lang_rowid, name)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS dictionary_entries (
+                dictionary_entries_rowid INTEGER PRIMARY KEY,
+                dictionary_rowid INTEGER NOT NULL,
+                headword TEXT NOT NULL,
+                gloss TEXT NOT NULL
+            )",
+            [],
+        )?;
+        // Which corpus words each entry applies to, as determined at import time.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS dictionary_word_matches (
+                dictionary_word_matches_rowid INTEGER PRIMARY KEY,
+                dictionary_entry_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                UNIQUE(dictionary_entry_rowid, word_rowid)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS dictionary_word_matches_word_rowid ON dictionary_word_matches (word_rowid)",
+            [],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+use crate::{Error, Result};
+use std::{
+    convert::TryInto,
+    convert::TryFrom,
+    io::{BufRead, Read},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DictionaryFormat {
+    /// Lines of headword and gloss, separated by a tab.  Further tab-separated columns are
+    /// taken as additional glosses, and lines starting with '#' are ignored.
+    Tsv,
+    /// FreeDict's TEI XML format, e.g. deu-eng.tei.
+    FreeDictTei,
+    /// A StarDict dictionary, given by the path of its .ifo file.  The .idx (or .idx.gz) and
+    /// .dict (or .dict.dz) files are expected next to it.
+    StarDict,
+    /// JSON lines as produced by wiktextract (e.g. from kaikki.org), one word sense group
+    /// per line, with "word", "senses" (each with "glosses") and optionally "forms".
+    WiktionaryJsonl,
+}
+
+impl std::fmt::Display for DictionaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            DictionaryFormat::Tsv => write!(f, "tsv"),
+            DictionaryFormat::FreeDictTei => write!(f, "freedict-tei"),
+            DictionaryFormat::StarDict => write!(f, "stardict"),
+            DictionaryFormat::WiktionaryJsonl => write!(f, "wiktionary-jsonl"),
+        }
+    }
+}
+
+impl std::str::FromStr for DictionaryFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "tsv" => Ok(DictionaryFormat::Tsv),
+            "freedict-tei" => Ok(DictionaryFormat::FreeDictTei),
+            "stardict" => Ok(DictionaryFormat::StarDict),
+            "wiktionary-jsonl" => Ok(DictionaryFormat::WiktionaryJsonl),
+            _ => Err(anyhow::anyhow!("unknown dictionary format {:#?}", s))?,
+        }
+    }
+}
+
+/// A dictionary entry as read from a file, before being matched to corpus words.
+#[derive(Debug, PartialEq)]
+pub struct DictionaryEntry {
+    pub headword: String,
+    /// Inflected forms of the headword (if the dictionary lists them), so that e.g. "Häuser"
+    /// can be matched to the entry for "Haus".
+    pub form_v: Vec<String>,
+    pub gloss_v: Vec<String>,
+}
+
+impl DictionaryEntry {
+    fn new(headword: String) -> Self {
+        Self { headword, form_v: Vec::new(), gloss_v: Vec::new() }
+    }
+    fn push_gloss(&mut self, gloss: &str) {
+        let gloss = gloss.trim();
+        if !gloss.is_empty() && !self.gloss_v.iter().any(|existing| existing == gloss) {
+            self.gloss_v.push(gloss.to_string());
+        }
+    }
+}
+
+/// Reads all the entries of the dictionary at dictionary_p.  Entries without a headword or
+/// without any glosses are skipped.
+pub fn read_dictionary_entry_v(dictionary_p: &Path, format: DictionaryFormat) -> Result<Vec<DictionaryEntry>> {
+    match format {
+        DictionaryFormat::Tsv => parse_tsv(&std::fs::read_to_string(dictionary_p)?),
+        DictionaryFormat::FreeDictTei => parse_freedict_tei(std::io::BufReader::new(std::fs::File::open(dictionary_p)?)),
+        DictionaryFormat::StarDict => read_stardict(dictionary_p),
+        DictionaryFormat::WiktionaryJsonl => parse_wiktionary_jsonl(std::io::BufReader::new(std::fs::File::open(dictionary_p)?)),
+    }
+}
+
+fn parse_tsv(tsv_string: &str) -> Result<Vec<DictionaryEntry>> {
+    let mut dictionary_entry_v = Vec::new();
+    for (line_index, line) in tsv_string.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut tsv_split = line.split('\t');
+        let headword = tsv_split.next().unwrap_or_default().trim();
+        let mut dictionary_entry = DictionaryEntry::new(headword.to_string());
+        for gloss in tsv_split {
+            dictionary_entry.push_gloss(gloss);
+        }
+        if headword.is_empty() || dictionary_entry.gloss_v.is_empty() {
+            log::warn!("Malformed dictionary TSV data on line {}; expected headword and gloss.  Ignoring this line.", line_index + 1);
+            continue;
+        }
+        dictionary_entry_v.push(dictionary_entry);
+    }
+    Ok(dictionary_entry_v)
+}
+
+fn parse_freedict_tei<R: BufRead>(buf_read: R) -> Result<Vec<DictionaryEntry>> {
+    use quick_xml::events::Event;
+
+    // Which element's text is currently being collected.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Collecting {
+        Orth,
+        Gloss,
+    }
+
+    let mut reader = quick_xml::Reader::from_reader(buf_read);
+    let mut buf = Vec::new();
+    let mut dictionary_entry_v = Vec::new();
+    let mut dictionary_entry_o: Option<DictionaryEntry> = None;
+    // The type attributes of the enclosing <cit> elements, innermost last.
+    let mut cit_type_v: Vec<String> = Vec::new();
+    let mut collecting_o: Option<Collecting> = None;
+    let mut text = String::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"entry" => {
+                    dictionary_entry_o = Some(DictionaryEntry::new(String::new()));
+                    cit_type_v.clear();
+                }
+                b"cit" => {
+                    let cit_type = match e.try_get_attribute("type")? {
+                        Some(attribute) => attribute.unescape_value()?.into_owned(),
+                        None => String::new(),
+                    };
+                    cit_type_v.push(cit_type);
+                }
+                b"orth" => {
+                    collecting_o = Some(Collecting::Orth);
+                    text.clear();
+                }
+                // Translations in the newer (<cit type="trans"><quote>) and older (<trans><tr>)
+                // FreeDict schemas, but not translations of usage examples.
+                b"quote" if cit_type_v.last().map(String::as_str) == Some("trans") && !cit_type_v.iter().any(|cit_type| cit_type == "example") => {
+                    collecting_o = Some(Collecting::Gloss);
+                    text.clear();
+                }
+                b"tr" | b"def" if cit_type_v.is_empty() => {
+                    collecting_o = Some(Collecting::Gloss);
+                    text.clear();
+                }
+                _ => {}
+            },
+            Event::Text(e) if collecting_o.is_some() => text.push_str(&e.unescape()?),
+            Event::CData(e) if collecting_o.is_some() => text.push_str(&String::from_utf8_lossy(&e)),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"entry" => {
+                    if let Some(dictionary_entry) = dictionary_entry_o.take() {
+                        if !dictionary_entry.headword.is_empty() && !dictionary_entry.gloss_v.is_empty() {
+                            dictionary_entry_v.push(dictionary_entry);
+                        }
+                    }
+                }
+                b"cit" => {
+                    cit_type_v.pop();
+                }
+                b"orth" | b"quote" | b"tr" | b"def" => {
+                    if let (Some(collecting), Some(dictionary_entry)) = (collecting_o.take(), dictionary_entry_o.as_mut()) {
+                        let text = text.trim();
+                        match collecting {
+                            Collecting::Orth if dictionary_entry.headword.is_empty() => dictionary_entry.headword = text.to_string(),
+                            Collecting::Orth => dictionary_entry.form_v.push(text.to_string()),
+                            Collecting::Gloss => dictionary_entry.push_gloss(text),
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(dictionary_entry_v)
+}
+
+/// Removes markup tags (as used by the HTML, Pango and XDXF StarDict entry types).
+fn strip_tags(text: &str) -> String {
+    lazy_static::lazy_static! {
+        static ref TAG_REGEX: regex::Regex = regex::Regex::new(r"<[^>]*>").unwrap();
+    }
+    TAG_REGEX.replace_all(text, "").into_owned()
+}
+
+/// Reads the file at p, or if it doesn't exist, the gzip-compressed file at p with the given
+/// suffix appended (dictzip files are gzip-compatible).
+fn read_maybe_compressed(p: &Path, compressed_suffix: &str) -> Result<Vec<u8>> {
+    let mut byte_v = Vec::new();
+    if p.exists() {
+        std::fs::File::open(p)?.read_to_end(&mut byte_v)?;
+    } else {
+        let mut compressed_p = p.as_os_str().to_owned();
+        compressed_p.push(compressed_suffix);
+        flate2::read::GzDecoder::new(std::fs::File::open(PathBuf::from(compressed_p))?).read_to_end(&mut byte_v)?;
+    }
+    Ok(byte_v)
+}
+
+fn read_stardict(ifo_p: &Path) -> Result<Vec<DictionaryEntry>> {
+    let ifo_string = std::fs::read_to_string(ifo_p)?;
+    let mut idx_offset_bits = 32;
+    let mut same_type_sequence = String::new();
+    for line in ifo_string.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            match key.trim() {
+                "idxoffsetbits" => idx_offset_bits = value.trim().parse()?,
+                "sametypesequence" => same_type_sequence = value.trim().to_string(),
+                _ => {}
+            }
+        }
+    }
+    let idx_byte_v = read_maybe_compressed(&ifo_p.with_extension("idx"), ".gz")?;
+    let dict_byte_v = read_maybe_compressed(&ifo_p.with_extension("dict"), ".dz")?;
+    parse_stardict(&idx_byte_v, &dict_byte_v, idx_offset_bits, &same_type_sequence)
+}
+
+fn parse_stardict(idx_byte_v: &[u8], dict_byte_v: &[u8], idx_offset_bits: u32, same_type_sequence: &str) -> Result<Vec<DictionaryEntry>> {
+    let malformed = |what: &str| Error::MalformedInputError(format!("StarDict {}", what));
+    let offset_size = if idx_offset_bits == 64 { 8 } else { 4 };
+    let mut dictionary_entry_v = Vec::new();
+    let mut idx_remaining = idx_byte_v;
+    while !idx_remaining.is_empty() {
+        // Each .idx record is a NUL-terminated headword followed by the big-endian offset and
+        // size of its data in the .dict file.
+        let nul_index = idx_remaining.iter().position(|&b| b == 0).ok_or_else(|| malformed("index has unterminated headword"))?;
+        let headword = String::from_utf8_lossy(&idx_remaining[..nul_index]).into_owned();
+        let record = idx_remaining.get(nul_index + 1..nul_index + 1 + offset_size + 4).ok_or_else(|| malformed("index is truncated"))?;
+        let offset = if offset_size == 8 {
+            u64::from_be_bytes(record[..8].try_into().unwrap()) as usize
+        } else {
+            u32::from_be_bytes(record[..4].try_into().unwrap()) as usize
+        };
+        let size = u32::from_be_bytes(record[offset_size..].try_into().unwrap()) as usize;
+        idx_remaining = &idx_remaining[nul_index + 1 + offset_size + 4..];
+
+        let data = offset
+            .checked_add(size)
+            .and_then(|end| dict_byte_v.get(offset..end))
+            .ok_or_else(|| malformed("index points outside of dict data"))?;
+        let mut dictionary_entry = DictionaryEntry::new(headword);
+        for (type_c, field) in stardict_field_v(data, same_type_sequence)? {
+            match type_c {
+                'm' | 'l' | 'w' => dictionary_entry.push_gloss(&String::from_utf8_lossy(field)),
+                'g' | 'h' | 'x' => dictionary_entry.push_gloss(&strip_tags(&String::from_utf8_lossy(field))),
+                // Phonetics, resources, images, etc. aren't glosses.
+                _ => {}
+            }
+        }
+        if !dictionary_entry.headword.is_empty() && !dictionary_entry.gloss_v.is_empty() {
+            dictionary_entry_v.push(dictionary_entry);
+        }
+    }
+    Ok(dictionary_entry_v)
+}
+
+/// Splits StarDict entry data into its (type, content) fields.  Lowercase types are text,
+/// which is NUL-terminated unless it's the last field of a sametypesequence entry; uppercase
+/// types are binary, prefixed by their big-endian u32 size unless they're the last field.
+fn stardict_field_v<'a>(mut data: &'a [u8], same_type_sequence: &str) -> Result<Vec<(char, &'a [u8])>> {
+    let malformed = || Error::MalformedInputError("StarDict entry data is truncated".into());
+    let mut field_v = Vec::new();
+    let mut type_i = same_type_sequence.chars().peekable();
+    while !data.is_empty() {
+        let (type_c, is_last) = if same_type_sequence.is_empty() {
+            let type_c = char::from(data[0]);
+            data = &data[1..];
+            (type_c, false)
+        } else {
+            match type_i.next() {
+                Some(type_c) => (type_c, type_i.peek().is_none()),
+                None => break,
+            }
+        };
+        let field_size = if is_last {
+            data.len()
+        } else if type_c.is_ascii_lowercase() {
+            data.iter().position(|&b| b == 0).unwrap_or(data.len())
+        } else {
+            let size_bytes = data.get(..4).ok_or_else(malformed)?;
+            data = &data[4..];
+            u32::from_be_bytes(size_bytes.try_into().unwrap()) as usize
+        };
+        let field = data.get(..field_size).ok_or_else(malformed)?;
+        field_v.push((type_c, field));
+        // Skip the NUL terminator of non-last text fields.
+        let terminator_size = if !is_last && type_c.is_ascii_lowercase() && field_size < data.len() { 1 } else { 0 };
+        data = &data[field_size + terminator_size..];
+    }
+    Ok(field_v)
+}
+
+fn parse_wiktionary_jsonl<R: BufRead>(buf_read: R) -> Result<Vec<DictionaryEntry>> {
+    let mut dictionary_entry_v = Vec::new();
+    for (line_index, line_r) in buf_read.lines().enumerate() {
+        let line = line_r?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: serde_json::Value = match serde_json::from_str(&line) {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!("On line {}, {}.  Ignoring this line.", line_index + 1, e);
+                continue;
+            }
+        };
+        let headword = match value["word"].as_str() {
+            Some(headword) => headword,
+            None => continue,
+        };
+        let mut dictionary_entry = DictionaryEntry::new(headword.to_string());
+        for sense in value["senses"].as_array().into_iter().flatten() {
+            for gloss in sense["glosses"].as_array().into_iter().flatten() {
+                if let Some(gloss) = gloss.as_str() {
+                    dictionary_entry.push_gloss(gloss);
+                }
+            }
+        }
+        for form in value["forms"].as_array().into_iter().flatten() {
+            // Forms tagged like this are inflection table metadata, not words.
+            let is_metadata = form["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|tag| matches!(tag.as_str(), Some("table-tags") | Some("inflection-template") | Some("class")));
+            if let (Some(form), false) = (form["form"].as_str(), is_metadata) {
+                if form != headword && !dictionary_entry.form_v.iter().any(|existing| existing == form) {
+                    dictionary_entry.form_v.push(form.to_string());
+                }
+            }
+        }
+        if !dictionary_entry.gloss_v.is_empty() {
+            dictionary_entry_v.push(dictionary_entry);
+        }
+    }
+    Ok(dictionary_entry_v)
+}
+
+/// A gloss from an imported dictionary that applies to a corpus word.
+#[derive(Clone, Debug)]
+pub struct DictionaryGloss {
+    pub dictionary_entries_rowid: i32,
+    pub dictionary_name: String,
+    pub headword: String,
+    /// The entry's glosses, separated by "; ".
+    pub gloss: String,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for DictionaryGloss {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(DictionaryGloss {
+            dictionary_entries_rowid: row.get(0)?,
+            dictionary_name: row.get(1)?,
+            headword: row.get(2)?,
+            gloss: row.get(3)?,
+        })
+    }
+}
+
+/// Result of DbHub::import_dictionary.
+#[derive(Debug)]
+pub struct DictionaryImportReport {
+    pub dictionaries_rowid: i32,
+    pub entry_count: usize,
+    /// Number of entries matching at least one corpus word.
+    pub matched_entry_count: usize,
+    /// Number of distinct corpus words matched by at least one entry.
+    pub matched_word_count: usize,
+}
@@ -3,13 +3,21 @@ pub enum Error {
     #[error(transparent)]
     AnyhowError(#[from] anyhow::Error),
     #[error(transparent)]
+    CsvError(#[from] csv::Error),
+    #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error("malformed input: {}", .0)]
     MalformedInputError(String),
     #[error(transparent)]
     ParseIntError(#[from] std::num::ParseIntError),
     #[error(transparent)]
+    QuickXmlError(#[from] quick_xml::Error),
+    #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
     #[error(transparent)]
     RusqliteError(#[from] rusqlite::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
 }
@@ -0,0 +1,612 @@
+use crate::{fold_text, Order, Range, WordClass};
+use rusqlite::types::Value;
+
+/// Determines the score by which the word frontier is ordered (see FrontierQuery::order).
+#[derive(Clone, Debug, Default)]
+pub enum FrontierRanking {
+    /// Corpus frequency of the rarest unknown word in the sentence.
+    #[default]
+    MinUnknownWordFreq,
+    /// Sum of the corpus frequencies of the unknown words in the sentence.
+    SumUnknownWordFreq,
+    /// Corpus frequency of the most common unknown word in the sentence.
+    MaxUnknownWordFreq,
+    /// Number of distinct words in the sentence.
+    SentenceLength,
+    /// Proportion of the sentence's words that are both known and among the corpus's
+    /// top_word_count most frequent words.  A sentence made mostly of common known words is
+    /// easier to understand from context.
+    Comprehensibility { top_word_count: u32 },
+    /// Most recent time (in seconds since the Unix epoch) that any of the sentence's unknown
+    /// words was seen, or 0 if none were.  See DbHub::mark_sentence_seen.
+    UnknownWordRecency,
+    /// Weighted sum of the given rankings.  Because the rankings have incomparable units, each
+    /// is first rescaled to [0, 1] over the sentences in the frontier.
+    Weighted(Vec<(f64, FrontierRanking)>),
+}
+
+impl FrontierRanking {
+    // Flattens (possibly nested) Weighted rankings into a list of weighted leaf rankings.
+    fn collect_weighted_leaves<'a>(&'a self, weight: f64, leaf_v: &mut Vec<(f64, &'a FrontierRanking)>) {
+        match self {
+            FrontierRanking::Weighted(weighted_v) => {
+                for (inner_weight, inner_ranking) in weighted_v.iter() {
+                    inner_ranking.collect_weighted_leaves(weight * inner_weight, leaf_v);
+                }
+            }
+            leaf => leaf_v.push((weight, leaf)),
+        }
+    }
+    // Produces the aggregate SQL expression for a leaf ranking, to be evaluated within the
+    // per-sentence GROUP BY of the frontier query.
+    fn leaf_sql(&self, word_is_unknown_sql: &str, param_v: &mut Vec<Value>) -> String {
+        match self {
+            FrontierRanking::MinUnknownWordFreq => format!(
+                "IFNULL(MIN(CASE WHEN {} THEN target_corpus_db.words.freq END), 0)",
+                word_is_unknown_sql,
+            ),
+            FrontierRanking::SumUnknownWordFreq => format!(
+                "IFNULL(SUM(CASE WHEN {} THEN target_corpus_db.words.freq END), 0)",
+                word_is_unknown_sql,
+            ),
+            FrontierRanking::MaxUnknownWordFreq => format!(
+                "IFNULL(MAX(CASE WHEN {} THEN target_corpus_db.words.freq END), 0)",
+                word_is_unknown_sql,
+            ),
+            FrontierRanking::SentenceLength => {
+                "COUNT(target_corpus_db.sentence_memberships.word_rowid)".into()
+            }
+            FrontierRanking::Comprehensibility { top_word_count } => {
+                // The frequency of the top_word_count-th most frequent word is the threshold.
+                param_v.push(Value::Integer((*top_word_count).max(1) as i64 - 1));
+                format!(
+                    "IFNULL(AVG(CASE
+                        WHEN target_corpus_db.sentence_memberships.word_rowid IS NULL THEN NULL
+                        WHEN NOT {} AND target_corpus_db.words.freq >= IFNULL(
+                            (
+                                SELECT top_words.freq
+                                FROM target_corpus_db.words AS top_words
+                                ORDER BY top_words.freq DESC
+                                LIMIT 1 OFFSET ?{}
+                            ),
+                            0
+                        ) THEN 1.0
+                        ELSE 0.0
+                    END), 0.0)",
+                    word_is_unknown_sql,
+                    param_v.len(),
+                )
+            }
+            FrontierRanking::UnknownWordRecency => format!(
+                "IFNULL(MAX(CASE WHEN {} THEN (
+                    SELECT user_db.seen_words.last_seen_at
+                    FROM user_db.seen_words
+                    WHERE
+                        user_db.seen_words.lang_rowid = ?1
+                        AND
+                        user_db.seen_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                ) END), 0)",
+                word_is_unknown_sql,
+            ),
+            FrontierRanking::Weighted(_) => unreachable!("Weighted rankings are flattened into leaves"),
+        }
+    }
+}
+
+impl std::fmt::Display for FrontierRanking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            FrontierRanking::MinUnknownWordFreq => write!(f, "Min Unknown Word Freq"),
+            FrontierRanking::SumUnknownWordFreq => write!(f, "Sum Unknown Word Freq"),
+            FrontierRanking::MaxUnknownWordFreq => write!(f, "Max Unknown Word Freq"),
+            FrontierRanking::SentenceLength => write!(f, "Sentence Length"),
+            FrontierRanking::Comprehensibility { top_word_count } => write!(f, "Comprehensibility (Top {})", top_word_count),
+            FrontierRanking::UnknownWordRecency => write!(f, "Unknown Word Recency"),
+            FrontierRanking::Weighted(weighted_v) => {
+                for (i, (weight, ranking)) in weighted_v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " + ")?;
+                    }
+                    write!(f, "{} * ({})", weight, ranking)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+
+/// Limits how many sentences in the frontier share the same set of unknown words (for a
+/// Range(1, 1) frontier, the same single unknown word), so that a page of the frontier isn't
+/// dominated by sentences that all teach the same word.  Within each group, the sentences
+/// that are kept are the first ones according to the frontier's ordering (or shuffle).
+#[derive(Clone, Copy, Debug)]
+pub struct FrontierDiversification {
+    /// Maximum number of sentences kept per set of unknown words.
+    pub max_sentences_per_group: u32,
+    /// If true, the frontier is ordered primarily by each sentence's position within its
+    /// group, so that it cycles through the groups: the best sentence of every group, then
+    /// the second best of every group, and so on.
+    pub round_robin: bool,
+}
+
+/// A condition on the text of frontier sentences.  These are evaluated within SQLite.
+#[derive(Clone, Debug)]
+pub enum TextFilter {
+    /// The text must contain this (case-sensitive) substring.
+    Contains(String),
+    /// The text must not contain this (case-sensitive) substring.
+    DoesNotContain(String),
+    /// The text must match this regex (using the syntax of the regex crate).
+    MatchesRegex(String),
+    /// The text must not match this regex (using the syntax of the regex crate).
+    DoesNotMatchRegex(String),
+    /// The text must contain this, ignoring case and accents (see fold_text).
+    Search(String),
+}
+
+impl TextFilter {
+    fn sql(&self, param_v: &mut Vec<Value>) -> String {
+        let (condition_sql, param) = match self {
+            TextFilter::Contains(s) => ("instr(target_corpus_db.sentences.text, ?{}) > 0", s.clone()),
+            TextFilter::DoesNotContain(s) => ("instr(target_corpus_db.sentences.text, ?{}) = 0", s.clone()),
+            TextFilter::MatchesRegex(s) => ("target_corpus_db.sentences.text REGEXP ?{}", s.clone()),
+            TextFilter::DoesNotMatchRegex(s) => ("NOT (target_corpus_db.sentences.text REGEXP ?{})", s.clone()),
+            TextFilter::Search(s) => ("instr(fold_text(target_corpus_db.sentences.text), ?{}) > 0", fold_text(s)),
+        };
+        param_v.push(Value::Text(param));
+        condition_sql.replace("?{}", &format!("?{}", param_v.len()))
+    }
+}
+
+/// Parameters for a word frontier query.  Construct with `FrontierQuery::new` and refine using
+/// the builder methods, e.g.
+///
+/// ```
+/// use wordfrontier::{FrontierQuery, Order, Range};
+/// let frontier_query = FrontierQuery::new(Range(1, 1)).order(Order::Ascending).page(2, 100);
+/// assert_eq!(frontier_query.offset, 200);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrontierQuery {
+    /// Inclusive range for the number of unknown words in each sentence.
+    pub unknown_word_count_range: Range,
+    /// The score that the frontier is ordered by.
+    pub ranking: FrontierRanking,
+    /// Ordering by the ranking score.  Ignored if shuffle_seed_o is set.
+    pub order: Order,
+    /// Maximum number of sentences to return.  None means no limit.
+    pub limit_o: Option<u32>,
+    /// Number of sentences to skip (after ordering) before returning any.
+    pub offset: u32,
+    /// If set, the frontier is returned in a pseudo-random order that is determined entirely
+    /// by this seed (and the frontier itself), so combined with limit_o this gives a
+    /// reproducible random sample.
+    pub shuffle_seed_o: Option<u64>,
+    /// If set, limits the number of sentences sharing the same unknown words.
+    pub diversification_o: Option<FrontierDiversification>,
+    /// If set, inclusive range for the number of words (tokens, counting repeats) in each
+    /// sentence.
+    pub token_count_range_o: Option<Range>,
+    /// If set, inclusive range for the number of characters in each sentence.
+    pub char_length_range_o: Option<Range>,
+    /// Conditions that the text of each sentence must all satisfy.
+    pub text_filter_v: Vec<TextFilter>,
+    /// If true, a word counts as known in a sentence if each of its occurrences there is part
+    /// of an occurrence of a known n-gram (see DbHub::add_known_ngram), so that knowing a fixed
+    /// expression covers its words.
+    pub known_ngrams_cover_words: bool,
+    /// Words of these classes count as known, e.g. so that a sentence whose only unknown word
+    /// is a name isn't in the 1-unknown-word frontier.
+    pub auto_known_word_class_v: Vec<WordClass>,
+    /// If true, a compound word counts as known if all of its components are known (see
+    /// DbHub::split_compounds).  Components must be known themselves, not by their parts.
+    pub compounds_known_by_components: bool,
+}
+
+impl FrontierQuery {
+    pub fn new(unknown_word_count_range: Range) -> Self {
+        Self {
+            unknown_word_count_range,
+            ranking: FrontierRanking::default(),
+            order: Order::Descending,
+            limit_o: None,
+            offset: 0,
+            shuffle_seed_o: None,
+            diversification_o: None,
+            token_count_range_o: None,
+            char_length_range_o: None,
+            text_filter_v: Vec::new(),
+            known_ngrams_cover_words: false,
+            auto_known_word_class_v: Vec::new(),
+            compounds_known_by_components: false,
+        }
+    }
+    pub fn ranking(mut self, ranking: FrontierRanking) -> Self {
+        self.ranking = ranking;
+        self
+    }
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit_o = Some(limit);
+        self
+    }
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+    /// Sets limit and offset to select the given 0-based page.
+    pub fn page(self, page_index: u32, page_size: u32) -> Self {
+        self.limit(page_size).offset(page_index.saturating_mul(page_size))
+    }
+    pub fn shuffled(mut self, seed: u64) -> Self {
+        self.shuffle_seed_o = Some(seed);
+        self
+    }
+    pub fn diversified(mut self, diversification: FrontierDiversification) -> Self {
+        self.diversification_o = Some(diversification);
+        self
+    }
+    pub fn token_count_range(mut self, token_count_range: Range) -> Self {
+        self.token_count_range_o = Some(token_count_range);
+        self
+    }
+    pub fn char_length_range(mut self, char_length_range: Range) -> Self {
+        self.char_length_range_o = Some(char_length_range);
+        self
+    }
+    /// Adds a text filter; a sentence must satisfy all of them.
+    pub fn text_filter(mut self, text_filter: TextFilter) -> Self {
+        self.text_filter_v.push(text_filter);
+        self
+    }
+
+    pub fn known_ngrams_cover_words(mut self, known_ngrams_cover_words: bool) -> Self {
+        self.known_ngrams_cover_words = known_ngrams_cover_words;
+        self
+    }
+
+    /// Adds a class of words to treat as known.
+    pub fn auto_known_word_class(mut self, word_class: WordClass) -> Self {
+        if !self.auto_known_word_class_v.contains(&word_class) {
+            self.auto_known_word_class_v.push(word_class);
+        }
+        self
+    }
+
+    pub fn compounds_known_by_components(mut self, compounds_known_by_components: bool) -> Self {
+        self.compounds_known_by_components = compounds_known_by_components;
+        self
+    }
+
+    /// Produces the SQL condition for whether a word of a sentence counts as unknown.  It's
+    /// evaluated per (sentence, word) row of a join of target_corpus_db.sentences, its
+    /// sentence_memberships, and user_db.known_words, with the target language rowid as ?1.
+    fn word_is_unknown_sql(&self) -> String {
+        // A word is covered if each of its tokens in the sentence lies within an occurrence of a
+        // known n-gram, so that e.g. a known "Guten Morgen" doesn't cover a separate "Morgen" in
+        // the same sentence.
+        let known_ngram_covers_word_sql = if self.known_ngrams_cover_words {
+            "
+            AND NOT (
+                EXISTS (
+                    SELECT 1
+                    FROM target_corpus_db.sentence_tokens
+                    WHERE
+                        target_corpus_db.sentence_tokens.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+                        AND
+                        target_corpus_db.sentence_tokens.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                )
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM target_corpus_db.sentence_tokens
+                    WHERE
+                        target_corpus_db.sentence_tokens.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+                        AND
+                        target_corpus_db.sentence_tokens.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                        AND NOT EXISTS (
+                            SELECT 1
+                            FROM user_db.known_ngrams
+                            INNER JOIN
+                                target_corpus_db.ngram_sentence_memberships
+                                ON
+                                target_corpus_db.ngram_sentence_memberships.ngram_rowid = user_db.known_ngrams.ngram_rowid
+                                AND
+                                target_corpus_db.ngram_sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+                            INNER JOIN target_corpus_db.ngrams ON target_corpus_db.ngrams.ngrams_rowid = user_db.known_ngrams.ngram_rowid
+                            WHERE
+                                user_db.known_ngrams.lang_rowid = ?1
+                                AND
+                                target_corpus_db.sentence_tokens.token_index BETWEEN
+                                    target_corpus_db.ngram_sentence_memberships.start_token_index
+                                    AND
+                                    target_corpus_db.ngram_sentence_memberships.start_token_index + target_corpus_db.ngrams.word_count - 1
+                        )
+                )
+            )"
+        } else {
+            ""
+        };
+        let mut auto_known_word_class_sql = String::new();
+        let corpus_word_class_str_v: Vec<String> = self.auto_known_word_class_v
+            .iter()
+            .filter(|word_class| **word_class != WordClass::Name)
+            .map(|word_class| format!("'{}'", word_class.as_sql_str()))
+            .collect();
+        if !corpus_word_class_str_v.is_empty() {
+            auto_known_word_class_sql.push_str(&format!(
+                "
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM target_corpus_db.word_classes
+                    WHERE
+                        target_corpus_db.word_classes.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                        AND
+                        target_corpus_db.word_classes.class IN ({})
+                )",
+                corpus_word_class_str_v.join(", "),
+            ));
+        }
+        if self.auto_known_word_class_v.contains(&WordClass::Name) {
+            auto_known_word_class_sql.push_str(
+                "
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM user_db.names
+                    WHERE
+                        user_db.names.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                        AND
+                        user_db.names.lang_rowid = ?1
+                )",
+            );
+        }
+        let compound_known_by_components_sql = if self.compounds_known_by_components {
+            "
+            AND NOT (
+                EXISTS (
+                    SELECT 1
+                    FROM target_corpus_db.word_components
+                    WHERE target_corpus_db.word_components.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                )
+                AND
+                NOT EXISTS (
+                    SELECT 1
+                    FROM target_corpus_db.word_components
+                    WHERE
+                        target_corpus_db.word_components.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                        AND
+                        target_corpus_db.word_components.component_word_rowid NOT IN (
+                            SELECT user_db.known_words.word_rowid
+                            FROM user_db.known_words
+                            WHERE user_db.known_words.lang_rowid = ?1
+                        )
+                )
+            )"
+        } else {
+            ""
+        };
+        // The NULL check excludes the single all-NULL row that a LEFT JOIN of
+        // sentence_memberships produces for a wordless sentence.
+        format!(
+            "(
+                target_corpus_db.sentence_memberships.word_rowid IS NOT NULL
+                AND
+                user_db.known_words.word_rowid IS NULL
+                {}
+                {}
+                {}
+            )",
+            known_ngram_covers_word_sql,
+            auto_known_word_class_sql,
+            compound_known_by_components_sql,
+        )
+    }
+
+    /// Produces the SQL for the rowids of the words of a sentence that count as unknown, as
+    /// they do for the frontier, with the target language rowid as parameter ?1 and the
+    /// sentence rowid as parameter ?2.
+    pub(crate) fn sentence_unknown_word_rowid_sql(&self) -> String {
+        format!(
+            "
+            SELECT target_corpus_db.sentence_memberships.word_rowid
+            FROM target_corpus_db.sentences
+            INNER JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE target_corpus_db.sentences.sentences_rowid = ?2 AND {}
+            ",
+            self.word_is_unknown_sql(),
+        )
+    }
+
+    /// Produces the SQL and its positional parameters.  If count_only is true, the query
+    /// produces a single row containing the number of sentences in the frontier, disregarding
+    /// ordering, limit, and offset.
+    pub(crate) fn to_sql(&self, target_lang_rowid: i32, count_only: bool) -> (String, Vec<Value>) {
+        let mut param_v = vec![
+            Value::Integer(target_lang_rowid as i64),
+            Value::Integer(self.unknown_word_count_range.0 as i64),
+            Value::Integer(self.unknown_word_count_range.1 as i64),
+        ];
+        let word_is_unknown_sql = &self.word_is_unknown_sql();
+
+        // Each leaf ranking gets its own score column, and rank_score combines them.
+        let mut leaf_v = Vec::new();
+        self.ranking.collect_weighted_leaves(1.0, &mut leaf_v);
+        let mut score_column_sql = String::new();
+        for (i, (_, leaf)) in leaf_v.iter().enumerate() {
+            score_column_sql.push_str(&format!(
+                ",\n{} AS score_{}",
+                leaf.leaf_sql(word_is_unknown_sql, &mut param_v),
+                i,
+            ));
+        }
+        let rank_score_sql = match self.ranking {
+            FrontierRanking::Weighted(_) => {
+                let mut rank_score_sql = String::from("0.0");
+                for (i, (weight, _)) in leaf_v.iter().enumerate() {
+                    // Rescale to [0, 1] across the frontier; a constant score contributes 0.
+                    rank_score_sql.push_str(&format!(
+                        " + {:?} * IFNULL(
+                            CAST(score_{i} - MIN(score_{i}) OVER () AS REAL)
+                            / NULLIF(MAX(score_{i}) OVER () - MIN(score_{i}) OVER (), 0),
+                            0.0
+                        )",
+                        weight,
+                        i = i,
+                    ));
+                }
+                rank_score_sql
+            }
+            _ => "score_0".into(),
+        };
+
+        // This is only needed (and only worth computing) for diversification.
+        let unknown_word_set_key_sql = if self.diversification_o.is_some() {
+            format!(
+                "set_key(CASE WHEN {} THEN target_corpus_db.sentence_memberships.word_rowid END)",
+                word_is_unknown_sql,
+            )
+        } else {
+            "NULL".into()
+        };
+
+        // Conditions on the sentences themselves, which narrow the frontier before any of the
+        // per-word work is done.
+        let mut sentence_filter_sql = String::new();
+        if let Some(token_count_range) = self.token_count_range_o {
+            param_v.push(Value::Integer(token_count_range.0 as i64));
+            param_v.push(Value::Integer(token_count_range.1 as i64));
+            sentence_filter_sql.push_str(&format!(
+                "AND token_count(target_corpus_db.sentences.text) BETWEEN ?{} AND ?{}\n",
+                param_v.len() - 1,
+                param_v.len(),
+            ));
+        }
+        if let Some(char_length_range) = self.char_length_range_o {
+            param_v.push(Value::Integer(char_length_range.0 as i64));
+            param_v.push(Value::Integer(char_length_range.1 as i64));
+            sentence_filter_sql.push_str(&format!(
+                "AND length(target_corpus_db.sentences.text) BETWEEN ?{} AND ?{}\n",
+                param_v.len() - 1,
+                param_v.len(),
+            ));
+        }
+        for text_filter in self.text_filter_v.iter() {
+            sentence_filter_sql.push_str(&format!("AND {}\n", text_filter.sql(&mut param_v)));
+        }
+
+        let frontier_sql = format!("
+            -- This selects sentences having a number of unknown words in a certain range.
+            SELECT
+                *,
+                {} AS rank_score
+            FROM (
+                SELECT
+                    target_corpus_db.sentences.sentences_rowid,
+                    target_corpus_db.sentences.lang_rowid,
+                    target_corpus_db.sentences.text,
+                    COUNT(CASE WHEN {} THEN 1 END) AS unknown_word_count,
+                    IFNULL(MIN(CASE WHEN {} THEN target_corpus_db.words.freq END), 0) AS unknown_word_freq,
+                    {} AS unknown_word_set_key
+                    {}
+                FROM target_corpus_db.sentences
+                LEFT JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+                LEFT JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = target_corpus_db.sentence_memberships.word_rowid
+                LEFT JOIN
+                    user_db.known_words
+                    ON
+                    user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                    AND
+                    user_db.known_words.lang_rowid = ?1
+                WHERE
+                    target_corpus_db.sentences.sentences_rowid IN (
+                        SELECT target_lang_sentence_rowid FROM translations_db.translations
+                    )
+                    AND
+                    target_corpus_db.sentences.lang_rowid = ?1
+                    {}
+                GROUP BY target_corpus_db.sentences.sentences_rowid
+                HAVING unknown_word_count BETWEEN ?2 AND ?3
+            )
+        ",
+            rank_score_sql,
+            word_is_unknown_sql,
+            word_is_unknown_sql,
+            unknown_word_set_key_sql,
+            score_column_sql,
+            sentence_filter_sql,
+        );
+        // The sentences_rowid tie-breaker keeps the order total, so that paging is stable.
+        let sort_key_sql_o: Option<String> = match (self.shuffle_seed_o, self.order) {
+            (Some(shuffle_seed), _) => {
+                param_v.push(Value::Integer(shuffle_seed as i64));
+                Some(format!("shuffle_key(?{}, sentences_rowid)", param_v.len()))
+            }
+            (None, Order::Ascending) => Some("rank_score ASC, sentences_rowid ASC".into()),
+            (None, Order::Descending) => Some("rank_score DESC, sentences_rowid ASC".into()),
+            (None, Order::Unordered) => None,
+        };
+
+        let (frontier_sql, ordering_str) = match self.diversification_o {
+            Some(diversification) => {
+                param_v.push(Value::Integer(diversification.max_sentences_per_group as i64));
+                let sort_key_sql = sort_key_sql_o.unwrap_or_else(|| "sentences_rowid ASC".into());
+                let diversified_sql = format!("
+                    -- This caps the number of sentences sharing each set of unknown words.
+                    SELECT *
+                    FROM (
+                        SELECT
+                            *,
+                            ROW_NUMBER() OVER (PARTITION BY unknown_word_set_key ORDER BY {}) AS group_position
+                        FROM ({})
+                    )
+                    WHERE group_position <= ?{}
+                ",
+                    sort_key_sql,
+                    frontier_sql,
+                    param_v.len(),
+                );
+                let ordering_str = if diversification.round_robin {
+                    format!("ORDER BY group_position ASC, {}", sort_key_sql)
+                } else {
+                    format!("ORDER BY {}", sort_key_sql)
+                };
+                (diversified_sql, ordering_str)
+            }
+            None => {
+                let ordering_str = sort_key_sql_o
+                    .map(|sort_key_sql| format!("ORDER BY {}", sort_key_sql))
+                    .unwrap_or_default();
+                (frontier_sql, ordering_str)
+            }
+        };
+        if count_only {
+            return (format!("SELECT COUNT(*) FROM ({})", frontier_sql), param_v);
+        }
+
+        // In SQLite, a negative LIMIT means no limit.
+        param_v.push(Value::Integer(self.limit_o.map(i64::from).unwrap_or(-1)));
+        param_v.push(Value::Integer(self.offset as i64));
+        let sql = format!(
+            "
+            SELECT sentences_rowid, lang_rowid, text, unknown_word_count, unknown_word_freq, rank_score
+            FROM ({})
+            {}
+            LIMIT ?{} OFFSET ?{}
+            ",
+            frontier_sql,
+            ordering_str,
+            param_v.len() - 1,
+            param_v.len(),
+        );
+        (sql, param_v)
+    }
+}
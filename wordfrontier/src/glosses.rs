@@ -0,0 +1,106 @@
+use std::convert::TryFrom;
+
+/// Parameters for DbHub::induce_glosses, which estimates the meanings of target language words
+/// from the translated sentence pairs, by scoring each (target word, reference word) pair with
+/// the Dice coefficient of their occurrences across the sentence pairs.
+#[derive(Clone, Copy, Debug)]
+pub struct GlossInduction {
+    /// Word pairs co-occurring in fewer sentence pairs than this are ignored, since their
+    /// scores are mostly noise.
+    pub min_cooccurrence_count: u32,
+    /// The number of best-scoring glosses kept for each target word.
+    pub max_glosses_per_word: u32,
+    /// Glosses scoring lower than this (the Dice coefficient is between 0 and 1) are dropped.
+    pub min_score: f64,
+}
+
+impl Default for GlossInduction {
+    fn default() -> Self {
+        Self {
+            min_cooccurrence_count: 2,
+            max_glosses_per_word: 5,
+            min_score: 0.1,
+        }
+    }
+}
+
+/// A candidate translation of a target language word into a reference language word.
+#[derive(Clone, Debug)]
+pub struct GlossWithText {
+    pub glosses_rowid: i32,
+    pub target_word_rowid: i32,
+    pub reference_word_rowid: i32,
+    pub reference_word_text: String,
+    /// Dice coefficient; higher is better.
+    pub score: f64,
+    /// Number of translated sentence pairs containing both words.
+    pub cooccurrence_count: i64,
+    /// 1 for the best gloss of the target word, 2 for the next, etc.
+    pub rank: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for GlossWithText {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(GlossWithText {
+            glosses_rowid: row.get(0)?,
+            target_word_rowid: row.get(1)?,
+            reference_word_rowid: row.get(2)?,
+            reference_word_text: row.get(3)?,
+            score: row.get(4)?,
+            cooccurrence_count: row.get(5)?,
+            rank: row.get(6)?,
+        })
+    }
+}
+
+/// Params are ?1 = min_cooccurrence_count, ?2 = max_glosses_per_word, ?3 = min_score.
+pub(crate) const INDUCE_GLOSSES_SQL: &str = "
+    INSERT INTO translations_db.glosses (target_word_rowid, reference_word_rowid, score, cooccurrence_count, rank)
+    WITH target_word_pair_counts AS (
+        SELECT target_corpus_db.sentence_memberships.word_rowid, COUNT(*) AS pair_count
+        FROM translations_db.translations
+        INNER JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = translations_db.translations.target_lang_sentence_rowid
+        GROUP BY target_corpus_db.sentence_memberships.word_rowid
+    ),
+    reference_word_pair_counts AS (
+        SELECT reference_corpus_db.sentence_memberships.word_rowid, COUNT(*) AS pair_count
+        FROM translations_db.translations
+        INNER JOIN reference_corpus_db.sentence_memberships ON reference_corpus_db.sentence_memberships.sentence_rowid = translations_db.translations.reference_lang_sentence_rowid
+        GROUP BY reference_corpus_db.sentence_memberships.word_rowid
+    ),
+    cooccurrence_counts AS (
+        SELECT
+            target_corpus_db.sentence_memberships.word_rowid AS target_word_rowid,
+            reference_corpus_db.sentence_memberships.word_rowid AS reference_word_rowid,
+            COUNT(*) AS cooccurrence_count
+        FROM translations_db.translations
+        INNER JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = translations_db.translations.target_lang_sentence_rowid
+        INNER JOIN reference_corpus_db.sentence_memberships ON reference_corpus_db.sentence_memberships.sentence_rowid = translations_db.translations.reference_lang_sentence_rowid
+        GROUP BY target_word_rowid, reference_word_rowid
+        HAVING cooccurrence_count >= ?1
+    ),
+    scored AS (
+        SELECT
+            cooccurrence_counts.target_word_rowid,
+            cooccurrence_counts.reference_word_rowid,
+            2.0 * cooccurrence_counts.cooccurrence_count / (target_word_pair_counts.pair_count + reference_word_pair_counts.pair_count) AS score,
+            cooccurrence_counts.cooccurrence_count
+        FROM cooccurrence_counts
+        INNER JOIN target_word_pair_counts ON target_word_pair_counts.word_rowid = cooccurrence_counts.target_word_rowid
+        INNER JOIN reference_word_pair_counts ON reference_word_pair_counts.word_rowid = cooccurrence_counts.reference_word_rowid
+    ),
+    ranked AS (
+        SELECT
+            *,
+            ROW_NUMBER() OVER (
+                PARTITION BY target_word_rowid
+                ORDER BY score DESC, cooccurrence_count DESC, reference_word_rowid ASC
+            ) AS rank
+        FROM scored
+        WHERE score >= ?3
+    )
+    SELECT target_word_rowid, reference_word_rowid, score, cooccurrence_count, rank
+    FROM ranked
+    WHERE rank <= ?2
+";
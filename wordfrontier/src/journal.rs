@@ -0,0 +1,69 @@
+use std::convert::TryFrom;
+
+/// One change to known_words within a journal entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalChange {
+    pub word_rowid: i32,
+    pub word_text: String,
+    /// True if the word was added to known_words, false if it was removed.
+    pub is_added: bool,
+}
+
+/// A user operation that changed known_words, which can be undone as a unit (see
+/// DbHub::undo and DbHub::redo).
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalEntry {
+    pub journal_entries_rowid: i32,
+    pub lang_rowid: i32,
+    /// Human-readable description of the operation, e.g. "Add known word".
+    pub description: String,
+    /// Unix time of the operation.
+    pub occurred_at: i64,
+    /// Undone entries can be redone until the next new operation.
+    pub is_undone: bool,
+    pub change_v: Vec<JournalChange>,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for JournalEntry {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(JournalEntry {
+            journal_entries_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            description: row.get(2)?,
+            occurred_at: row.get(3)?,
+            is_undone: row.get(4)?,
+            change_v: Vec::new(),
+        })
+    }
+}
+
+pub(crate) const JOURNAL_ENTRY_COLUMNS_SQL: &str = "
+    user_db.journal_entries.journal_entries_rowid,
+    user_db.journal_entries.lang_rowid,
+    user_db.journal_entries.description,
+    user_db.journal_entries.occurred_at,
+    user_db.journal_entries.is_undone
+";
+
+pub(crate) const JOURNAL_CHANGES_SQL: &str = "
+    SELECT
+        user_db.journal_changes.word_rowid,
+        target_corpus_db.words.text,
+        user_db.journal_changes.is_added
+    FROM user_db.journal_changes
+    INNER JOIN target_corpus_db.words ON target_corpus_db.words.words_rowid = user_db.journal_changes.word_rowid
+    WHERE user_db.journal_changes.journal_entry_rowid = ?1
+    ORDER BY user_db.journal_changes.journal_changes_rowid
+";
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for JournalChange {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(JournalChange {
+            word_rowid: row.get(0)?,
+            word_text: row.get(1)?,
+            is_added: row.get(2)?,
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LearningPathStep {
+    pub word_rowid: i32,
+    /// Number of sentences that became fully known by learning this word.
+    pub newly_comprehensible_sentence_count: usize,
+    /// Cumulative number of fully known sentences after this step.
+    pub comprehensible_sentence_count: usize,
+    /// comprehensible_sentence_count as a fraction of all sentences.
+    pub coverage: f64,
+}
+
+#[derive(Debug)]
+pub struct LearningPathStepWithText {
+    pub word_rowid: i32,
+    pub word_text: String,
+    pub word_freq: i32,
+    pub newly_comprehensible_sentence_count: usize,
+    pub comprehensible_sentence_count: usize,
+    pub coverage: f64,
+}
+
+/// Greedily plans which words to learn, in order, to make as many sentences as possible fully
+/// known.  sentence_unknown_word_vv has one element per sentence (including already fully known
+/// sentences, as empty Vec-s), each listing the distinct unknown words of that sentence.  Each
+/// step picks the word which is the only remaining unknown word in the most sentences, breaking
+/// ties by word_freq_m (words missing from it count as freq 0) and then by lowest word_rowid.
+/// Returns at most step_count steps.
+pub fn plan_learning_path(
+    sentence_unknown_word_vv: &[Vec<i32>],
+    word_freq_m: &HashMap<i32, i32>,
+    step_count: usize,
+) -> Vec<LearningPathStep> {
+    let sentence_count = sentence_unknown_word_vv.len();
+    let mut remaining_unknown_word_count_v: Vec<usize> =
+        sentence_unknown_word_vv.iter().map(|unknown_word_v| unknown_word_v.len()).collect();
+    let mut comprehensible_sentence_count =
+        remaining_unknown_word_count_v.iter().filter(|&&count| count == 0).count();
+
+    // For each word, the indices of the sentences it's unknown in, and the number of those
+    // sentences where it's the only remaining unknown word (i.e. what learning it would gain).
+    let mut sentence_index_vm: HashMap<i32, Vec<usize>> = HashMap::new();
+    let mut gain_m: HashMap<i32, usize> = HashMap::new();
+    for (sentence_index, unknown_word_v) in sentence_unknown_word_vv.iter().enumerate() {
+        for &word_rowid in unknown_word_v.iter() {
+            sentence_index_vm.entry(word_rowid).or_default().push(sentence_index);
+            let gain = gain_m.entry(word_rowid).or_insert(0);
+            if unknown_word_v.len() == 1 {
+                *gain += 1;
+            }
+        }
+    }
+
+    // Gains only ever increase (except for learned words), so a max-heap with lazily discarded
+    // stale entries always yields the current best word.
+    let freq_of = |word_rowid: i32| word_freq_m.get(&word_rowid).copied().unwrap_or(0);
+    let mut heap: BinaryHeap<(usize, i32, Reverse<i32>)> = gain_m
+        .iter()
+        .map(|(&word_rowid, &gain)| (gain, freq_of(word_rowid), Reverse(word_rowid)))
+        .collect();
+    let mut learned_word_s: HashSet<i32> = HashSet::new();
+    let mut learning_path_step_v = Vec::with_capacity(step_count.min(gain_m.len()));
+    while learning_path_step_v.len() < step_count {
+        let (gain, _, Reverse(word_rowid)) = match heap.pop() {
+            Some(entry) => entry,
+            None => break,
+        };
+        if learned_word_s.contains(&word_rowid) || gain != gain_m[&word_rowid] {
+            continue;
+        }
+        learned_word_s.insert(word_rowid);
+
+        let mut newly_comprehensible_sentence_count = 0;
+        for &sentence_index in sentence_index_vm[&word_rowid].iter() {
+            remaining_unknown_word_count_v[sentence_index] -= 1;
+            match remaining_unknown_word_count_v[sentence_index] {
+                0 => newly_comprehensible_sentence_count += 1,
+                1 => {
+                    let last_word_rowid = *sentence_unknown_word_vv[sentence_index]
+                        .iter()
+                        .find(|word_rowid| !learned_word_s.contains(word_rowid))
+                        .expect("remaining unknown word count is out of sync");
+                    let last_gain = gain_m.get_mut(&last_word_rowid).unwrap();
+                    *last_gain += 1;
+                    heap.push((*last_gain, freq_of(last_word_rowid), Reverse(last_word_rowid)));
+                }
+                _ => {}
+            }
+        }
+        comprehensible_sentence_count += newly_comprehensible_sentence_count;
+        learning_path_step_v.push(LearningPathStep {
+            word_rowid,
+            newly_comprehensible_sentence_count,
+            comprehensible_sentence_count,
+            coverage: if sentence_count == 0 {
+                0.0
+            } else {
+                comprehensible_sentence_count as f64 / sentence_count as f64
+            },
+        });
+    }
+    learning_path_step_v
+}
@@ -1,18 +1,64 @@
 #![allow(dead_code)] // TEMP HACK
 
+mod compounds;
+mod computed_tables;
+mod concordance;
 mod corpus_db;
 mod db_hub;
+mod dictionary_db;
+mod dictionary_import;
 mod error;
+mod frontier_query;
+mod glosses;
+mod journal;
 mod langs_db;
+mod learning_path;
+mod next_words;
+mod ngrams;
+mod placement;
+mod progress;
+mod reading_packet;
+mod sentence_export;
+mod sentence_tokens;
+mod sql_functions;
+mod stats;
+mod text_analysis;
+mod tokenizer;
 mod translations_db;
+mod user_data;
 mod user_db;
+mod word_classes;
+mod word_list_import;
+mod word_notes;
 
 pub use crate::{
+    compounds::{CompoundComponentWithText, CompoundSplitting},
+    concordance::{ConcordanceMember, ConcordanceQuery, KeywordInContext},
     corpus_db::{CorpusDb, CorpusPurpose, SentenceRow},
-    db_hub::{DbHub, DbHubConfig, KnownWordWithText, SentenceMembershipWithTextEtc, TranslationWithText, WordFrontierMember},
+    db_hub::{DbHub, DbHubConfig, KnownWordWithText, NameWithText, SentenceMembershipWithTextEtc, TranslationWithText, WordFrontierMember, WordSetMemberWithText},
+    dictionary_db::{DictionaryDb, DictionaryRow},
+    dictionary_import::{read_dictionary_entry_v, DictionaryEntry, DictionaryFormat, DictionaryGloss, DictionaryImportReport},
+    frontier_query::{FrontierDiversification, FrontierQuery, FrontierRanking, TextFilter},
+    glosses::{GlossInduction, GlossWithText},
+    journal::{JournalChange, JournalEntry},
     langs_db::{Lang, LangsDb, LangRow},
+    learning_path::{plan_learning_path, LearningPathStep, LearningPathStepWithText},
+    next_words::{NextWordRanking, NextWordRecommendation},
+    ngrams::{NgramRow, NgramSelection, NgramWithKnown},
+    placement::{PlacementBandEstimate, PlacementEstimate, PlacementQuestion, PlacementTest, PlacementTestConfig},
+    progress::{KnownWordTimelinePoint, ProgressSnapshotRow},
+    reading_packet::{render_reading_packet, ReadingPacketFormat},
+    sentence_export::{anki_note_guid, tatoeba_sentence_url, write_anki_tsv, ExportSentence, ExportWord, ANKI_TSV_COLUMN_V, TATOEBA_LICENSE, TATOEBA_LICENSE_URL},
+    sentence_tokens::{SentenceSpan, SentenceSpanToken, SentenceTokenRow},
+    stats::{FrequencyBandCoverage, VocabularyStats, FREQUENCY_BAND_TOP_WORD_COUNT_V},
+    text_analysis::{AnalyzedSentence, AnalyzedWord, GlossaryEntry, TextAnalysis, WordKnowledge},
+    tokenizer::{fold_text, normalize_word, split_sentences, tokenize, tokenize_with_byte_offsets},
     translations_db::{TranslationsDb},
-    user_db::{UserDb},
+    user_data::{BundleKnownWordEvent, BundleSeenWord, BundleWordNote, BundleWordSet, UserDataBundle, UserDataImportReport, UserDataMergeReport, USER_DATA_BUNDLE_FORMAT, USER_DATA_BUNDLE_VERSION},
+    user_db::{UserDb, WordSetRow},
+    word_classes::WordClass,
+    word_list_import::{read_word_list_entry_v, WordListField, WordListFormat, WordListImport, WordListImportReport, WordListTarget},
+    word_notes::{WordNote, WordNoteRow, WordNoteWithText},
     error::Error,
 };
 pub(crate) use crate::langs_db::LANG_M;
@@ -40,7 +86,7 @@ impl std::fmt::Display for OnConflict {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Order {
     Ascending,
     Descending,
@@ -48,6 +94,7 @@ pub enum Order {
 }
 
 // TODO: Use appropriate type with trait with comparison operators
+#[derive(Debug, Clone, Copy)]
 pub struct Range(pub i32, pub i32);
 
 pub type Result<T> = std::result::Result<T, Error>;
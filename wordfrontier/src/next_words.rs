@@ -0,0 +1,94 @@
+use crate::ConcordanceMember;
+use std::convert::TryFrom;
+
+/// How DbHub::recommend_next_words ranks the unknown words.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NextWordRanking {
+    /// Most frequent words in the corpus first.
+    CorpusFreq,
+    /// Words which, if learned, would make the most frontier sentences (those with exactly one
+    /// unknown word) fully known come first.  Ties are broken by corpus frequency.
+    #[default]
+    UnlockedSentenceCount,
+}
+
+#[derive(Debug)]
+pub struct NextWordRecommendation {
+    pub word_rowid: i32,
+    pub word_text: String,
+    pub word_freq: i32,
+    /// Number of (translated) sentences whose only unknown word is this one.
+    pub unlocked_sentence_count: i64,
+    /// The easiest sentences containing this word, with translations.
+    pub example_sentence_v: Vec<ConcordanceMember>,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for NextWordRecommendation {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(NextWordRecommendation {
+            word_rowid: row.get(0)?,
+            word_text: row.get(1)?,
+            word_freq: row.get(2)?,
+            unlocked_sentence_count: row.get(3)?,
+            example_sentence_v: Vec::new(),
+        })
+    }
+}
+
+/// Params are ?1 = target lang_rowid, ?2 = the number of words to return.
+pub(crate) fn next_words_sql(ranking: NextWordRanking) -> String {
+    let ordering_str = match ranking {
+        NextWordRanking::CorpusFreq => "target_corpus_db.words.freq DESC, unlocked_sentence_count DESC",
+        NextWordRanking::UnlockedSentenceCount => "unlocked_sentence_count DESC, target_corpus_db.words.freq DESC",
+    };
+    format!("
+        -- For each translated sentence with exactly one unknown word, that word.
+        WITH sentence_unknown_words AS (
+            SELECT
+                target_corpus_db.sentence_memberships.sentence_rowid,
+                MIN(target_corpus_db.sentence_memberships.word_rowid) AS word_rowid
+            FROM target_corpus_db.sentence_memberships
+            LEFT JOIN
+                user_db.known_words
+                ON
+                user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+                AND
+                user_db.known_words.lang_rowid = ?1
+            WHERE
+                user_db.known_words.word_rowid IS NULL
+                AND
+                target_corpus_db.sentence_memberships.sentence_rowid IN (
+                    SELECT target_lang_sentence_rowid FROM translations_db.translations
+                )
+            GROUP BY target_corpus_db.sentence_memberships.sentence_rowid
+            HAVING COUNT(*) = 1
+        ),
+        unlocked_sentence_counts AS (
+            SELECT word_rowid, COUNT(*) AS unlocked_sentence_count
+            FROM sentence_unknown_words
+            GROUP BY word_rowid
+        )
+        SELECT
+            target_corpus_db.words.words_rowid,
+            target_corpus_db.words.text,
+            target_corpus_db.words.freq,
+            IFNULL(unlocked_sentence_counts.unlocked_sentence_count, 0) AS unlocked_sentence_count
+        FROM target_corpus_db.words
+        LEFT JOIN unlocked_sentence_counts ON unlocked_sentence_counts.word_rowid = target_corpus_db.words.words_rowid
+        LEFT JOIN
+            user_db.known_words
+            ON
+            user_db.known_words.word_rowid = target_corpus_db.words.words_rowid
+            AND
+            user_db.known_words.lang_rowid = ?1
+        WHERE
+            target_corpus_db.words.lang_rowid = ?1
+            AND
+            user_db.known_words.word_rowid IS NULL
+        ORDER BY {}, target_corpus_db.words.words_rowid ASC
+        LIMIT ?2
+    ",
+        ordering_str,
+    )
+}
@@ -0,0 +1,256 @@
+use crate::{tokenize, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
+
+/// Determines which of a corpus's n-grams (runs of consecutive words within a sentence) are
+/// kept as knowable units.  Frequent n-grams that occur much more often than their words would
+/// by chance tend to be fixed expressions, e.g. "ein bisschen" or "es gibt".
+#[derive(Clone, Debug)]
+pub struct NgramSelection {
+    /// Longest n-grams to consider; n-grams have at least 2 words.
+    pub max_word_count: usize,
+    /// Minimum number of occurrences in the corpus.
+    pub min_freq: i32,
+    /// Minimum pointwise mutual information, i.e. log2 of how many times more often the n-gram
+    /// occurs than it would if its words occurred independently.
+    pub min_pmi: f64,
+}
+
+impl Default for NgramSelection {
+    fn default() -> Self {
+        Self {
+            max_word_count: 4,
+            min_freq: 10,
+            min_pmi: 3.0,
+        }
+    }
+}
+
+/// The schema name is substituted for {}.  N-grams that are no longer selected are kept
+/// with freq 0 rather than deleted, so that their rowids, which known_ngrams refers to, are
+/// never reused for other n-grams.  ngram_sentence_memberships has a row per occurrence.
+pub(crate) const CREATE_NGRAM_TABLES_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.ngrams (
+        ngrams_rowid INTEGER PRIMARY KEY,
+        lang_rowid INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        word_count INTEGER NOT NULL,
+        freq INTEGER NOT NULL,
+        pmi REAL NOT NULL,
+        UNIQUE(lang_rowid, text)
+    );
+    CREATE TABLE IF NOT EXISTS {}.ngram_words (
+        ngram_words_rowid INTEGER PRIMARY KEY,
+        ngram_rowid INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        word_rowid INTEGER NOT NULL,
+        UNIQUE(ngram_rowid, position)
+    );
+    CREATE TABLE IF NOT EXISTS {}.ngram_sentence_memberships (
+        ngram_sentence_memberships_rowid INTEGER PRIMARY KEY,
+        sentence_rowid INTEGER NOT NULL,
+        ngram_rowid INTEGER NOT NULL,
+        -- The sentence_tokens token_index of the n-gram's first word.
+        start_token_index INTEGER NOT NULL,
+        UNIQUE(sentence_rowid, ngram_rowid, start_token_index)
+    );
+    CREATE INDEX IF NOT EXISTS {}.ngram_sentence_memberships_ngram_rowid ON ngram_sentence_memberships (ngram_rowid);
+";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NgramRow {
+    pub ngrams_rowid: i32,
+    pub lang_rowid: i32,
+    /// The words of the n-gram separated by single spaces.
+    pub text: String,
+    pub word_count: i32,
+    pub freq: i32,
+    pub pmi: f64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for NgramRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(NgramRow {
+            ngrams_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            text: row.get(2)?,
+            word_count: row.get(3)?,
+            freq: row.get(4)?,
+            pmi: row.get(5)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NgramWithKnown {
+    pub ngram_row: NgramRow,
+    pub ngram_is_known: bool,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for NgramWithKnown {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(NgramWithKnown {
+            ngram_row: NgramRow::try_from(row)?,
+            ngram_is_known: row.get(6)?,
+        })
+    }
+}
+
+pub(crate) const NGRAM_COLUMNS_SQL: &str = "
+    target_corpus_db.ngrams.ngrams_rowid,
+    target_corpus_db.ngrams.lang_rowid,
+    target_corpus_db.ngrams.text,
+    target_corpus_db.ngrams.word_count,
+    target_corpus_db.ngrams.freq,
+    target_corpus_db.ngrams.pmi
+";
+
+/// An n-gram that passed NgramSelection, as computed by select_ngram_v.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SelectedNgram {
+    pub word_rowid_v: Vec<i32>,
+    pub freq: i32,
+    pub pmi: f64,
+    /// (sentence_rowid, start_token_index) of each occurrence.
+    pub occurrence_v: Vec<(i32, usize)>,
+}
+
+/// Counts the n-grams of the given (sentence_rowid, word_rowid sequence) pairs and returns the
+/// ones selected by ngram_selection.  Since an n-gram is never more frequent than the
+/// (n-1)-grams it contains, longer n-grams are only counted where both of those passed the
+/// frequency cut-off, which keeps this tractable for large corpora.
+pub(crate) fn select_ngram_v(sentence_word_rowid_vv: &[(i32, Vec<i32>)], ngram_selection: &NgramSelection) -> Vec<SelectedNgram> {
+    let mut word_freq_m: HashMap<i32, i32> = HashMap::new();
+    for (_, word_rowid_v) in sentence_word_rowid_vv.iter() {
+        for word_rowid in word_rowid_v.iter() {
+            *word_freq_m.entry(*word_rowid).or_insert(0) += 1;
+        }
+    }
+    let token_count: i32 = word_freq_m.values().sum();
+
+    let mut selected_ngram_v = Vec::new();
+    let mut frequent_ngram_s: HashSet<&[i32]> = HashSet::new();
+    for word_count in 2..=ngram_selection.max_word_count {
+        let mut ngram_freq_m: HashMap<&[i32], i32> = HashMap::new();
+        let mut ngram_occurrence_count = 0;
+        for (_, word_rowid_v) in sentence_word_rowid_vv.iter() {
+            for ngram in word_rowid_v.windows(word_count) {
+                ngram_occurrence_count += 1;
+                if word_count > 2
+                    && !(frequent_ngram_s.contains(&ngram[..word_count - 1]) && frequent_ngram_s.contains(&ngram[1..]))
+                {
+                    continue;
+                }
+                *ngram_freq_m.entry(ngram).or_insert(0) += 1;
+            }
+        }
+        ngram_freq_m.retain(|_, freq| *freq >= ngram_selection.min_freq);
+        frequent_ngram_s = ngram_freq_m.keys().copied().collect();
+
+        for (ngram, freq) in ngram_freq_m.into_iter() {
+            let ngram_probability = freq as f64 / ngram_occurrence_count as f64;
+            let independent_probability: f64 = ngram
+                .iter()
+                .map(|word_rowid| word_freq_m[word_rowid] as f64 / token_count as f64)
+                .product();
+            let pmi = (ngram_probability / independent_probability).log2();
+            if pmi >= ngram_selection.min_pmi {
+                selected_ngram_v.push(SelectedNgram { word_rowid_v: ngram.to_vec(), freq, pmi, occurrence_v: Vec::new() });
+            }
+        }
+    }
+
+    // Now find the occurrences of each selected n-gram.
+    let selected_ngram_index_m: HashMap<Vec<i32>, usize> = selected_ngram_v
+        .iter()
+        .enumerate()
+        .map(|(index, selected_ngram)| (selected_ngram.word_rowid_v.clone(), index))
+        .collect();
+    for (sentence_rowid, word_rowid_v) in sentence_word_rowid_vv.iter() {
+        for word_count in 2..=ngram_selection.max_word_count {
+            for (start_token_index, ngram) in word_rowid_v.windows(word_count).enumerate() {
+                if let Some(&index) = selected_ngram_index_m.get(ngram) {
+                    selected_ngram_v[index].occurrence_v.push((*sentence_rowid, start_token_index));
+                }
+            }
+        }
+    }
+
+    // Most frequent first, for determinism.
+    selected_ngram_v.sort_by(|a, b| b.freq.cmp(&a.freq).then_with(|| a.word_rowid_v.cmp(&b.word_rowid_v)));
+    selected_ngram_v
+}
+
+/// (Re)computes the n-grams of the corpus in the given schema from its sentences and words
+/// tables, returning the number of n-grams selected.  N-grams keep their ngrams_rowid, so that
+/// known n-grams stay known when they're selected again.  The sentences are tokenized the same
+/// way as for sentence_tokens, so occurrences' token positions match its token_index.  This
+/// should be run in a transaction.
+pub(crate) fn populate_ngrams(conn: &rusqlite::Connection, schema: &str, lang_rowid: i32, ngram_selection: &NgramSelection) -> Result<usize> {
+    conn.execute_batch(&CREATE_NGRAM_TABLES_SQL.replace("{}", schema))?;
+
+    let word_rowid_m: HashMap<String, i32> = conn
+        .prepare(&format!("SELECT text, words_rowid FROM {}.words WHERE lang_rowid = ?1", schema))?
+        .query_map([lang_rowid], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut sentence_word_rowid_vv: Vec<(i32, Vec<i32>)> = Vec::new();
+    {
+        let mut stmt = conn.prepare(&format!("SELECT sentences_rowid, text FROM {}.sentences WHERE lang_rowid = ?1", schema))?;
+        let mut rows = stmt.query([lang_rowid])?;
+        while let Some(row) = rows.next()? {
+            let text: String = row.get(1)?;
+            let word_rowid_v = tokenize(&text).filter_map(|word_str| word_rowid_m.get(word_str).copied()).collect();
+            sentence_word_rowid_vv.push((row.get(0)?, word_rowid_v));
+        }
+    }
+    let word_text_m: HashMap<i32, &str> = word_rowid_m.iter().map(|(text, words_rowid)| (*words_rowid, text.as_str())).collect();
+
+    let selected_ngram_v = select_ngram_v(&sentence_word_rowid_vv, ngram_selection);
+    log::debug!("selected {} n-grams", selected_ngram_v.len());
+
+    conn.execute(&format!("DELETE FROM {}.ngram_words", schema), [])?;
+    conn.execute(&format!("DELETE FROM {}.ngram_sentence_memberships", schema), [])?;
+    // N-grams left with freq 0 after the upserts below are no longer selected, but are kept.
+    conn.execute(&format!("UPDATE {}.ngrams SET freq = 0 WHERE lang_rowid = ?1", schema), [lang_rowid])?;
+    {
+        let mut upsert_ngram = conn.prepare(&format!(
+            "
+            INSERT INTO {}.ngrams (lang_rowid, text, word_count, freq, pmi) VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (lang_rowid, text) DO UPDATE SET freq = excluded.freq, pmi = excluded.pmi
+            RETURNING ngrams_rowid
+            ",
+            schema,
+        ))?;
+        let mut insert_ngram_word = conn.prepare(&format!(
+            "INSERT INTO {}.ngram_words (ngram_rowid, position, word_rowid) VALUES (?1, ?2, ?3)",
+            schema,
+        ))?;
+        let mut insert_ngram_sentence_membership = conn.prepare(&format!(
+            "INSERT INTO {}.ngram_sentence_memberships (sentence_rowid, ngram_rowid, start_token_index) VALUES (?1, ?2, ?3)",
+            schema,
+        ))?;
+        for selected_ngram in selected_ngram_v.iter() {
+            let text = selected_ngram.word_rowid_v
+                .iter()
+                .map(|word_rowid| word_text_m[word_rowid])
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ngrams_rowid: i32 = upsert_ngram.query_row(
+                rusqlite::params![lang_rowid, text, selected_ngram.word_rowid_v.len() as i32, selected_ngram.freq, selected_ngram.pmi],
+                |row| row.get(0),
+            )?;
+            for (position, word_rowid) in selected_ngram.word_rowid_v.iter().enumerate() {
+                insert_ngram_word.execute([ngrams_rowid, position as i32, *word_rowid])?;
+            }
+            for (sentence_rowid, start_token_index) in selected_ngram.occurrence_v.iter() {
+                insert_ngram_sentence_membership.execute([*sentence_rowid, ngrams_rowid, *start_token_index as i32])?;
+            }
+        }
+    }
+
+    Ok(selected_ngram_v.len())
+}
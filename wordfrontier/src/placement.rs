@@ -0,0 +1,235 @@
+use crate::Range;
+
+/// Parameters of a placement test, which estimates how many words a new user already knows by
+/// asking about a sample of words from each frequency band.  See DbHub::start_placement_test.
+#[derive(Clone, Debug)]
+pub struct PlacementTestConfig {
+    /// The bands, as numbers of most frequent words, e.g. [100, 1000] gives the bands of the top
+    /// 100 words, the next 900, and all the remaining words.
+    pub band_top_word_count_v: Vec<u32>,
+    /// Each band is asked about at least this many times (if it has that many words).
+    pub min_questions_per_band: usize,
+    /// Each band is asked about at most this many times.
+    pub max_questions_per_band: usize,
+    /// Questions about a band stop once the 95% confidence interval of its known fraction
+    /// extends at most this far on either side of the estimate.
+    pub target_margin: f64,
+    /// Once a band's known fraction is estimated below this, the less frequent bands are
+    /// assumed unknown and not asked about.
+    pub stop_below_known_fraction: f64,
+    /// Determines which words are sampled.
+    pub seed: u64,
+}
+
+impl Default for PlacementTestConfig {
+    fn default() -> Self {
+        Self {
+            band_top_word_count_v: vec![100, 250, 500, 1000, 2000, 4000, 8000, 16000],
+            min_questions_per_band: 8,
+            max_questions_per_band: 20,
+            target_margin: 0.15,
+            stop_below_known_fraction: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+/// A "do you know this word?" question of a placement test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlacementQuestion {
+    pub band_index: usize,
+    pub word_rowid: i32,
+    pub word_text: String,
+}
+
+pub(crate) struct PlacementBand {
+    /// 1-based frequency ranks of the first and last words of the band.
+    pub rank_range: Range,
+    /// The sampled words, in the order they're asked about.
+    pub question_v: Vec<PlacementQuestion>,
+    /// The answers to the first answer_v.len() elements of question_v.
+    pub answer_v: Vec<bool>,
+}
+
+/// A placement test in progress.  The client asks next_question_o, gives the user's answer to
+/// answer, and repeats until there are no more questions (or the user has had enough), then
+/// passes the test to DbHub::apply_placement_test.  Bands are asked about from the most to the
+/// least frequent, and each only until its estimate is confident enough.
+pub struct PlacementTest {
+    pub(crate) config: PlacementTestConfig,
+    pub(crate) band_v: Vec<PlacementBand>,
+    band_index: usize,
+    is_finished: bool,
+}
+
+/// Estimated vocabulary within one frequency band.
+#[derive(Clone, Debug)]
+pub struct PlacementBandEstimate {
+    /// 1-based frequency ranks of the first and last words of the band.
+    pub rank_range: Range,
+    pub word_count: i64,
+    pub answer_count: usize,
+    pub known_answer_count: usize,
+    /// Estimated fraction of the band's words that the user knows.  This is 0 for bands that
+    /// weren't asked about.
+    pub known_fraction: f64,
+    /// The 95% confidence interval of known_fraction (the Wilson score interval).  For bands
+    /// that weren't asked about, the upper bound is that of the previous band, on the assumption
+    /// that less frequent words aren't better known.
+    pub known_fraction_low: f64,
+    pub known_fraction_high: f64,
+    pub estimated_known_word_count: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlacementEstimate {
+    pub band_estimate_v: Vec<PlacementBandEstimate>,
+    /// The sums over the bands.
+    pub estimated_known_word_count: i64,
+    pub estimated_known_word_count_low: i64,
+    pub estimated_known_word_count_high: i64,
+}
+
+// z for a 95% confidence interval.
+const Z: f64 = 1.96;
+
+/// Returns the Wilson score interval for known_count successes out of count trials.
+fn wilson_interval(known_count: usize, count: usize) -> (f64, f64) {
+    if count == 0 {
+        return (0.0, 1.0);
+    }
+    let n = count as f64;
+    let p = known_count as f64 / n;
+    let denominator = 1.0 + Z * Z / n;
+    let center = (p + Z * Z / (2.0 * n)) / denominator;
+    let margin = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / denominator;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+impl PlacementBand {
+    fn word_count(&self) -> i64 {
+        (self.rank_range.1 - self.rank_range.0 + 1) as i64
+    }
+    fn known_answer_count(&self) -> usize {
+        self.answer_v.iter().filter(|&&knows_word| knows_word).count()
+    }
+    fn known_fraction(&self) -> f64 {
+        crate::stats::ratio(self.known_answer_count() as i64, self.answer_v.len() as i64)
+    }
+    fn is_done(&self, config: &PlacementTestConfig) -> bool {
+        let answer_count = self.answer_v.len();
+        if answer_count >= self.question_v.len() || answer_count >= config.max_questions_per_band {
+            return true;
+        }
+        if answer_count < config.min_questions_per_band {
+            return false;
+        }
+        let (known_fraction_low, known_fraction_high) = wilson_interval(self.known_answer_count(), answer_count);
+        let known_fraction = self.known_fraction();
+        (known_fraction - known_fraction_low).max(known_fraction_high - known_fraction) <= config.target_margin
+    }
+}
+
+impl PlacementTest {
+    /// Bands without words are dropped.
+    pub(crate) fn new(config: PlacementTestConfig, band_v: Vec<PlacementBand>) -> Self {
+        let band_v: Vec<PlacementBand> = band_v.into_iter().filter(|band| !band.question_v.is_empty()).collect();
+        let is_finished = band_v.is_empty();
+        Self { config, band_v, band_index: 0, is_finished }
+    }
+    /// Returns the question to ask next, or None if the test is finished.
+    pub fn next_question_o(&self) -> Option<&PlacementQuestion> {
+        if self.is_finished {
+            return None;
+        }
+        let band = &self.band_v[self.band_index];
+        band.question_v.get(band.answer_v.len())
+    }
+    /// Records the answer to the question returned by next_question_o.  Does nothing if the
+    /// test is finished.
+    pub fn answer(&mut self, knows_word: bool) {
+        if self.is_finished {
+            return;
+        }
+        let band = &mut self.band_v[self.band_index];
+        band.answer_v.push(knows_word);
+        if band.is_done(&self.config) {
+            if band.known_fraction() < self.config.stop_below_known_fraction || self.band_index + 1 == self.band_v.len() {
+                self.is_finished = true;
+            } else {
+                self.band_index += 1;
+            }
+        }
+    }
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+    pub fn answer_count(&self) -> usize {
+        self.band_v.iter().map(|band| band.answer_v.len()).sum()
+    }
+    /// Estimates the user's vocabulary from the answers so far.
+    pub fn estimate(&self) -> PlacementEstimate {
+        let mut band_estimate_v = Vec::with_capacity(self.band_v.len());
+        let mut previous_known_fraction_high = 1.0;
+        for band in self.band_v.iter() {
+            let word_count = band.word_count();
+            let answer_count = band.answer_v.len();
+            let known_answer_count = band.known_answer_count();
+            let (known_fraction, known_fraction_low, known_fraction_high) = if answer_count > 0 {
+                let (known_fraction_low, known_fraction_high) = wilson_interval(known_answer_count, answer_count);
+                (band.known_fraction(), known_fraction_low, known_fraction_high)
+            } else {
+                (0.0, 0.0, previous_known_fraction_high)
+            };
+            previous_known_fraction_high = known_fraction_high;
+            band_estimate_v.push(PlacementBandEstimate {
+                rank_range: band.rank_range,
+                word_count,
+                answer_count,
+                known_answer_count,
+                known_fraction,
+                known_fraction_low,
+                known_fraction_high,
+                estimated_known_word_count: (known_fraction * word_count as f64).round() as i64,
+            });
+        }
+        let sum = |f: &dyn Fn(&PlacementBandEstimate) -> f64| -> i64 {
+            band_estimate_v.iter().map(|band_estimate| (f(band_estimate) * band_estimate.word_count as f64).round() as i64).sum()
+        };
+        PlacementEstimate {
+            estimated_known_word_count: band_estimate_v.iter().map(|band_estimate| band_estimate.estimated_known_word_count).sum(),
+            estimated_known_word_count_low: sum(&|band_estimate| band_estimate.known_fraction_low),
+            estimated_known_word_count_high: sum(&|band_estimate| band_estimate.known_fraction_high),
+            band_estimate_v,
+        }
+    }
+}
+
+/// Params are ?1 = target lang_rowid, ?2 and ?3 = the first and last frequency ranks.
+/// Produces (words_rowid, text) rows in frequency order.
+pub(crate) const BAND_WORDS_SQL: &str = "
+    SELECT words_rowid, text
+    FROM (
+        SELECT
+            target_corpus_db.words.words_rowid,
+            target_corpus_db.words.text,
+            ROW_NUMBER() OVER (ORDER BY target_corpus_db.words.freq DESC, target_corpus_db.words.words_rowid ASC) AS freq_rank
+        FROM target_corpus_db.words
+        WHERE target_corpus_db.words.lang_rowid = ?1
+    )
+    WHERE freq_rank BETWEEN ?2 AND ?3
+";
+
+/// Returns the 1-based frequency rank ranges of the bands, given the number of words.
+pub(crate) fn band_rank_range_v(band_top_word_count_v: &[u32], word_count: i64) -> Vec<Range> {
+    let mut band_rank_range_v = Vec::new();
+    let mut first_rank = 1;
+    for &top_word_count in band_top_word_count_v.iter().chain(std::iter::once(&u32::MAX)) {
+        let last_rank = (top_word_count as i64).min(word_count) as i32;
+        if last_rank >= first_rank {
+            band_rank_range_v.push(Range(first_rank, last_rank));
+            first_rank = last_rank + 1;
+        }
+    }
+    band_rank_range_v
+}
@@ -0,0 +1,83 @@
+use std::convert::TryFrom;
+
+/// The statistics of one day, as recorded by DbHub::record_progress_snapshot.  Days are
+/// 'YYYY-MM-DD' in local time.
+#[derive(Debug)]
+pub struct ProgressSnapshotRow {
+    pub progress_snapshots_rowid: i32,
+    pub lang_rowid: i32,
+    pub day: String,
+    pub known_word_count: i64,
+    pub type_coverage: f64,
+    pub token_coverage: f64,
+    /// Number of sentences in the frontier of sentences with exactly 1 unknown word.
+    pub frontier_sentence_count: i64,
+    /// Unix time of the latest recording on that day.
+    pub recorded_at: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for ProgressSnapshotRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(ProgressSnapshotRow {
+            progress_snapshots_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            day: row.get(2)?,
+            known_word_count: row.get(3)?,
+            type_coverage: row.get(4)?,
+            token_coverage: row.get(5)?,
+            frontier_sentence_count: row.get(6)?,
+            recorded_at: row.get(7)?,
+        })
+    }
+}
+
+/// Known word changes on one day, reconstructed from the known word event history.
+#[derive(Debug, PartialEq)]
+pub struct KnownWordTimelinePoint {
+    pub day: String,
+    pub added_count: i64,
+    pub removed_count: i64,
+    /// Number of known words at the end of the day.
+    pub known_word_count: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for KnownWordTimelinePoint {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(KnownWordTimelinePoint {
+            day: row.get(0)?,
+            added_count: row.get(1)?,
+            removed_count: row.get(2)?,
+            known_word_count: row.get(3)?,
+        })
+    }
+}
+
+/// Param is ?1 = target lang_rowid.  Words that were known before events were recorded are
+/// accounted for by offsetting the running total so that it ends at the current count.
+pub(crate) const KNOWN_WORD_TIMELINE_SQL: &str = "
+    WITH daily_counts AS (
+        SELECT
+            date(occurred_at, 'unixepoch', 'localtime') AS day,
+            SUM(is_added) AS added_count,
+            SUM(NOT is_added) AS removed_count
+        FROM user_db.known_word_events
+        WHERE lang_rowid = ?1
+        GROUP BY day
+    ),
+    baseline AS (
+        SELECT
+            (SELECT COUNT(*) FROM user_db.known_words WHERE lang_rowid = ?1)
+            -
+            (SELECT IFNULL(SUM(added_count - removed_count), 0) FROM daily_counts)
+            AS known_word_count
+    )
+    SELECT
+        day,
+        added_count,
+        removed_count,
+        (SELECT known_word_count FROM baseline) + SUM(added_count - removed_count) OVER (ORDER BY day) AS known_word_count
+    FROM daily_counts
+    ORDER BY day
+";
@@ -0,0 +1,186 @@
+use crate::{
+    sentence_export::escape_html, tatoeba_sentence_url, Error, ExportSentence, Result,
+    TATOEBA_LICENSE, TATOEBA_LICENSE_URL,
+};
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadingPacketFormat {
+    /// A standalone HTML document, styled for printing, with the answer key on its own page.
+    Html,
+    Markdown,
+}
+
+impl std::fmt::Display for ReadingPacketFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            ReadingPacketFormat::Html => write!(f, "html"),
+            ReadingPacketFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReadingPacketFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "html" => Ok(ReadingPacketFormat::Html),
+            "markdown" => Ok(ReadingPacketFormat::Markdown),
+            _ => Err(anyhow::anyhow!("unknown reading packet format {:#?}", s))?,
+        }
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The distinct unknown words of the sentences, with their glosses, sorted case-insensitively.
+fn glossary_entry_v(export_sentence_v: &[ExportSentence]) -> Vec<(&str, &str)> {
+    let mut glossary_entry_v: Vec<(&str, &str)> = Vec::new();
+    for export_word in export_sentence_v.iter().flat_map(|export_sentence| export_sentence.unknown_word_v.iter()) {
+        if !glossary_entry_v.iter().any(|&(word_text, _)| word_text == export_word.word_text) {
+            glossary_entry_v.push((&export_word.word_text, &export_word.gloss));
+        }
+    }
+    glossary_entry_v.sort_by_cached_key(|&(word_text, _)| (word_text.to_lowercase(), word_text));
+    glossary_entry_v
+}
+
+/// Renders a worksheet of the sentences (see DbHub::query_export_sentence_v): the numbered
+/// sentences with their unknown words highlighted, a glossary of the unknown words, the
+/// translations as an answer key, and the attribution that the sentences' license requires.
+pub fn render_reading_packet(title: &str, export_sentence_v: &[ExportSentence], format: ReadingPacketFormat) -> String {
+    match format {
+        ReadingPacketFormat::Html => render_html(title, export_sentence_v),
+        ReadingPacketFormat::Markdown => render_markdown(title, export_sentence_v),
+    }
+}
+
+// Writing to a String can't fail, so the results of write! are ignored below.
+
+fn render_markdown(title: &str, export_sentence_v: &[ExportSentence]) -> String {
+    let mut s = String::new();
+    let _ = writeln!(s, "# {}\n", escape_markdown(title));
+
+    let _ = writeln!(s, "## Sentences\n");
+    for (index, export_sentence) in export_sentence_v.iter().enumerate() {
+        let _ = writeln!(s, "{}. {}", index + 1, export_sentence.highlighted_text(escape_markdown, |text| format!("**{}**", text)));
+    }
+
+    let _ = writeln!(s, "\n## Glossary\n");
+    for (word_text, gloss) in glossary_entry_v(export_sentence_v) {
+        if gloss.is_empty() {
+            let _ = writeln!(s, "- **{}**", escape_markdown(word_text));
+        } else {
+            let _ = writeln!(s, "- **{}**: {}", escape_markdown(word_text), escape_markdown(gloss));
+        }
+    }
+
+    let _ = writeln!(s, "\n## Answer Key\n");
+    for (index, export_sentence) in export_sentence_v.iter().enumerate() {
+        let translations = if export_sentence.translation_v.is_empty() {
+            "*(no translation)*".to_string()
+        } else {
+            export_sentence.translation_v
+                .iter()
+                .map(|translation| escape_markdown(&translation.reference_lang_sentence_text))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        };
+        let _ = writeln!(s, "{}. {}", index + 1, translations);
+    }
+
+    let _ = writeln!(s, "\n## Attribution\n");
+    let _ = writeln!(
+        s,
+        "The sentences and translations are from [Tatoeba](https://tatoeba.org), licensed under [{}]({}).  \
+        Their authors are credited on their Tatoeba pages:\n",
+        TATOEBA_LICENSE,
+        TATOEBA_LICENSE_URL,
+    );
+    for (index, export_sentence) in export_sentence_v.iter().enumerate() {
+        let _ = writeln!(
+            s,
+            "{}. {}",
+            index + 1,
+            export_sentence.source_sentence_rowid_v()
+                .into_iter()
+                .map(|sentence_rowid| format!("[#{}]({})", sentence_rowid, tatoeba_sentence_url(sentence_rowid)))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    s
+}
+
+const HTML_STYLE: &str = "
+    body { font-family: serif; max-width: 40em; margin: 2em auto; line-height: 1.6; }
+    mark { background: #fff3a0; font-weight: bold; }
+    .answer-key { page-break-before: always; }
+    .attribution { font-size: small; }
+";
+
+fn render_html(title: &str, export_sentence_v: &[ExportSentence]) -> String {
+    let title = escape_html(title);
+    let mut s = String::new();
+    let _ = writeln!(s, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(s, "<title>{}</title>\n<style>{}</style>\n</head>\n<body>", title, HTML_STYLE);
+    let _ = writeln!(s, "<h1>{}</h1>", title);
+
+    let _ = writeln!(s, "<h2>Sentences</h2>\n<ol class=\"sentences\">");
+    for export_sentence in export_sentence_v.iter() {
+        let _ = writeln!(s, "<li>{}</li>", export_sentence.highlighted_text(escape_html, |text| format!("<mark>{}</mark>", text)));
+    }
+    let _ = writeln!(s, "</ol>");
+
+    let _ = writeln!(s, "<h2>Glossary</h2>\n<dl class=\"glossary\">");
+    for (word_text, gloss) in glossary_entry_v(export_sentence_v) {
+        let _ = writeln!(s, "<dt>{}</dt><dd>{}</dd>", escape_html(word_text), escape_html(gloss));
+    }
+    let _ = writeln!(s, "</dl>");
+
+    let _ = writeln!(s, "<section class=\"answer-key\">\n<h2>Answer Key</h2>\n<ol>");
+    for export_sentence in export_sentence_v.iter() {
+        let translations = if export_sentence.translation_v.is_empty() {
+            "<em>(no translation)</em>".to_string()
+        } else {
+            export_sentence.translation_v
+                .iter()
+                .map(|translation| escape_html(&translation.reference_lang_sentence_text))
+                .collect::<Vec<_>>()
+                .join(" / ")
+        };
+        let _ = writeln!(s, "<li>{}</li>", translations);
+    }
+    let _ = writeln!(s, "</ol>\n</section>");
+
+    let _ = writeln!(s, "<section class=\"attribution\">\n<h2>Attribution</h2>");
+    let _ = writeln!(
+        s,
+        "<p>The sentences and translations are from <a href=\"https://tatoeba.org\">Tatoeba</a>, licensed under \
+        <a href=\"{}\">{}</a>.  Their authors are credited on their Tatoeba pages:</p>\n<ol>",
+        TATOEBA_LICENSE_URL,
+        TATOEBA_LICENSE,
+    );
+    for export_sentence in export_sentence_v.iter() {
+        let _ = writeln!(
+            s,
+            "<li>{}</li>",
+            export_sentence.source_sentence_rowid_v()
+                .into_iter()
+                .map(|sentence_rowid| format!("<a href=\"{}\">#{}</a>", tatoeba_sentence_url(sentence_rowid), sentence_rowid))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    let _ = writeln!(s, "</ol>\n</section>\n</body>\n</html>");
+    s
+}
@@ -0,0 +1,116 @@
+use crate::{Result, SentenceSpan, TranslationWithText};
+use std::path::Path;
+
+/// The license of the tatoeba.org sentences (and so of exports containing them).
+pub const TATOEBA_LICENSE: &str = "CC BY 2.0 FR";
+pub const TATOEBA_LICENSE_URL: &str = "https://creativecommons.org/licenses/by/2.0/fr/";
+
+/// Returns the tatoeba.org page of a sentence, which credits its author.  Corpus sentence rowids
+/// are tatoeba.org sentence ids.
+pub fn tatoeba_sentence_url(sentence_rowid: i32) -> String {
+    format!("https://tatoeba.org/en/sentences/show/{}", sentence_rowid)
+}
+
+/// An unknown word of an ExportSentence.
+#[derive(Debug)]
+pub struct ExportWord {
+    pub word_rowid: i32,
+    pub word_text: String,
+    /// See SentenceMembershipWithTextEtc::preferred_gloss; empty if there's none.
+    pub gloss: String,
+}
+
+/// A word frontier sentence with what's needed to study it outside the app.  See
+/// DbHub::query_export_sentence_v.
+#[derive(Debug)]
+pub struct ExportSentence {
+    pub sentence_rowid: i32,
+    pub text: String,
+    pub span_v: Vec<SentenceSpan>,
+    /// The distinct unknown words, in order of first occurrence.
+    pub unknown_word_v: Vec<ExportWord>,
+    pub translation_v: Vec<TranslationWithText>,
+}
+
+impl ExportSentence {
+    /// Returns the text with each span passed through escape, and the unknown words' spans
+    /// additionally passed through highlight.
+    pub fn highlighted_text(&self, escape: impl Fn(&str) -> String, highlight: impl Fn(&str) -> String) -> String {
+        self.span_v
+            .iter()
+            .map(|span| match &span.token_o {
+                Some(token) if !token.word_is_known => highlight(&escape(&span.text)),
+                _ => escape(&span.text),
+            })
+            .collect()
+    }
+    /// Returns the tatoeba.org sentence ids of the sentence and its translations, whose pages
+    /// credit their authors, as the sentences' license requires.
+    pub fn source_sentence_rowid_v(&self) -> Vec<i32> {
+        std::iter::once(self.sentence_rowid)
+            .chain(self.translation_v.iter().map(|translation| translation.reference_lang_sentence_rowid))
+            .collect()
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Returns the GUID of the Anki note of a sentence.  Since it only depends on the sentence,
+/// importing a re-export updates the existing notes instead of adding duplicates.
+pub fn anki_note_guid(lang_short: &str, sentence_rowid: i32) -> String {
+    format!("wordfrontier-{}-{}", lang_short, sentence_rowid)
+}
+
+/// Column names of write_anki_tsv's output; the GUID and tags columns are identified to Anki by
+/// the file's headers, and the rest map to the note type's fields in order.
+pub const ANKI_TSV_COLUMN_V: [&str; 7] = ["GUID", "Sentence", "Unknown Words", "Translation", "Gloss", "Attribution", "Tags"];
+
+/// Writes the sentences as an Anki-importable (Anki 2.1.55 or later) tab-separated file of notes
+/// with HTML fields.  The unknown words are bold in the sentence field.  Attributes are
+/// single-quoted so that fields don't need quoting.
+pub fn write_anki_tsv(anki_tsv_p: &Path, lang_short: &str, export_sentence_v: &[ExportSentence]) -> Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(anki_tsv_p)?);
+    {
+        use std::io::Write;
+        writeln!(file, "#separator:tab")?;
+        writeln!(file, "#html:true")?;
+        writeln!(file, "#guid column:1")?;
+        writeln!(file, "#tags column:{}", ANKI_TSV_COLUMN_V.len())?;
+        writeln!(file, "#columns:{}", ANKI_TSV_COLUMN_V.join("\t"))?;
+    }
+    let mut writer = csv::WriterBuilder::new().delimiter(b'\t').has_headers(false).from_writer(file);
+    for export_sentence in export_sentence_v.iter() {
+        let attribution = format!(
+            "Sentences {} from <a href='https://tatoeba.org'>Tatoeba</a>, licensed under <a href='{}'>{}</a>",
+            export_sentence.source_sentence_rowid_v()
+                .into_iter()
+                .map(|sentence_rowid| format!("<a href='{}'>#{}</a>", tatoeba_sentence_url(sentence_rowid), sentence_rowid))
+                .collect::<Vec<_>>()
+                .join(", "),
+            TATOEBA_LICENSE_URL,
+            TATOEBA_LICENSE,
+        );
+        writer.write_record(&[
+            anki_note_guid(lang_short, export_sentence.sentence_rowid),
+            export_sentence.highlighted_text(escape_html, |text| format!("<b>{}</b>", text)),
+            export_sentence.unknown_word_v.iter().map(|word| escape_html(&word.word_text)).collect::<Vec<_>>().join(", "),
+            export_sentence.translation_v
+                .iter()
+                .map(|translation| escape_html(&translation.reference_lang_sentence_text))
+                .collect::<Vec<_>>()
+                .join("<br>"),
+            export_sentence.unknown_word_v
+                .iter()
+                .filter(|word| !word.gloss.is_empty())
+                .map(|word| format!("<b>{}</b>: {}", escape_html(&word.word_text), escape_html(&word.gloss)))
+                .collect::<Vec<_>>()
+                .join("<br>"),
+            attribution,
+            format!("wordfrontier {}", lang_short),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use crate::{computed_tables, tokenize_with_byte_offsets, Result};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// Unlike sentence_memberships, which is the set of words of each sentence, this has a row for
+/// each token of each sentence, in order.  The schema name is substituted for {}.  Corpus DBs
+/// created by older versions don't have this table yet.
+pub(crate) const CREATE_SENTENCE_TOKENS_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.sentence_tokens (
+        sentence_tokens_rowid INTEGER PRIMARY KEY,
+        sentence_rowid INTEGER NOT NULL,
+        token_index INTEGER NOT NULL,
+        byte_offset INTEGER NOT NULL,
+        surface TEXT NOT NULL,
+        word_rowid INTEGER NOT NULL,
+        UNIQUE(sentence_rowid, token_index)
+    )
+";
+
+#[derive(Debug, PartialEq)]
+pub struct SentenceTokenRow {
+    pub sentence_tokens_rowid: i32,
+    pub sentence_rowid: i32,
+    /// 0-based position of the token within the sentence.
+    pub token_index: i32,
+    /// Byte offset of the token within the sentence text.
+    pub byte_offset: i32,
+    /// The token as it appears in the sentence text.
+    pub surface: String,
+    pub word_rowid: i32,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for SentenceTokenRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(SentenceTokenRow {
+            sentence_tokens_rowid: row.get(0)?,
+            sentence_rowid: row.get(1)?,
+            token_index: row.get(2)?,
+            byte_offset: row.get(3)?,
+            surface: row.get(4)?,
+            word_rowid: row.get(5)?,
+        })
+    }
+}
+
+/// The token that a SentenceSpan consists of.
+#[derive(Debug, PartialEq)]
+pub struct SentenceSpanToken {
+    pub token_index: i32,
+    pub word_rowid: i32,
+    pub word_is_known: bool,
+}
+
+/// A piece of a sentence's text, which is either a token or the text between tokens (spaces
+/// and punctuation).  The spans of a sentence concatenate to its text.
+#[derive(Debug, PartialEq)]
+pub struct SentenceSpan {
+    /// Byte offset of the span within the sentence text.
+    pub byte_offset: usize,
+    pub text: String,
+    /// None for text between tokens.
+    pub token_o: Option<SentenceSpanToken>,
+}
+
+/// Splits text into spans at the given (byte_offset, surface length, token) triples, which
+/// must be in order and not overlap.
+pub(crate) fn sentence_span_v(text: &str, token_v: Vec<(usize, usize, SentenceSpanToken)>) -> Vec<SentenceSpan> {
+    let mut sentence_span_v = Vec::with_capacity(2 * token_v.len() + 1);
+    let mut end = 0;
+    for (byte_offset, len, token) in token_v.into_iter() {
+        if byte_offset > end {
+            sentence_span_v.push(SentenceSpan { byte_offset: end, text: text[end..byte_offset].into(), token_o: None });
+        }
+        sentence_span_v.push(SentenceSpan {
+            byte_offset,
+            text: text[byte_offset..byte_offset + len].into(),
+            token_o: Some(token),
+        });
+        end = byte_offset + len;
+    }
+    if end < text.len() {
+        sentence_span_v.push(SentenceSpan { byte_offset: end, text: text[end..].into(), token_o: None });
+    }
+    sentence_span_v
+}
+
+/// (Re)computes the sentence_tokens table of the corpus in the given schema from its
+/// sentences and words tables, tokenizing the same way as the corpus import.  This should be
+/// run in a transaction.
+pub(crate) fn populate_sentence_tokens(conn: &rusqlite::Connection, schema: &str, lang_rowid: i32) -> Result<()> {
+    conn.execute(&CREATE_SENTENCE_TOKENS_TABLE_SQL.replace("{}", schema), [])?;
+    conn.execute(
+        &format!(
+            "
+            DELETE FROM {schema}.sentence_tokens
+            WHERE sentence_rowid IN (SELECT sentences_rowid FROM {schema}.sentences WHERE lang_rowid = ?1)
+            ",
+            schema = schema,
+        ),
+        [lang_rowid],
+    )?;
+
+    let word_rowid_m: HashMap<String, i32> = conn
+        .prepare(&format!("SELECT text, words_rowid FROM {}.words WHERE lang_rowid = ?1", schema))?
+        .query_map([lang_rowid], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut insert_sentence_token = conn.prepare(&format!(
+        "INSERT INTO {}.sentence_tokens (sentence_rowid, token_index, byte_offset, surface, word_rowid) VALUES (?1, ?2, ?3, ?4, ?5)",
+        schema,
+    ))?;
+    let mut stmt = conn.prepare(&format!("SELECT sentences_rowid, text FROM {}.sentences WHERE lang_rowid = ?1", schema))?;
+    let mut rows = stmt.query([lang_rowid])?;
+    while let Some(row) = rows.next()? {
+        let sentences_rowid: i32 = row.get(0)?;
+        let text: String = row.get(1)?;
+        let token_i = tokenize_with_byte_offsets(&text)
+            .filter_map(|(byte_offset, word_str)| word_rowid_m.get(word_str).map(|word_rowid| (byte_offset, word_str, *word_rowid)));
+        for (token_index, (byte_offset, word_str, word_rowid)) in token_i.enumerate() {
+            insert_sentence_token.execute(rusqlite::params![sentences_rowid, token_index as i32, byte_offset as i32, word_str, word_rowid])?;
+        }
+    }
+    computed_tables::mark_table_computed(conn, schema, "sentence_tokens")?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use crate::{fold_text, tokenize, Result};
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+
+/// The SplitMix64 mixing function -- a cheap, well-distributed hash of a 64-bit value.  This is
+/// what makes seeded shuffles reproducible across runs and platforms, unlike SQLite's RANDOM().
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Returns the position of rowid in a pseudo-random permutation determined by seed.  Ordering
+/// by this key gives a deterministic shuffle.
+pub(crate) fn shuffle_key(seed: u64, rowid: i64) -> i64 {
+    splitmix64(seed ^ splitmix64(rowid as u64)) as i64
+}
+
+/// Aggregate producing a canonical text key for the set of (non-NULL) integers it's given,
+/// e.g. 3, 1, 3 gives "1,3".  Returns NULL for the empty set.  SQLite's own group_concat
+/// doesn't guarantee an order, so it can't be used to compare sets.
+struct SetKey;
+
+impl Aggregate<Vec<i64>, Option<String>> for SetKey {
+    fn init(&self, _: &mut Context<'_>) -> rusqlite::Result<Vec<i64>> {
+        Ok(Vec::new())
+    }
+    fn step(&self, ctx: &mut Context<'_>, value_v: &mut Vec<i64>) -> rusqlite::Result<()> {
+        if let Some(value) = ctx.get::<Option<i64>>(0)? {
+            value_v.push(value);
+        }
+        Ok(())
+    }
+    fn finalize(&self, _: &mut Context<'_>, value_vo: Option<Vec<i64>>) -> rusqlite::Result<Option<String>> {
+        let mut value_v = value_vo.unwrap_or_default();
+        if value_v.is_empty() {
+            return Ok(None);
+        }
+        value_v.sort_unstable();
+        value_v.dedup();
+        Ok(Some(value_v.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(",")))
+    }
+}
+
+/// Registers the custom SQL functions used by DbHub queries on the given connection.
+pub(crate) fn register(conn: &rusqlite::Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "shuffle_key",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let seed = ctx.get::<i64>(0)?;
+            let rowid = ctx.get::<i64>(1)?;
+            Ok(shuffle_key(seed as u64, rowid))
+        },
+    )?;
+    conn.create_scalar_function(
+        "token_count",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<String>(0)?;
+            Ok(tokenize(&text).count() as i64)
+        },
+    )?;
+    conn.create_scalar_function(
+        "fold_text",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<String>(0)?;
+            Ok(fold_text(&text))
+        },
+    )?;
+    // This is what the REGEXP operator calls; `X REGEXP Y` is regexp(Y, X).  The compiled
+    // pattern is cached by SQLite for the duration of the statement.
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let regex = ctx.get_or_create_aux(0, |pattern_value| -> std::result::Result<_, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(regex::Regex::new(pattern_value.as_str()?)?)
+            })?;
+            let text = ctx.get::<String>(1)?;
+            Ok(regex.is_match(&text))
+        },
+    )?;
+    conn.create_aggregate_function(
+        "set_key",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        SetKey,
+    )?;
+    Ok(())
+}
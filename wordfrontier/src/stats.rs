@@ -0,0 +1,110 @@
+use std::convert::TryFrom;
+
+/// The frequency bands reported by DbHub::stats, as numbers of most frequent words.
+pub const FREQUENCY_BAND_TOP_WORD_COUNT_V: [u32; 3] = [100, 1000, 5000];
+
+/// Vocabulary coverage of the target language corpus.  Token-weighted figures count each
+/// occurrence of a word (i.e. are weighted by words.freq), type-weighted ones count each
+/// distinct word once.
+#[derive(Debug)]
+pub struct VocabularyStats {
+    pub known_word_count: i64,
+    pub word_count: i64,
+    pub token_count: i64,
+    pub known_token_count: i64,
+    /// known_word_count / word_count
+    pub type_coverage: f64,
+    /// known_token_count / token_count
+    pub token_coverage: f64,
+    pub sentence_count: i64,
+    /// Number of sentences with 0, 1, 2 and 3-or-more unknown words, respectively.
+    pub sentence_count_by_unknown_word_count: [i64; 4],
+    /// One element for each of FREQUENCY_BAND_TOP_WORD_COUNT_V.
+    pub frequency_band_coverage_v: Vec<FrequencyBandCoverage>,
+}
+
+/// Coverage of the top_word_count most frequent words of the corpus.
+#[derive(Debug)]
+pub struct FrequencyBandCoverage {
+    pub top_word_count: u32,
+    /// Can be less than top_word_count if the corpus is small.
+    pub word_count: i64,
+    pub known_word_count: i64,
+    /// known_word_count / word_count
+    pub type_coverage: f64,
+    /// Fraction of the occurrences of words in this band that are of known words.
+    pub token_coverage: f64,
+}
+
+pub(crate) struct CoverageCounts {
+    pub word_count: i64,
+    pub token_count: i64,
+    pub known_word_count: i64,
+    pub known_token_count: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for CoverageCounts {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(CoverageCounts {
+            word_count: row.get(0)?,
+            token_count: row.get(1)?,
+            known_word_count: row.get(2)?,
+            known_token_count: row.get(3)?,
+        })
+    }
+}
+
+pub(crate) fn ratio(numerator: i64, denominator: i64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Params are ?1 = target lang_rowid, ?2 = band size (-1 for all words).
+pub(crate) const COVERAGE_COUNTS_SQL: &str = "
+    WITH ranked_words AS (
+        SELECT
+            target_corpus_db.words.freq,
+            user_db.known_words.word_rowid IS NOT NULL AS word_is_known,
+            ROW_NUMBER() OVER (ORDER BY target_corpus_db.words.freq DESC, target_corpus_db.words.words_rowid ASC) AS freq_rank
+        FROM target_corpus_db.words
+        LEFT JOIN
+            user_db.known_words
+            ON
+            user_db.known_words.word_rowid = target_corpus_db.words.words_rowid
+            AND
+            user_db.known_words.lang_rowid = ?1
+        WHERE target_corpus_db.words.lang_rowid = ?1
+    )
+    SELECT
+        COUNT(*),
+        IFNULL(SUM(freq), 0),
+        IFNULL(SUM(word_is_known), 0),
+        IFNULL(SUM(CASE WHEN word_is_known THEN freq ELSE 0 END), 0)
+    FROM ranked_words
+    WHERE ?2 < 0 OR freq_rank <= ?2
+";
+
+/// Param is ?1 = target lang_rowid.  Produces (bucket, sentence count) rows, where bucket is
+/// the unknown word count, capped at 3.
+pub(crate) const SENTENCE_COUNT_BY_UNKNOWN_WORD_COUNT_SQL: &str = "
+    SELECT MIN(unknown_word_count, 3) AS bucket, COUNT(*)
+    FROM (
+        SELECT
+            COUNT(CASE WHEN target_corpus_db.sentence_memberships.word_rowid IS NOT NULL AND user_db.known_words.word_rowid IS NULL THEN 1 END) AS unknown_word_count
+        FROM target_corpus_db.sentences
+        LEFT JOIN target_corpus_db.sentence_memberships ON target_corpus_db.sentence_memberships.sentence_rowid = target_corpus_db.sentences.sentences_rowid
+        LEFT JOIN
+            user_db.known_words
+            ON
+            user_db.known_words.word_rowid = target_corpus_db.sentence_memberships.word_rowid
+            AND
+            user_db.known_words.lang_rowid = ?1
+        WHERE target_corpus_db.sentences.lang_rowid = ?1
+        GROUP BY target_corpus_db.sentences.sentences_rowid
+    )
+    GROUP BY bucket
+";
@@ -0,0 +1,80 @@
+use crate::ConcordanceMember;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WordKnowledge {
+    Known,
+    Unknown,
+    /// The word doesn't occur in the corpus, so it can't be known or learned via the corpus.
+    OutOfCorpus,
+}
+
+/// A distinct word of an analyzed text.
+#[derive(Debug)]
+pub struct AnalyzedWord {
+    /// The corpus's spelling of the word if it's in the corpus, otherwise as it occurs in the text.
+    pub text: String,
+    pub word_rowid_o: Option<i32>,
+    /// Corpus frequency; 0 for out-of-corpus words.
+    pub word_freq: i32,
+    pub knowledge: WordKnowledge,
+    /// Number of occurrences in the analyzed text.
+    pub occurrence_count: usize,
+}
+
+#[derive(Debug)]
+pub struct AnalyzedSentence {
+    pub text: String,
+    pub token_count: usize,
+    /// Number of distinct unknown (in-corpus) words.
+    pub unknown_word_count: usize,
+    /// Number of distinct out-of-corpus words.
+    pub out_of_corpus_word_count: usize,
+}
+
+#[derive(Debug)]
+pub struct GlossaryEntry {
+    pub word_rowid: i32,
+    pub word_text: String,
+    pub word_freq: i32,
+    /// The translated corpus sentence containing the word with the fewest unknown words, with
+    /// its translations, or if none is translated, the untranslated one with the fewest.  None
+    /// if the corpus has no sentence containing the word.
+    pub example_sentence_o: Option<ConcordanceMember>,
+}
+
+/// Result of DbHub::analyze_text.
+#[derive(Debug)]
+pub struct TextAnalysis {
+    pub token_count: usize,
+    pub known_token_count: usize,
+    pub unknown_token_count: usize,
+    pub out_of_corpus_token_count: usize,
+    /// Fraction of the tokens that are known words.
+    pub coverage: f64,
+    /// Distinct words, in order of first occurrence.
+    pub word_v: Vec<AnalyzedWord>,
+    pub sentence_v: Vec<AnalyzedSentence>,
+    /// The unknown (in-corpus) words, in order of first occurrence.
+    pub glossary_v: Vec<GlossaryEntry>,
+}
+
+/// Spellings of word_str to try, in order, when looking it up in the corpus's words table.
+/// Besides the exact spelling, this tries the word with its first letter's case swapped, which
+/// handles capitalization at the start of sentences.
+pub(crate) fn lookup_candidate_v(word_str: &str) -> Vec<String> {
+    let mut candidate_v = vec![word_str.to_string()];
+    let mut char_i = word_str.chars();
+    if let Some(first_c) = char_i.next() {
+        let rest_str = char_i.as_str();
+        let swapped_first_str: String = if first_c.is_uppercase() {
+            first_c.to_lowercase().collect()
+        } else {
+            first_c.to_uppercase().collect()
+        };
+        let swapped = format!("{}{}", swapped_first_str, rest_str);
+        if swapped != word_str {
+            candidate_v.push(swapped);
+        }
+    }
+    candidate_v
+}
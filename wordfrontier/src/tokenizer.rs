@@ -0,0 +1,68 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Punctuation that is cleaned off of the ends of each whitespace-separated token.
+const TRIM_PATTERN: &[char] = &['.', ',', '!', '¡', '?', '¿', '"', '\''];
+
+/// Splits sentence text into words the same way the corpus import does, so that words
+/// produced here can be looked up in the corpus's words table.  Tokens consisting entirely
+/// of trimmed punctuation are skipped.
+pub fn tokenize(text: &str) -> impl Iterator<Item = &str> {
+    tokenize_with_byte_offsets(text).map(|(_, word_str)| word_str)
+}
+
+/// Same as tokenize, but also produces the byte offset of each word within text.
+pub fn tokenize_with_byte_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(|word_str| word_str.trim_start_matches(TRIM_PATTERN).trim_end_matches(TRIM_PATTERN))
+        .filter(|word_str| !word_str.is_empty())
+        // word_str is always a subslice of text, so this is its offset.
+        .map(move |word_str| (word_str.as_ptr() as usize - text.as_ptr() as usize, word_str))
+}
+
+/// Characters that end a sentence when followed by whitespace.
+const SENTENCE_TERMINATORS: &[char] = &['.', '!', '?', '…'];
+/// Characters that may trail a sentence terminator and still belong to that sentence.
+const SENTENCE_CLOSERS: &[char] = &['.', '!', '?', '…', '"', '\'', '»', '“', '”', ')'];
+
+/// Splits running text (e.g. an article) into sentences, at sentence terminators followed by
+/// whitespace and at line breaks.  This is a simple heuristic, so e.g. abbreviations like
+/// "z. B." will split sentences.  The sentences are trimmed, and empty ones are skipped.
+pub fn split_sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut sentence_v = Vec::new();
+    let mut start = 0;
+    let mut char_i = text.char_indices().peekable();
+    while let Some((index, c)) = char_i.next() {
+        if c == '\n' {
+            sentence_v.push(&text[start..index]);
+            start = index + c.len_utf8();
+        } else if SENTENCE_TERMINATORS.contains(&c) {
+            let mut end = index + c.len_utf8();
+            while let Some(&(next_index, next_c)) = char_i.peek() {
+                if !SENTENCE_CLOSERS.contains(&next_c) {
+                    break;
+                }
+                end = next_index + next_c.len_utf8();
+                char_i.next();
+            }
+            if char_i.peek().map(|&(_, next_c)| next_c.is_whitespace()).unwrap_or(true) {
+                sentence_v.push(&text[start..end]);
+                start = end;
+            }
+        }
+    }
+    sentence_v.push(&text[start..]);
+    sentence_v.into_iter().map(str::trim).filter(|sentence_str| !sentence_str.is_empty())
+}
+
+/// Normalizes a word for matching words from external sources (dictionaries, word lists)
+/// against the corpus's words: Unicode NFC and lowercase.  Unlike fold_text, this keeps
+/// diacritics, since e.g. "schon" and "schön" are different words.
+pub fn normalize_word(word_str: &str) -> String {
+    word_str.trim().nfc().collect::<String>().to_lowercase()
+}
+
+/// Folds text for loose matching: lowercases it and strips diacritics, e.g. "Müde Straße"
+/// becomes "mude straße".
+pub fn fold_text(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect::<String>().to_lowercase()
+}
@@ -19,6 +19,20 @@ impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for TranslationRow {
 }
 
 
+/// Statistically induced word glosses (see DbHub::induce_glosses) are stored alongside the
+/// translations they're derived from.  The schema name is substituted for {}.
+pub(crate) const CREATE_GLOSSES_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.glosses (
+        glosses_rowid INTEGER PRIMARY KEY,
+        target_word_rowid INTEGER NOT NULL,
+        reference_word_rowid INTEGER NOT NULL,
+        score REAL NOT NULL,
+        cooccurrence_count INTEGER NOT NULL,
+        rank INTEGER NOT NULL,
+        UNIQUE(target_word_rowid, reference_word_rowid)
+    )
+";
+
 pub struct TranslationsDb {
     target_lang_short: String,
     reference_lang_short: String,
@@ -61,8 +75,6 @@ impl TranslationsDb {
         &mut self,
         override_base_url_o: Option<&str>,
     ) -> Result<()> {
-        let tx = self.conn.transaction()?;
-
         let url = {
             let default_base_url = "https://downloads.tatoeba.org/exports/per_language";
             let base_url = override_base_url_o.unwrap_or(default_base_url);
@@ -77,6 +89,28 @@ impl TranslationsDb {
 
         log::info!("TranslationsDb; populating from {:#?}", url);
 
+        // TODO: Figure out how to do this in a streaming way
+        let compressed_bytes =
+            reqwest::get(url)
+            .await?
+            // TODO: Streaming into bzip2 decompression
+            .bytes()
+            .await?;
+        let mut bz2_decoder = bzip2::bufread::BzDecoder::new(compressed_bytes.as_ref());
+        // TODO: Try to pre-allocate capacity
+        let mut translations_tsv_string = String::new();
+        use std::io::Read;
+        bz2_decoder.read_to_string(&mut translations_tsv_string)?;
+
+//         log::debug!("translations_tsv_string:\n{}", translations_tsv_string);
+
+        self.populate_from_links_tsv(&translations_tsv_string)
+    }
+    /// Ingests translations in the tatoeba.org links TSV format (target sentence id, reference
+    /// sentence id), which is also what `populate` downloads.
+    pub fn populate_from_links_tsv(&mut self, translations_tsv_string: &str) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
         // Create the table(s).
         tx.execute(
             "CREATE TABLE IF NOT EXISTS translations (
@@ -87,31 +121,14 @@ impl TranslationsDb {
             )",
             [],
         )?;
+        tx.execute(&CREATE_GLOSSES_TABLE_SQL.replace("{}", "main"), [])?;
 
-        // Download and ingest the content
+        // Ingest the content
         {
             let mut insert_translation = tx.prepare(
                 &format!("INSERT OR {} INTO translations (target_lang_sentence_rowid, reference_lang_sentence_rowid) VALUES (?1, ?2)", OnConflict::Ignore)
             )?;
 
-            let default_base_url = "https://downloads.tatoeba.org/exports/per_language";
-            let base_url = override_base_url_o.unwrap_or(default_base_url);
-
-            // TODO: Figure out how to do this in a streaming way
-            let compressed_bytes =
-                reqwest::get(format!("{}/{}/{}-{}_links.tsv.bz2", base_url, self.target_lang_short, self.target_lang_short, self.reference_lang_short))
-                .await?
-                // TODO: Streaming into bzip2 decompression
-                .bytes()
-                .await?;
-            let mut bz2_decoder = bzip2::bufread::BzDecoder::new(compressed_bytes.as_ref());
-            // TODO: Try to pre-allocate capacity
-            let mut translations_tsv_string = String::new();
-            use std::io::Read;
-            bz2_decoder.read_to_string(&mut translations_tsv_string)?;
-
-//             log::debug!("translations_tsv_string:\n{}", translations_tsv_string);
-
             for (line_index, translation_tsv_line) in translations_tsv_string.split('\n').enumerate() {
                 let line_number = line_index + 1;
                 let mut tsv_split = translation_tsv_line.split('\t');
@@ -142,13 +159,9 @@ impl TranslationsDb {
                         continue;
                     },
                 };
-                match tsv_split.next() {
-                    Some(s) => {
-                        log::warn!("Unexpected third value {:#?} in translations TSV data on line {}.  Ignoring this line.", s, line_number);
-                        continue;
-                    },
-                    None => {
-                    },
+                if let Some(s) = tsv_split.next() {
+                    log::warn!("Unexpected third value {:#?} in translations TSV data on line {}.  Ignoring this line.", s, line_number);
+                    continue;
                 }
 
                 // Record the translation.
@@ -0,0 +1,214 @@
+use crate::{Error, Result, WordNote};
+use std::path::Path;
+
+/// Identifies user data bundle files.
+pub const USER_DATA_BUNDLE_FORMAT: &str = "wordfrontier-user-data";
+/// Incremented whenever the bundle format changes incompatibly.
+pub const USER_DATA_BUNDLE_VERSION: i64 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BundleKnownWordEvent {
+    pub word_text: String,
+    pub is_added: bool,
+    pub occurred_at: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BundleSeenWord {
+    pub word_text: String,
+    pub last_seen_at: i64,
+    pub seen_count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BundleWordSet {
+    pub name: String,
+    pub word_text_v: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BundleWordNote {
+    pub word_text: String,
+    pub word_note: WordNote,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A portable copy of the user's data for one target language.  Unlike user.db, which refers to
+/// words by the rowids of a particular corpus DB, this refers to words (and n-grams) by their
+/// text, so it can be imported into a different corpus version or on another machine.  Progress
+/// snapshots aren't included, since their figures are specific to the corpus they were computed
+/// from.  See DbHub::export_user_data and DbHub::import_user_data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserDataBundle {
+    /// The short name of the target language, e.g. "deu".
+    pub lang_short: String,
+    pub exported_at: i64,
+    pub known_word_text_v: Vec<String>,
+    pub known_word_event_v: Vec<BundleKnownWordEvent>,
+    pub seen_word_v: Vec<BundleSeenWord>,
+    pub word_set_v: Vec<BundleWordSet>,
+    pub word_note_v: Vec<BundleWordNote>,
+    pub name_word_text_v: Vec<String>,
+    pub known_ngram_text_v: Vec<String>,
+}
+
+/// What DbHub::import_user_data did.  The counts are of rows that were added or changed.
+#[derive(Debug, Default, PartialEq)]
+pub struct UserDataImportReport {
+    pub known_word_count: usize,
+    pub known_word_event_count: usize,
+    pub seen_word_count: usize,
+    pub word_set_member_count: usize,
+    pub word_note_count: usize,
+    pub name_count: usize,
+    pub known_ngram_count: usize,
+    /// Distinct word texts in the bundle that don't match any word of the current corpus.
+    pub unmatched_word_text_v: Vec<String>,
+    /// Distinct n-gram texts in the bundle that aren't n-grams of the current corpus.
+    pub unmatched_ngram_text_v: Vec<String>,
+}
+
+/// What DbHub::merge_user_data did, or would do in a dry run.
+#[derive(Debug, Default, PartialEq)]
+pub struct UserDataMergeReport {
+    pub is_dry_run: bool,
+    /// Words that became known, sorted.
+    pub added_known_word_text_v: Vec<String>,
+    /// Words that became unknown because the bundle removed them more recently, sorted.
+    pub removed_known_word_text_v: Vec<String>,
+    /// The changes to the rest of the user data; its known_word_count is the number of words
+    /// that became known.
+    pub import_report: UserDataImportReport,
+}
+
+fn malformed(message: String) -> Error {
+    Error::MalformedInputError(format!("user data bundle: {}", message))
+}
+
+fn field<'a>(value: &'a serde_json::Value, key: &str) -> Result<&'a serde_json::Value> {
+    value.get(key).ok_or_else(|| malformed(format!("missing field {:?}", key)))
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> Result<String> {
+    Ok(field(value, key)?.as_str().ok_or_else(|| malformed(format!("field {:?} is not a string", key)))?.to_string())
+}
+
+fn i64_field(value: &serde_json::Value, key: &str) -> Result<i64> {
+    field(value, key)?.as_i64().ok_or_else(|| malformed(format!("field {:?} is not an integer", key)))
+}
+
+fn bool_field(value: &serde_json::Value, key: &str) -> Result<bool> {
+    field(value, key)?.as_bool().ok_or_else(|| malformed(format!("field {:?} is not a boolean", key)))
+}
+
+fn array_field<'a>(value: &'a serde_json::Value, key: &str) -> Result<&'a Vec<serde_json::Value>> {
+    field(value, key)?.as_array().ok_or_else(|| malformed(format!("field {:?} is not an array", key)))
+}
+
+fn str_v(value_v: &[serde_json::Value], key: &str) -> Result<Vec<String>> {
+    value_v
+        .iter()
+        .map(|value| Ok(value.as_str().ok_or_else(|| malformed(format!("element of {:?} is not a string", key)))?.to_string()))
+        .collect()
+}
+
+impl UserDataBundle {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "format": USER_DATA_BUNDLE_FORMAT,
+            "version": USER_DATA_BUNDLE_VERSION,
+            "lang": self.lang_short,
+            "exported_at": self.exported_at,
+            "known_words": self.known_word_text_v,
+            "known_word_events": self.known_word_event_v.iter().map(|known_word_event| serde_json::json!({
+                "word": known_word_event.word_text,
+                "is_added": known_word_event.is_added,
+                "occurred_at": known_word_event.occurred_at,
+            })).collect::<Vec<_>>(),
+            "seen_words": self.seen_word_v.iter().map(|seen_word| serde_json::json!({
+                "word": seen_word.word_text,
+                "last_seen_at": seen_word.last_seen_at,
+                "seen_count": seen_word.seen_count,
+            })).collect::<Vec<_>>(),
+            "word_sets": self.word_set_v.iter().map(|word_set| serde_json::json!({
+                "name": word_set.name,
+                "words": word_set.word_text_v,
+            })).collect::<Vec<_>>(),
+            "word_notes": self.word_note_v.iter().map(|word_note| serde_json::json!({
+                "word": word_note.word_text,
+                "note": word_note.word_note.note,
+                "user_gloss": word_note.word_note.user_gloss,
+                "tags": word_note.word_note.tag_v,
+                "created_at": word_note.created_at,
+                "updated_at": word_note.updated_at,
+            })).collect::<Vec<_>>(),
+            "names": self.name_word_text_v,
+            "known_ngrams": self.known_ngram_text_v,
+        })
+    }
+    pub fn from_json(value: &serde_json::Value) -> Result<Self> {
+        if value.get("format").and_then(|format| format.as_str()) != Some(USER_DATA_BUNDLE_FORMAT) {
+            return Err(malformed(format!("not a {} file", USER_DATA_BUNDLE_FORMAT)));
+        }
+        let version = i64_field(value, "version")?;
+        if version > USER_DATA_BUNDLE_VERSION {
+            return Err(malformed(format!("version {} is newer than the supported version {}", version, USER_DATA_BUNDLE_VERSION)));
+        }
+        Ok(UserDataBundle {
+            lang_short: str_field(value, "lang")?,
+            exported_at: i64_field(value, "exported_at")?,
+            known_word_text_v: str_v(array_field(value, "known_words")?, "known_words")?,
+            known_word_event_v: array_field(value, "known_word_events")?
+                .iter()
+                .map(|known_word_event| Ok(BundleKnownWordEvent {
+                    word_text: str_field(known_word_event, "word")?,
+                    is_added: bool_field(known_word_event, "is_added")?,
+                    occurred_at: i64_field(known_word_event, "occurred_at")?,
+                }))
+                .collect::<Result<_>>()?,
+            seen_word_v: array_field(value, "seen_words")?
+                .iter()
+                .map(|seen_word| Ok(BundleSeenWord {
+                    word_text: str_field(seen_word, "word")?,
+                    last_seen_at: i64_field(seen_word, "last_seen_at")?,
+                    seen_count: i64_field(seen_word, "seen_count")?,
+                }))
+                .collect::<Result<_>>()?,
+            word_set_v: array_field(value, "word_sets")?
+                .iter()
+                .map(|word_set| Ok(BundleWordSet {
+                    name: str_field(word_set, "name")?,
+                    word_text_v: str_v(array_field(word_set, "words")?, "words")?,
+                }))
+                .collect::<Result<_>>()?,
+            word_note_v: array_field(value, "word_notes")?
+                .iter()
+                .map(|word_note| Ok(BundleWordNote {
+                    word_text: str_field(word_note, "word")?,
+                    word_note: WordNote {
+                        note: str_field(word_note, "note")?,
+                        user_gloss: str_field(word_note, "user_gloss")?,
+                        tag_v: str_v(array_field(word_note, "tags")?, "tags")?,
+                    },
+                    created_at: i64_field(word_note, "created_at")?,
+                    updated_at: i64_field(word_note, "updated_at")?,
+                }))
+                .collect::<Result<_>>()?,
+            name_word_text_v: str_v(array_field(value, "names")?, "names")?,
+            known_ngram_text_v: str_v(array_field(value, "known_ngrams")?, "known_ngrams")?,
+        })
+    }
+    pub fn write_to_file(&self, p: &Path) -> Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(p)?);
+        serde_json::to_writer_pretty(&mut writer, &self.to_json())?;
+        // Dropping the BufWriter would flush it too, but would ignore any error.
+        std::io::Write::flush(&mut writer)?;
+        Ok(())
+    }
+    pub fn read_from_file(p: &Path) -> Result<Self> {
+        let file = std::fs::File::open(p)?;
+        let value: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(file))?;
+        Self::from_json(&value)
+    }
+}
@@ -1,5 +1,5 @@
 use crate::Result;
-use std::{convert::TryFrom, path::Path};
+use std::convert::TryFrom;
 
 pub struct KnownWordRow {
     pub known_words_rowid: i32,
@@ -16,15 +16,33 @@ impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for KnownWordRow {
     }
 }
 
+#[derive(Debug)]
+pub struct WordSetRow {
+    pub word_sets_rowid: i32,
+    pub lang_rowid: i32,
+    pub name: String,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordSetRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(WordSetRow {
+            word_sets_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            name: row.get(2)?,
+        })
+    }
+}
+
 pub struct UserDb {
     conn: rusqlite::Connection,
 }
 
 impl UserDb {
     pub fn create_and_populate_if_missing() -> Result<()> {
-        if !Path::new(Self::db_path()).exists() {
-            Self::open()?.populate()?
-        }
+        // populate only creates missing tables, so it's also run on existing DBs, which
+        // brings user DBs created by older versions up to date.
+        Self::open()?.populate()?;
         Ok(())
     }
     pub fn attach(conn: &rusqlite::Connection) -> Result<()> {
@@ -49,6 +67,152 @@ impl UserDb {
             )",
             [],
         )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS seen_words (
+                seen_words_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                last_seen_at INTEGER NOT NULL,
+                seen_count INTEGER NOT NULL DEFAULT 1,
+                UNIQUE(lang_rowid, word_rowid)
+            )",
+            [],
+        )?;
+        // History of known_words changes; is_added is 1 for additions and 0 for removals.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS known_word_events (
+                known_word_events_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                is_added INTEGER NOT NULL,
+                occurred_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        // The undo/redo journal: each entry is one user operation, consisting of the changes
+        // to known_words that it made.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS journal_entries (
+                journal_entries_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                description TEXT NOT NULL,
+                occurred_at INTEGER NOT NULL,
+                is_undone INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS journal_changes (
+                journal_changes_rowid INTEGER PRIMARY KEY,
+                journal_entry_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                is_added INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS journal_changes_journal_entry_rowid ON journal_changes (journal_entry_rowid)",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS progress_snapshots (
+                progress_snapshots_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                day TEXT NOT NULL,
+                known_word_count INTEGER NOT NULL,
+                type_coverage REAL NOT NULL,
+                token_coverage REAL NOT NULL,
+                frontier_sentence_count INTEGER NOT NULL,
+                recorded_at INTEGER NOT NULL,
+                UNIQUE(lang_rowid, day)
+            )",
+            [],
+        )?;
+        // Named lists of words, e.g. a study list of the next words to learn.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS word_sets (
+                word_sets_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                UNIQUE(lang_rowid, name)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS word_set_members (
+                word_set_members_rowid INTEGER PRIMARY KEY,
+                word_set_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                UNIQUE(word_set_rowid, word_rowid)
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS known_ngrams (
+                known_ngrams_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                ngram_rowid INTEGER NOT NULL,
+                UNIQUE(lang_rowid, ngram_rowid)
+            )",
+            [],
+        )?;
+        // The user's own list of names, which the word frontier can treat as known (see
+        // WordClass::Name).
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS names (
+                names_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                UNIQUE(lang_rowid, word_rowid)
+            )",
+            [],
+        )?;
+        // At most one note per word; tags are separated by spaces.
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS word_notes (
+                word_notes_rowid INTEGER PRIMARY KEY,
+                lang_rowid INTEGER NOT NULL,
+                word_rowid INTEGER NOT NULL,
+                note TEXT NOT NULL,
+                user_gloss TEXT NOT NULL,
+                tags TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                UNIQUE(lang_rowid, word_rowid)
+            )",
+            [],
+        )?;
+        // Full-text index of word_notes, kept up to date by the triggers below.  See
+        // https://www.sqlite.org/fts5.html#external_content_tables
+        self.conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS word_notes_fts USING fts5(
+                note,
+                user_gloss,
+                tags,
+                content='word_notes',
+                content_rowid='word_notes_rowid',
+                tokenize='unicode61 remove_diacritics 2'
+            )",
+            [],
+        )?;
+        self.conn.execute_batch(
+            "
+            CREATE TRIGGER IF NOT EXISTS word_notes_fts_after_insert AFTER INSERT ON word_notes BEGIN
+                INSERT INTO word_notes_fts (rowid, note, user_gloss, tags)
+                VALUES (new.word_notes_rowid, new.note, new.user_gloss, new.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS word_notes_fts_after_delete AFTER DELETE ON word_notes BEGIN
+                INSERT INTO word_notes_fts (word_notes_fts, rowid, note, user_gloss, tags)
+                VALUES ('delete', old.word_notes_rowid, old.note, old.user_gloss, old.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS word_notes_fts_after_update AFTER UPDATE ON word_notes BEGIN
+                INSERT INTO word_notes_fts (word_notes_fts, rowid, note, user_gloss, tags)
+                VALUES ('delete', old.word_notes_rowid, old.note, old.user_gloss, old.tags);
+                INSERT INTO word_notes_fts (rowid, note, user_gloss, tags)
+                VALUES (new.word_notes_rowid, new.note, new.user_gloss, new.tags);
+            END;
+            ",
+        )?;
         Ok(())
     }
 }
@@ -0,0 +1,252 @@
+use crate::{computed_tables, tokenize, Error, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Classes of words that learners generally don't need to learn, which the word frontier can
+/// treat as known (see FrontierQuery::auto_known_word_class).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WordClass {
+    /// Contains a digit and otherwise only number punctuation, e.g. "1984", "3.5" or "10:30".
+    Number,
+    /// Contains no letters or digits, e.g. "—" or "&".
+    Symbol,
+    /// Mostly capitalized even in the middle of sentences, e.g. "Tom" or "Boston".  This is a
+    /// heuristic based on capitalization statistics of the corpus.  For languages that
+    /// capitalize all nouns, it's instead based on the word appearing unchanged in the
+    /// sentences' translations, which requires DbHub::compute_word_classes.
+    LikelyProperNoun,
+    /// In the user's own list of names (see DbHub::add_name).
+    Name,
+}
+
+impl WordClass {
+    /// How the class is stored in the word_classes table.
+    pub(crate) fn as_sql_str(self) -> &'static str {
+        match self {
+            WordClass::Number => "number",
+            WordClass::Symbol => "symbol",
+            WordClass::LikelyProperNoun => "likely_proper_noun",
+            WordClass::Name => "name",
+        }
+    }
+}
+
+impl std::fmt::Display for WordClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            WordClass::Number => write!(f, "Number"),
+            WordClass::Symbol => write!(f, "Symbol"),
+            WordClass::LikelyProperNoun => write!(f, "Likely Proper Noun"),
+            WordClass::Name => write!(f, "Name"),
+        }
+    }
+}
+
+impl std::str::FromStr for WordClass {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "number" => Ok(WordClass::Number),
+            "symbol" => Ok(WordClass::Symbol),
+            "likely_proper_noun" => Ok(WordClass::LikelyProperNoun),
+            "name" => Ok(WordClass::Name),
+            _ => Err(anyhow::anyhow!("unknown word class {:#?}", s))?,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for WordClass {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|e: Error| rusqlite::types::FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// The schema name is substituted for {}.  Corpus DBs created by older versions don't have
+/// this table yet.  Names aren't stored here, but in user_db.names.
+pub(crate) const CREATE_WORD_CLASSES_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS {}.word_classes (
+        word_classes_rowid INTEGER PRIMARY KEY,
+        word_rowid INTEGER NOT NULL,
+        class TEXT NOT NULL,
+        UNIQUE(word_rowid)
+    )
+";
+
+/// Languages in which all nouns are capitalized, so that capitalization says nothing about
+/// proper nouns.
+const NOUN_CAPITALIZING_LANG_SHORT_V: &[&str] = &["deu", "gsw", "ltz", "nds"];
+/// A likely proper noun must be capitalized in the middle of a sentence at least this many times.
+const MIN_PROPER_NOUN_NON_INITIAL_COUNT: i32 = 2;
+/// In noun-capitalizing languages, a likely proper noun must appear unchanged in the
+/// translations of at least this fraction of the translated sentences containing it.
+const MIN_PROPER_NOUN_UNCHANGED_TRANSLATION_RATIO: f64 = 0.5;
+
+/// Punctuation that may occur within numbers.
+const NUMBER_PUNCTUATION: &[char] = &['.', ',', ':', '/', '-', '%', '+'];
+
+fn classify_by_text(word_str: &str) -> Option<WordClass> {
+    if word_str.chars().any(|c| c.is_ascii_digit())
+        && word_str.chars().all(|c| c.is_numeric() || NUMBER_PUNCTUATION.contains(&c))
+    {
+        Some(WordClass::Number)
+    } else if !word_str.chars().any(char::is_alphanumeric) {
+        Some(WordClass::Symbol)
+    } else {
+        None
+    }
+}
+
+// Single letters (e.g. "I") and words with non-letters (e.g. "I'm") are never proper nouns.
+fn could_be_proper_noun(word_str: &str) -> bool {
+    word_str.chars().count() >= 2 && word_str.chars().all(char::is_alphabetic)
+}
+
+// Returns word_str with its first letter lowercased, if that changes it.
+fn decapitalize(word_str: &str) -> Option<String> {
+    let mut char_i = word_str.chars();
+    let first_char = char_i.next()?;
+    if !first_char.is_uppercase() {
+        return None;
+    }
+    Some(first_char.to_lowercase().chain(char_i).collect())
+}
+
+/// Classifies the given words, where sentence_word_v is the tokenized sentences of the corpus.
+/// A capitalized word is a likely proper noun if it's capitalized in the middle of sentences
+/// more often than its lowercase form occurs at all.  Single letters (e.g. "I") and words with
+/// non-letters (e.g. "I'm") are never considered proper nouns.
+pub(crate) fn classify_word_v<'a>(
+    word_freq_m: &HashMap<&'a str, i32>,
+    sentence_word_vv: &[Vec<&'a str>],
+    detect_proper_nouns: bool,
+) -> HashMap<&'a str, WordClass> {
+    let mut word_class_m: HashMap<&str, WordClass> = word_freq_m
+        .keys()
+        .filter_map(|word_str| classify_by_text(word_str).map(|word_class| (*word_str, word_class)))
+        .collect();
+    if detect_proper_nouns {
+        let mut non_initial_count_m: HashMap<&str, i32> = HashMap::new();
+        for sentence_word_v in sentence_word_vv.iter() {
+            for word_str in sentence_word_v.iter().skip(1) {
+                *non_initial_count_m.entry(word_str).or_insert(0) += 1;
+            }
+        }
+        for (word_str, non_initial_count) in non_initial_count_m.into_iter() {
+            if non_initial_count < MIN_PROPER_NOUN_NON_INITIAL_COUNT || !could_be_proper_noun(word_str) {
+                continue;
+            }
+            if let Some(decapitalized) = decapitalize(word_str) {
+                let decapitalized_freq = word_freq_m.get(decapitalized.as_str()).copied().unwrap_or(0);
+                if decapitalized_freq < non_initial_count {
+                    word_class_m.insert(word_str, WordClass::LikelyProperNoun);
+                }
+            }
+        }
+    }
+    word_class_m
+}
+
+/// Returns the likely proper nouns of a language that capitalizes all nouns, where
+/// sentence_word_vv is the tokenized sentences of the corpus, sentence_rowid_v their rowids,
+/// and translation_text_vm has the translations of each translated sentence.  Names are rarely
+/// translated, unlike other nouns, so a capitalized word is a likely proper noun if it appears
+/// unchanged in the translations of most translated sentences containing it, e.g. "Tom" in
+/// "Tom ist hier." / "Tom is here.".  Comparing case-sensitively keeps out most nouns that are
+/// spelled the same in both languages, which the reference language writes in lowercase.
+pub(crate) fn find_translated_proper_noun_v<'a>(
+    sentence_rowid_v: &[i32],
+    sentence_word_vv: &[Vec<&'a str>],
+    translation_text_vm: &HashMap<i32, Vec<String>>,
+) -> Vec<&'a str> {
+    let mut count_m: HashMap<&str, (i32, i32)> = HashMap::new();
+    for (sentence_rowid, sentence_word_v) in sentence_rowid_v.iter().zip(sentence_word_vv.iter()) {
+        let translation_text_v = match translation_text_vm.get(sentence_rowid) {
+            Some(translation_text_v) => translation_text_v,
+            None => continue,
+        };
+        let translation_word_s: HashSet<&str> = translation_text_v.iter().flat_map(|text| tokenize(text)).collect();
+        let sentence_word_s: HashSet<&str> = sentence_word_v.iter().copied().collect();
+        for word_str in sentence_word_s.into_iter() {
+            if decapitalize(word_str).is_none() || !could_be_proper_noun(word_str) {
+                continue;
+            }
+            let (translated_count, unchanged_count) = count_m.entry(word_str).or_insert((0, 0));
+            *translated_count += 1;
+            if translation_word_s.contains(word_str) {
+                *unchanged_count += 1;
+            }
+        }
+    }
+    let mut proper_noun_v: Vec<&str> = count_m
+        .into_iter()
+        .filter(|(_, (translated_count, unchanged_count))| {
+            *unchanged_count > 0 && *unchanged_count as f64 >= *translated_count as f64 * MIN_PROPER_NOUN_UNCHANGED_TRANSLATION_RATIO
+        })
+        .map(|(word_str, _)| word_str)
+        .collect();
+    proper_noun_v.sort_unstable();
+    proper_noun_v
+}
+
+/// (Re)computes the word_classes table of the corpus in the given schema from its sentences
+/// and words tables.  Proper nouns of languages that capitalize all nouns are only found if
+/// translation_text_vm_o has the translations of the sentences (see
+/// find_translated_proper_noun_v); otherwise the table isn't recorded as computed, so that
+/// DbHub::compute_word_classes can finish the job.  This should be run in a transaction.
+pub(crate) fn populate_word_classes(
+    conn: &rusqlite::Connection,
+    schema: &str,
+    lang_rowid: i32,
+    lang_short: &str,
+    translation_text_vm_o: Option<&HashMap<i32, Vec<String>>>,
+) -> Result<()> {
+    conn.execute(&CREATE_WORD_CLASSES_TABLE_SQL.replace("{}", schema), [])?;
+    conn.execute(
+        &format!(
+            "
+            DELETE FROM {schema}.word_classes
+            WHERE word_rowid IN (SELECT words_rowid FROM {schema}.words WHERE lang_rowid = ?1)
+            ",
+            schema = schema,
+        ),
+        [lang_rowid],
+    )?;
+
+    let word_row_v: Vec<(String, i32, i32)> = conn
+        .prepare(&format!("SELECT text, words_rowid, freq FROM {}.words WHERE lang_rowid = ?1", schema))?
+        .query_map([lang_rowid], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let sentence_text_v: Vec<(i32, String)> = conn
+        .prepare(&format!("SELECT sentences_rowid, text FROM {}.sentences WHERE lang_rowid = ?1", schema))?
+        .query_map([lang_rowid], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let word_freq_m: HashMap<&str, i32> = word_row_v.iter().map(|(text, _, freq)| (text.as_str(), *freq)).collect();
+    let sentence_rowid_v: Vec<i32> = sentence_text_v.iter().map(|(sentences_rowid, _)| *sentences_rowid).collect();
+    let sentence_word_vv: Vec<Vec<&str>> = sentence_text_v.iter().map(|(_, text)| tokenize(text).collect()).collect();
+    let is_noun_capitalizing = NOUN_CAPITALIZING_LANG_SHORT_V.contains(&lang_short);
+    let mut word_class_m = classify_word_v(&word_freq_m, &sentence_word_vv, !is_noun_capitalizing);
+    if is_noun_capitalizing {
+        if let Some(translation_text_vm) = translation_text_vm_o {
+            for word_str in find_translated_proper_noun_v(&sentence_rowid_v, &sentence_word_vv, translation_text_vm).into_iter() {
+                word_class_m.entry(word_str).or_insert(WordClass::LikelyProperNoun);
+            }
+        }
+    }
+
+    let mut insert_word_class = conn.prepare(&format!(
+        "INSERT INTO {}.word_classes (word_rowid, class) VALUES (?1, ?2)",
+        schema,
+    ))?;
+    for (text, words_rowid, _) in word_row_v.iter() {
+        if let Some(word_class) = word_class_m.get(text.as_str()) {
+            insert_word_class.execute(rusqlite::params![words_rowid, word_class.as_sql_str()])?;
+        }
+    }
+    log::debug!("classified {} words", word_class_m.len());
+    if !is_noun_capitalizing || translation_text_vm_o.is_some() {
+        computed_tables::mark_table_computed(conn, schema, "word_classes")?;
+    }
+    Ok(())
+}
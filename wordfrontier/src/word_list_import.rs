@@ -0,0 +1,273 @@
+use crate::{Error, Result};
+use std::{collections::HashMap, io::Read, path::Path};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WordListFormat {
+    /// An Anki deck or collection package (.apkg or .colpkg), which is a zip archive containing
+    /// the SQLite collection, in either the current (zstd-compressed) or the legacy format.
+    Anki,
+    /// Comma-separated values, e.g. from a spreadsheet.  Rows starting with '#' are ignored.
+    Csv,
+    /// Tab-separated values, e.g. Anki's "Notes in Plain Text" export.  Rows starting with '#'
+    /// are ignored.
+    Tsv,
+}
+
+impl std::fmt::Display for WordListFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            WordListFormat::Anki => write!(f, "anki"),
+            WordListFormat::Csv => write!(f, "csv"),
+            WordListFormat::Tsv => write!(f, "tsv"),
+        }
+    }
+}
+
+impl std::str::FromStr for WordListFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "anki" => Ok(WordListFormat::Anki),
+            "csv" => Ok(WordListFormat::Csv),
+            "tsv" => Ok(WordListFormat::Tsv),
+            _ => Err(anyhow::anyhow!("unknown word list format {:#?}", s))?,
+        }
+    }
+}
+
+/// The field of each Anki note, or column of each CSV/TSV row, that contains the target
+/// language word.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WordListField {
+    /// 0-based index of the field or column.
+    Index(usize),
+    /// Name of the field in the note type, or of the column in the header row.
+    Name(String),
+}
+
+impl std::str::FromStr for WordListField {
+    type Err = Error;
+    /// Numbers are taken as indexes, anything else as names.
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.parse::<usize>() {
+            Ok(index) => WordListField::Index(index),
+            Err(_) => WordListField::Name(s.to_string()),
+        })
+    }
+}
+
+/// Where DbHub::import_word_list puts the matched words.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WordListTarget {
+    KnownWords,
+    /// The word set with this name, which is created if it doesn't exist.
+    WordSet(String),
+}
+
+/// How to import a word list (see DbHub::import_word_list).
+#[derive(Clone, Debug)]
+pub struct WordListImport {
+    pub format: WordListFormat,
+    pub field: WordListField,
+    /// Whether the first (non-comment) row of a CSV/TSV file is a header row.  Ignored for Anki.
+    pub has_header_row: bool,
+    /// Whether an entry of several words (e.g. "das Haus") that doesn't match as a whole matches
+    /// each of its words instead.
+    pub match_each_word: bool,
+    pub target: WordListTarget,
+}
+
+impl WordListImport {
+    pub fn new(format: WordListFormat, field: WordListField) -> Self {
+        Self {
+            format,
+            field,
+            has_header_row: false,
+            match_each_word: false,
+            target: WordListTarget::KnownWords,
+        }
+    }
+    pub fn header_row(mut self, has_header_row: bool) -> Self {
+        self.has_header_row = has_header_row;
+        self
+    }
+    pub fn match_each_word(mut self, match_each_word: bool) -> Self {
+        self.match_each_word = match_each_word;
+        self
+    }
+    pub fn word_set(mut self, name: &str) -> Self {
+        self.target = WordListTarget::WordSet(name.to_string());
+        self
+    }
+}
+
+/// What DbHub::import_word_list did.
+#[derive(Debug, Default, PartialEq)]
+pub struct WordListImportReport {
+    pub entry_count: usize,
+    pub matched_entry_count: usize,
+    /// Number of words that weren't already known (or in the word set).
+    pub added_word_count: usize,
+    /// The entries that didn't match any corpus word, without duplicates, in order.
+    pub unmatched_entry_v: Vec<String>,
+}
+
+lazy_static::lazy_static! {
+    // HTML tags, and Anki's sound and image references.
+    static ref MARKUP_RE: regex::Regex = regex::Regex::new(r"<[^>]*>|\[sound:[^\]]*\]").unwrap();
+}
+
+/// Reduces a field (which may be HTML, as in Anki) to its plain text, with whitespace collapsed.
+pub(crate) fn clean_field_text(text: &str) -> String {
+    let text = MARKUP_RE.replace_all(text, " ");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads the (cleaned up, non-empty) entries of the word list at word_list_p.
+pub fn read_word_list_entry_v(word_list_p: &Path, word_list_import: &WordListImport) -> Result<Vec<String>> {
+    match word_list_import.format {
+        WordListFormat::Anki => read_anki_entry_v(word_list_p, &word_list_import.field),
+        WordListFormat::Csv => read_delimited_entry_v(word_list_p, b',', word_list_import),
+        WordListFormat::Tsv => read_delimited_entry_v(word_list_p, b'\t', word_list_import),
+    }
+}
+
+fn read_delimited_entry_v(word_list_p: &Path, delimiter: u8, word_list_import: &WordListImport) -> Result<Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(word_list_import.has_header_row)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .from_path(word_list_p)?;
+    let field_index = match &word_list_import.field {
+        WordListField::Index(index) => *index,
+        WordListField::Name(name) => {
+            if !word_list_import.has_header_row {
+                Err(anyhow::anyhow!("the field can only be given by name if there's a header row"))?;
+            }
+            reader
+                .headers()?
+                .iter()
+                .position(|header| header.trim() == name)
+                .ok_or_else(|| anyhow::anyhow!("there's no column named {:?}", name))?
+        }
+    };
+    let mut entry_v = Vec::new();
+    for record_r in reader.records() {
+        if let Some(field) = record_r?.get(field_index) {
+            let entry = clean_field_text(field);
+            if !entry.is_empty() {
+                entry_v.push(entry);
+            }
+        }
+    }
+    Ok(entry_v)
+}
+
+// The collection files of an Anki package, most recent format first.  Packages in the current
+// format also contain a legacy collection, which only has a note saying to upgrade Anki.
+const ANKI_COLLECTION_NAME_V: [(&str, bool); 3] = [
+    ("collection.anki21b", true),
+    ("collection.anki21", false),
+    ("collection.anki2", false),
+];
+
+fn read_anki_entry_v(package_p: &Path, field: &WordListField) -> Result<Vec<String>> {
+    let mut zip_archive = zip::ZipArchive::new(std::fs::File::open(package_p)?)?;
+    let collection_file = tempfile::NamedTempFile::new()?;
+    let mut is_extracted = false;
+    for &(name, is_zstd_compressed) in ANKI_COLLECTION_NAME_V.iter() {
+        let mut zip_file = match zip_archive.by_name(name) {
+            Ok(zip_file) => zip_file,
+            Err(zip::result::ZipError::FileNotFound) => continue,
+            Err(e) => Err(e)?,
+        };
+        let mut writer = collection_file.reopen()?;
+        if is_zstd_compressed {
+            zstd::stream::copy_decode(&mut zip_file, &mut writer)?;
+        } else {
+            let mut buffer = Vec::new();
+            zip_file.read_to_end(&mut buffer)?;
+            std::io::Write::write_all(&mut writer, &buffer)?;
+        }
+        is_extracted = true;
+        break;
+    }
+    if !is_extracted {
+        Err(anyhow::anyhow!("{:?} doesn't contain an Anki collection", package_p))?;
+    }
+
+    let conn = rusqlite::Connection::open_with_flags(collection_file.path(), rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let field_name_vm = query_anki_field_name_vm(&conn)?;
+    let mut entry_v = Vec::new();
+    let mut has_field = false;
+    let mut stmt = conn.prepare("SELECT mid, flds FROM notes ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let note_type_id: i64 = row.get(0)?;
+        let fields: String = row.get(1)?;
+        let field_index = match field {
+            WordListField::Index(index) => *index,
+            WordListField::Name(name) => {
+                match field_name_vm.get(&note_type_id).and_then(|field_name_v| field_name_v.iter().position(|field_name| field_name == name)) {
+                    Some(field_index) => field_index,
+                    // Notes of other note types may not have this field.
+                    None => continue,
+                }
+            }
+        };
+        // Fields are separated by the unit separator character.
+        if let Some(field) = fields.split('\x1f').nth(field_index) {
+            has_field = true;
+            let entry = clean_field_text(field);
+            if !entry.is_empty() {
+                entry_v.push(entry);
+            }
+        }
+    }
+    if !has_field {
+        if let WordListField::Name(name) = field {
+            Err(anyhow::anyhow!("no note has a field named {:?}", name))?;
+        }
+    }
+    Ok(entry_v)
+}
+
+// Returns the field names of each note type, by note type id.  The current collection format
+// has a fields table, while the legacy one keeps note types as JSON in col.models.
+fn query_anki_field_name_vm(conn: &rusqlite::Connection) -> Result<HashMap<i64, Vec<String>>> {
+    let has_fields_table: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'fields')",
+        [],
+        |row| row.get(0),
+    )?;
+    let mut field_name_vm: HashMap<i64, Vec<String>> = HashMap::new();
+    if has_fields_table {
+        let mut stmt = conn.prepare("SELECT ntid, name FROM fields ORDER BY ntid, ord")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            field_name_vm.entry(row.get(0)?).or_default().push(row.get(1)?);
+        }
+    } else {
+        let models: String = conn.query_row("SELECT models FROM col", [], |row| row.get(0))?;
+        let models_value: serde_json::Value = serde_json::from_str(&models)?;
+        for (note_type_id, model) in models_value.as_object().into_iter().flatten() {
+            let mut field_v: Vec<(i64, String)> = model["flds"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|field| Some((field["ord"].as_i64()?, field["name"].as_str()?.to_string())))
+                .collect();
+            field_v.sort();
+            field_name_vm.insert(note_type_id.parse()?, field_v.into_iter().map(|(_, name)| name).collect());
+        }
+    }
+    Ok(field_name_vm)
+}
@@ -0,0 +1,90 @@
+use crate::Result;
+use std::convert::TryFrom;
+
+/// The user's own notes on a word.  Tags are single words, e.g. "verb" or "false-friend".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WordNote {
+    pub note: String,
+    pub user_gloss: String,
+    pub tag_v: Vec<String>,
+}
+
+impl WordNote {
+    pub(crate) fn tags_text(&self) -> Result<String> {
+        if let Some(tag) = self.tag_v.iter().find(|tag| tag.is_empty() || tag.contains(char::is_whitespace)) {
+            Err(anyhow::anyhow!("word note tag {:#?} must be nonempty and not contain whitespace", tag))?;
+        }
+        Ok(self.tag_v.join(" "))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordNoteRow {
+    pub word_notes_rowid: i32,
+    pub lang_rowid: i32,
+    pub word_rowid: i32,
+    pub word_note: WordNote,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordNoteRow {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        let tags: String = row.get(5)?;
+        Ok(WordNoteRow {
+            word_notes_rowid: row.get(0)?,
+            lang_rowid: row.get(1)?,
+            word_rowid: row.get(2)?,
+            word_note: WordNote {
+                note: row.get(3)?,
+                user_gloss: row.get(4)?,
+                tag_v: tags.split_whitespace().map(str::to_string).collect(),
+            },
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct WordNoteWithText {
+    pub word_note_row: WordNoteRow,
+    pub word_text: String,
+}
+
+impl<'stmt> TryFrom<&rusqlite::Row<'stmt>> for WordNoteWithText {
+    type Error = rusqlite::Error;
+    fn try_from(row: &rusqlite::Row<'stmt>) -> std::result::Result<Self, Self::Error> {
+        Ok(WordNoteWithText {
+            word_note_row: WordNoteRow::try_from(row)?,
+            word_text: row.get(8)?,
+        })
+    }
+}
+
+pub(crate) const WORD_NOTE_COLUMNS_SQL: &str = "
+    user_db.word_notes.word_notes_rowid,
+    user_db.word_notes.lang_rowid,
+    user_db.word_notes.word_rowid,
+    user_db.word_notes.note,
+    user_db.word_notes.user_gloss,
+    user_db.word_notes.tags,
+    user_db.word_notes.created_at,
+    user_db.word_notes.updated_at
+";
+
+/// Turns free text into an FTS5 query matching notes that contain all of its words, where the
+/// last word may be a prefix (so that searching as you type works).  Quoting each word keeps
+/// FTS5 syntax characters in the text from being interpreted.
+pub(crate) fn word_note_fts_query(text: &str) -> String {
+    let quoted_word_v: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if quoted_word_v.is_empty() {
+        String::new()
+    } else {
+        format!("{}*", quoted_word_v.join(" "))
+    }
+}
@@ -1,4 +1,93 @@
-use wordfrontier::{CorpusDb, DbHub, DbHubConfig, LangsDb, Order, Range, Result, TranslationsDb, UserDb};
+use wordfrontier::{
+    fold_text, plan_learning_path, split_sentences, tokenize, CompoundSplitting, ConcordanceQuery,
+    CorpusDb, DbHub, DbHubConfig, DictionaryDb, DictionaryFormat, FrontierDiversification,
+    FrontierQuery, FrontierRanking, GlossInduction, KeywordInContext, LangsDb, LearningPathStep,
+    NextWordRanking, NgramSelection, Order, PlacementTestConfig, Range, ReadingPacketFormat, Result,
+    TextFilter, TranslationsDb, UserDataBundle, UserDataImportReport, UserDataMergeReport, UserDb,
+    WordClass, WordKnowledge, WordListField, WordListFormat, WordListImport, WordNote,
+};
+
+// Small hermetic corpus used by the fixture tests (tatoeba.org TSV formats).
+const FIXTURE_DEU_SENTENCES_TSV: &str = "\
+1\tdeu\tIch bin müde.
+2\tdeu\tIch bin hier.
+3\tdeu\tDu bist müde.
+4\tdeu\tTom ist hier.
+5\tdeu\tDas Haus ist groß.
+6\tdeu\tIch habe ein Haus.
+7\tdeu\tWo ist das Krankenhaus?
+8\tdeu\tIch liebe dich.
+9\tdeu\tDas ist ein Satz ohne Übersetzung.
+";
+const FIXTURE_ENG_SENTENCES_TSV: &str = "\
+101\teng\tI am tired.
+102\teng\tI am here.
+103\teng\tYou are tired.
+104\teng\tTom is here.
+105\teng\tThe house is big.
+106\teng\tI have a house.
+107\teng\tWhere is the hospital?
+108\teng\tI love you.
+";
+const FIXTURE_DEU_ENG_LINKS_TSV: &str = "\
+1\t101
+2\t102
+3\t103
+4\t104
+5\t105
+6\t106
+7\t107
+8\t108
+";
+
+// Runs the test body inside a fresh temp dir (which is where all the DBs live), restoring the
+// previous working dir afterward.  Only use this from #[serial_test::serial] tests.
+struct Fixture {
+    db_hub: DbHub,
+    previous_dir: std::path::PathBuf,
+    _temp_dir: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let temp_dir = tempfile::tempdir()?;
+        let previous_dir = std::env::current_dir()?;
+        std::env::set_current_dir(temp_dir.path())?;
+
+        LangsDb::create_and_populate_if_missing()?;
+        UserDb::create_and_populate_if_missing()?;
+        DictionaryDb::create_and_populate_if_missing()?;
+        {
+            let langs_db = LangsDb::open()?;
+            CorpusDb::open(langs_db.query_lang_row("deu")?)?.populate_from_sentences_tsv(FIXTURE_DEU_SENTENCES_TSV)?;
+            CorpusDb::open(langs_db.query_lang_row("eng")?)?.populate_from_sentences_tsv(FIXTURE_ENG_SENTENCES_TSV)?;
+        }
+        TranslationsDb::open("deu", "eng")?.populate_from_links_tsv(FIXTURE_DEU_ENG_LINKS_TSV)?;
+
+        let db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+        Ok(Fixture { db_hub, previous_dir, _temp_dir: temp_dir })
+    }
+    fn word_rowid(&self, word_text: &str) -> Result<i32> {
+        let conn = rusqlite::Connection::open(CorpusDb::db_path_from("deu")?)?;
+        Ok(conn.query_row(
+            "SELECT words_rowid FROM words WHERE text = ?1",
+            rusqlite::params![word_text],
+            |row| row.get(0),
+        )?)
+    }
+    fn add_known_words(&self, word_text_v: &[&str]) -> Result<()> {
+        for word_text in word_text_v {
+            self.db_hub.add_known_word(self.word_rowid(word_text)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        std::env::set_current_dir(&self.previous_dir).unwrap();
+    }
+}
 
 #[tokio::test]
 #[serial_test::serial]
@@ -27,14 +116,176 @@ fn test_db_hub_query_word_frontier() -> Result<()> {
 
     let db_hub = DbHub::from_config(DbHubConfig::new(target_lang_short, reference_lang_short, None)?)?;
     let word_frontier_v = db_hub.query_word_frontier_v(
-        Range(1, 1),
-        Order::Ascending,
+        &FrontierQuery::new(Range(1, 1)).order(Order::Ascending),
     )?;
     log::trace!("word_frontier_v: {:#?}", word_frontier_v);
     log::debug!("word_frontier_v.len(): {:#?}", word_frontier_v.len());
     Ok(())
 }
 
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_word_frontier_paging_and_sampling() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin", "ist", "hier", "Das", "das"])?;
+
+    // Sentence 9 has no translation, so it's never in the frontier.
+    let all_v = fixture.db_hub.query_word_frontier_v(&FrontierQuery::new(Range(0, 10)).order(Order::Unordered))?;
+    let all_rowid_v: Vec<i32> = all_v.iter().map(|m| m.sentences_rowid).collect();
+    assert_eq!(all_rowid_v, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(all_v[1].unknown_word_count, 0);
+    assert_eq!(all_v[0].unknown_word_count, 1);
+
+    let frontier_query = FrontierQuery::new(Range(1, 2)).order(Order::Ascending);
+    assert_eq!(fixture.db_hub.query_word_frontier_count(&frontier_query)?, 5);
+    let page_0_v = fixture.db_hub.query_word_frontier_v(&frontier_query.clone().page(0, 3))?;
+    let page_1_v = fixture.db_hub.query_word_frontier_v(&frontier_query.clone().page(1, 3))?;
+    assert_eq!(page_0_v.len(), 3);
+    assert_eq!(page_1_v.len(), 2);
+    assert!(page_0_v.iter().chain(page_1_v.iter()).all(|m| (1..=2).contains(&m.unknown_word_count)));
+    assert!(page_0_v.windows(2).all(|w| w[0].unknown_word_freq <= w[1].unknown_word_freq));
+
+    // Seeded samples are reproducible, and the streaming variant agrees with the Vec one.
+    let sample_query = FrontierQuery::new(Range(0, 10)).shuffled(42).limit(5);
+    let sample_v = fixture.db_hub.query_word_frontier_v(&sample_query)?;
+    let streamed_rowid_v = fixture.db_hub.with_word_frontier_iter(&sample_query, |member_i| {
+        member_i.map(|member_r| member_r.map(|m| m.sentences_rowid)).collect::<Result<Vec<_>>>()
+    })??;
+    assert_eq!(sample_v.len(), 5);
+    assert_eq!(sample_v.iter().map(|m| m.sentences_rowid).collect::<Vec<_>>(), streamed_rowid_v);
+    let other_seed_rowid_v: Vec<i32> = fixture.db_hub
+        .query_word_frontier_v(&FrontierQuery::new(Range(0, 10)).shuffled(43))?
+        .iter()
+        .map(|m| m.sentences_rowid)
+        .collect();
+    assert_eq!(other_seed_rowid_v.len(), 8);
+    assert_ne!(other_seed_rowid_v[..5], streamed_rowid_v[..]);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_word_frontier_rankings() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin", "ist", "hier", "Das", "das"])?;
+    let ranked_rowid_v = |ranking: FrontierRanking, order: Order| -> Result<Vec<i32>> {
+        Ok(fixture.db_hub
+            .query_word_frontier_v(&FrontierQuery::new(Range(1, 3)).ranking(ranking).order(order))?
+            .iter()
+            .map(|m| m.sentences_rowid)
+            .collect())
+    };
+
+    assert_eq!(ranked_rowid_v(FrontierRanking::SumUnknownWordFreq, Order::Descending)?, vec![6, 3, 5, 1, 7, 8, 4]);
+    assert_eq!(ranked_rowid_v(FrontierRanking::MaxUnknownWordFreq, Order::Ascending)?, vec![4, 7, 8, 1, 3, 5, 6]);
+    assert_eq!(ranked_rowid_v(FrontierRanking::SentenceLength, Order::Ascending)?, vec![1, 3, 4, 8, 5, 6, 7]);
+    // The 2 most frequent words are "Ich" and "ist", both known.
+    assert_eq!(
+        ranked_rowid_v(FrontierRanking::Comprehensibility { top_word_count: 2 }, Order::Descending)?,
+        vec![1, 4, 8, 5, 6, 7, 3],
+    );
+
+    // Seeing sentence 3 makes "müde" recent, which sentence 1 shares.
+    fixture.db_hub.mark_sentence_seen(3)?;
+    assert_eq!(ranked_rowid_v(FrontierRanking::UnknownWordRecency, Order::Descending)?[..2], [1, 3]);
+
+    // A single weighted ranking orders the same as the unweighted one, but is rescaled.
+    let weighted_v = fixture.db_hub.query_word_frontier_v(
+        &FrontierQuery::new(Range(1, 3)).ranking(FrontierRanking::Weighted(vec![(1.0, FrontierRanking::SumUnknownWordFreq)])),
+    )?;
+    assert_eq!(weighted_v.iter().map(|m| m.sentences_rowid).collect::<Vec<_>>(), vec![6, 3, 5, 1, 7, 8, 4]);
+    assert_eq!(weighted_v[0].rank_score, 1.0);
+    assert_eq!(weighted_v[6].rank_score, 0.0);
+    assert_eq!(
+        ranked_rowid_v(
+            FrontierRanking::Weighted(vec![
+                (1.0, FrontierRanking::SentenceLength),
+                (-2.0, FrontierRanking::SumUnknownWordFreq),
+            ]),
+            Order::Descending,
+        )?[0],
+        7,
+    );
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_word_frontier_diversified() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin", "ist", "hier", "Das", "das", "Du", "bist"])?;
+    let rowid_v = |frontier_query: FrontierQuery| -> Result<Vec<i32>> {
+        Ok(fixture.db_hub.query_word_frontier_v(&frontier_query)?.iter().map(|m| m.sentences_rowid).collect())
+    };
+
+    // Sentences 1 and 3 both have only "müde" unknown, sentence 4 only "Tom".
+    let frontier_query = FrontierQuery::new(Range(1, 1)).order(Order::Descending);
+    assert_eq!(rowid_v(frontier_query.clone())?, vec![1, 3, 4]);
+
+    let capped_query = frontier_query.clone().diversified(FrontierDiversification {
+        max_sentences_per_group: 1,
+        round_robin: false,
+    });
+    assert_eq!(rowid_v(capped_query.clone())?, vec![1, 4]);
+    assert_eq!(fixture.db_hub.query_word_frontier_count(&capped_query)?, 2);
+
+    let round_robin_query = frontier_query.diversified(FrontierDiversification {
+        max_sentences_per_group: 2,
+        round_robin: true,
+    });
+    assert_eq!(rowid_v(round_robin_query)?, vec![1, 4, 3]);
+
+    // Groups are by the whole set of unknown words, so these are all distinct.
+    let multi_query = FrontierQuery::new(Range(2, 3)).diversified(FrontierDiversification {
+        max_sentences_per_group: 1,
+        round_robin: false,
+    });
+    assert_eq!(rowid_v(multi_query)?.len(), 4);
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_word_frontier_filters() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let rowid_v = |frontier_query: FrontierQuery| -> Result<Vec<i32>> {
+        Ok(fixture.db_hub
+            .query_word_frontier_v(&frontier_query.order(Order::Unordered))?
+            .iter()
+            .map(|m| m.sentences_rowid)
+            .collect())
+    };
+    let all_query = FrontierQuery::new(Range(0, 10));
+
+    assert_eq!(rowid_v(all_query.clone().token_count_range(Range(4, 4)))?, vec![5, 6, 7]);
+    assert_eq!(rowid_v(all_query.clone().char_length_range(Range(0, 13)))?, vec![1, 2, 3, 4]);
+    assert_eq!(rowid_v(all_query.clone().text_filter(TextFilter::Contains("Haus".into())))?, vec![5, 6]);
+    assert_eq!(
+        rowid_v(
+            all_query.clone()
+                .text_filter(TextFilter::DoesNotContain("Ich".into()))
+                .text_filter(TextFilter::DoesNotMatchRegex(r"\?$".into()))
+        )?,
+        vec![3, 4, 5],
+    );
+    assert_eq!(rowid_v(all_query.clone().text_filter(TextFilter::MatchesRegex("^(Ich|Du) ".into())))?, vec![1, 2, 3, 6, 8]);
+    assert_eq!(rowid_v(all_query.clone().text_filter(TextFilter::Search("MUDE".into())))?, vec![1, 3]);
+    assert_eq!(
+        fixture.db_hub.query_word_frontier_count(&all_query.clone().text_filter(TextFilter::Search("krankenhaus".into())))?,
+        1,
+    );
+    assert!(rowid_v(all_query.text_filter(TextFilter::MatchesRegex("(".into()))).is_err());
+    Ok(())
+}
+
 #[test]
 #[serial_test::serial]
 fn test_db_hub_query_known_word_with_text() -> Result<()> {
@@ -63,6 +314,1419 @@ fn test_langs_db_create_and_populate() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_sentences_containing_word() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Tom", "ist", "hier", "Das", "Haus"])?;
+    let ist_rowid = fixture.word_rowid("ist")?;
+    let db_hub = &fixture.db_hub;
+
+    // Easiest contexts come first, untranslated sentences included.
+    let concordance_member_v = db_hub.query_sentences_containing_word(ist_rowid, &ConcordanceQuery::new())?;
+    let sentences_rowid_v: Vec<i32> = concordance_member_v.iter().map(|m| m.sentences_rowid).collect();
+    assert_eq!(sentences_rowid_v, vec![4, 5, 7, 9]);
+    let unknown_word_count_v: Vec<i32> = concordance_member_v.iter().map(|m| m.unknown_word_count).collect();
+    assert_eq!(unknown_word_count_v, vec![0, 1, 3, 4]);
+    assert!(concordance_member_v.iter().all(|m| m.translation_with_text_v.is_empty()));
+
+    let concordance_member_v = db_hub.query_sentences_containing_word(
+        ist_rowid,
+        &ConcordanceQuery::new().order(Order::Descending).page(1, 3).with_translations(),
+    )?;
+    assert_eq!(concordance_member_v.len(), 1);
+    assert_eq!(concordance_member_v[0].sentences_rowid, 4);
+    assert_eq!(concordance_member_v[0].translation_with_text_v[0].reference_lang_sentence_text, "Tom is here.");
+
+    // A seeded sample is reproducible and drawn from the same sentences.
+    let sample = |seed| -> Result<Vec<i32>> {
+        Ok(db_hub
+            .query_sentences_containing_word(ist_rowid, &ConcordanceQuery::new().shuffled(seed).limit(2))?
+            .iter()
+            .map(|m| m.sentences_rowid)
+            .collect())
+    };
+    let sample_v = sample(7)?;
+    assert_eq!(sample_v.len(), 2);
+    assert_eq!(sample_v, sample(7)?);
+    assert!(sample_v.iter().all(|sentences_rowid| [4, 5, 7, 9].contains(sentences_rowid)));
+
+    // Corpus DBs created before the concordance index existed get it when opened.
+    let has_index = || -> Result<bool> {
+        let conn = rusqlite::Connection::open(CorpusDb::db_path_from("deu")?)?;
+        Ok(conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'sentence_memberships_word_rowid')",
+            [],
+            |row| row.get(0),
+        )?)
+    };
+    assert!(has_index()?);
+    rusqlite::Connection::open(CorpusDb::db_path_from("deu")?)?.execute("DROP INDEX sentence_memberships_word_rowid", [])?;
+    assert!(!has_index()?);
+    DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+    assert!(has_index()?);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_recommend_next_words() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin"])?;
+
+    // "müde" and "hier" are each the only unknown word in one sentence.
+    let next_word_recommendation_v = fixture.db_hub.recommend_next_words(2, NextWordRanking::UnlockedSentenceCount)?;
+    let mut word_text_v: Vec<&str> = next_word_recommendation_v.iter().map(|r| r.word_text.as_str()).collect();
+    word_text_v.sort_unstable();
+    assert_eq!(word_text_v, vec!["hier", "müde"]);
+    assert!(next_word_recommendation_v.iter().all(|r| r.unlocked_sentence_count == 1 && r.word_freq == 2));
+    let mude = next_word_recommendation_v.iter().find(|r| r.word_text == "müde").unwrap();
+    let example_rowid_v: Vec<i32> = mude.example_sentence_v.iter().map(|m| m.sentences_rowid).collect();
+    assert_eq!(example_rowid_v, vec![1, 3]);
+    assert_eq!(mude.example_sentence_v[0].translation_with_text_v[0].reference_lang_sentence_text, "I am tired.");
+
+    // "ist" is the most frequent unknown word, but unlocks nothing yet.
+    let next_word_recommendation_v = fixture.db_hub.recommend_next_words(1, NextWordRanking::CorpusFreq)?;
+    assert_eq!(next_word_recommendation_v[0].word_text, "ist");
+    assert_eq!(next_word_recommendation_v[0].word_freq, 4);
+    assert_eq!(next_word_recommendation_v[0].unlocked_sentence_count, 0);
+
+    // Save a recommendation as a word set.
+    let word_rowid_v: Vec<i32> = fixture.db_hub
+        .recommend_next_words(3, NextWordRanking::UnlockedSentenceCount)?
+        .iter()
+        .map(|r| r.word_rowid)
+        .collect();
+    let word_sets_rowid = fixture.db_hub.create_word_set("Next Words", &word_rowid_v)?;
+    assert!(fixture.db_hub.create_word_set("Next Words", &word_rowid_v).is_err());
+    let word_set_row_v = fixture.db_hub.query_word_set_v()?;
+    assert_eq!(word_set_row_v.len(), 1);
+    assert_eq!(word_set_row_v[0].name, "Next Words");
+    let member_rowid_v: Vec<i32> = fixture.db_hub
+        .query_word_set_member_with_text_v(word_sets_rowid)?
+        .iter()
+        .map(|m| m.word_rowid)
+        .collect();
+    assert_eq!(member_rowid_v, word_rowid_v);
+    fixture.db_hub.delete_word_set(word_sets_rowid)?;
+    assert!(fixture.db_hub.query_word_set_v()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_learning_path() {
+    // Sentences: {1}, {1, 2}, {2, 3}, {3}, {3}, and one already known.
+    let sentence_unknown_word_vv = vec![vec![1], vec![1, 2], vec![2, 3], vec![3], vec![3], vec![]];
+    let word_freq_m = vec![(1, 10), (2, 5), (3, 1)].into_iter().collect();
+    let learning_path_step_v = plan_learning_path(&sentence_unknown_word_vv, &word_freq_m, 10);
+    let word_rowid_v: Vec<i32> = learning_path_step_v.iter().map(|step| step.word_rowid).collect();
+    assert_eq!(word_rowid_v, vec![3, 1, 2]);
+    assert_eq!(
+        learning_path_step_v[0],
+        LearningPathStep {
+            word_rowid: 3,
+            newly_comprehensible_sentence_count: 2,
+            comprehensible_sentence_count: 3,
+            coverage: 0.5,
+        }
+    );
+    let newly_v: Vec<usize> = learning_path_step_v.iter().map(|step| step.newly_comprehensible_sentence_count).collect();
+    assert_eq!(newly_v, vec![2, 1, 2]);
+    assert_eq!(learning_path_step_v[2].coverage, 1.0);
+    assert_eq!(plan_learning_path(&sentence_unknown_word_vv, &word_freq_m, 1).len(), 1);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_plan_learning_path() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin"])?;
+
+    let learning_path_step_v = fixture.db_hub.plan_learning_path(4)?;
+    let word_text_v: Vec<&str> = learning_path_step_v.iter().map(|step| step.word_text.as_str()).collect();
+    // Learning "ist" unlocks nothing directly, but leaves "Tom" as the only unknown word of "Tom ist hier."
+    assert_eq!(word_text_v, vec!["müde", "hier", "ist", "Tom"]);
+    let comprehensible_v: Vec<usize> = learning_path_step_v.iter().map(|step| step.comprehensible_sentence_count).collect();
+    assert_eq!(comprehensible_v, vec![1, 2, 2, 3]);
+    // The untranslated sentence isn't part of the frontier, so there are 8 sentences.
+    assert_eq!(learning_path_step_v[3].coverage, 3.0 / 8.0);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_stats() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let stats = fixture.db_hub.stats()?;
+    assert_eq!((stats.known_word_count, stats.word_count, stats.token_count), (0, 21, 33));
+    assert_eq!(stats.token_coverage, 0.0);
+    assert_eq!(stats.sentence_count_by_unknown_word_count, [0, 0, 0, 9]);
+
+    // "Ich" occurs 4 times and "bin" twice.
+    fixture.add_known_words(&["Ich", "bin"])?;
+    let stats = fixture.db_hub.stats()?;
+    assert_eq!((stats.known_word_count, stats.known_token_count), (2, 6));
+    assert_eq!(stats.type_coverage, 2.0 / 21.0);
+    assert_eq!(stats.token_coverage, 6.0 / 33.0);
+    assert_eq!(stats.sentence_count, 9);
+    assert_eq!(stats.sentence_count_by_unknown_word_count, [0, 2, 1, 6]);
+    assert_eq!(stats.frequency_band_coverage_v.len(), 3);
+    let top_100 = &stats.frequency_band_coverage_v[0];
+    assert_eq!((top_100.top_word_count, top_100.word_count, top_100.known_word_count), (100, 21, 2));
+    assert_eq!(top_100.token_coverage, 6.0 / 33.0);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_progress_history() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin", "hier"])?;
+    // Re-adding and removing unknown words aren't changes, so they aren't recorded.
+    fixture.add_known_words(&["Ich"])?;
+    fixture.db_hub.remove_known_word(fixture.word_rowid("müde")?)?;
+    fixture.db_hub.remove_known_word(fixture.word_rowid("hier")?)?;
+
+    let known_word_timeline_point_v = fixture.db_hub.query_known_word_timeline_v()?;
+    assert_eq!(known_word_timeline_point_v.len(), 1);
+    assert_eq!(
+        (known_word_timeline_point_v[0].added_count, known_word_timeline_point_v[0].removed_count, known_word_timeline_point_v[0].known_word_count),
+        (3, 1, 2)
+    );
+
+    let progress_snapshot_row = fixture.db_hub.record_progress_snapshot()?;
+    assert_eq!(progress_snapshot_row.known_word_count, 2);
+    assert_eq!(progress_snapshot_row.token_coverage, 6.0 / 33.0);
+    // "Ich bin müde." and "Ich bin hier."
+    assert_eq!(progress_snapshot_row.frontier_sentence_count, 2);
+
+    // Recording again on the same day replaces that day's snapshot.
+    fixture.add_known_words(&["müde"])?;
+    fixture.db_hub.record_progress_snapshot()?;
+    let progress_snapshot_row_v = fixture.db_hub.query_progress_snapshot_v()?;
+    assert_eq!(progress_snapshot_row_v.len(), 1);
+    assert_eq!(progress_snapshot_row_v[0].day, progress_snapshot_row.day);
+    assert_eq!(progress_snapshot_row_v[0].known_word_count, 3);
+    assert_eq!(progress_snapshot_row_v[0].frontier_sentence_count, 1);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_analyze_text() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin"])?;
+
+    let text_analysis = fixture.db_hub.analyze_text("Ich bin hier. Das Haus ist sehr groß!\nich bin müde")?;
+    assert_eq!(text_analysis.token_count, 11);
+    // The lowercase "ich" is matched to the corpus's "Ich".
+    assert_eq!(
+        (text_analysis.known_token_count, text_analysis.unknown_token_count, text_analysis.out_of_corpus_token_count),
+        (4, 6, 1)
+    );
+    assert_eq!(text_analysis.coverage, 4.0 / 11.0);
+    let ich = &text_analysis.word_v[0];
+    assert_eq!((ich.text.as_str(), ich.knowledge, ich.occurrence_count), ("Ich", WordKnowledge::Known, 2));
+    let sehr = text_analysis.word_v.iter().find(|word| word.text == "sehr").unwrap();
+    assert_eq!((sehr.word_rowid_o, sehr.knowledge), (None, WordKnowledge::OutOfCorpus));
+
+    let sentence_summary_v: Vec<(&str, usize, usize)> = text_analysis.sentence_v
+        .iter()
+        .map(|sentence| (sentence.text.as_str(), sentence.unknown_word_count, sentence.out_of_corpus_word_count))
+        .collect();
+    assert_eq!(
+        sentence_summary_v,
+        vec![("Ich bin hier.", 1, 0), ("Das Haus ist sehr groß!", 4, 1), ("ich bin müde", 1, 0)]
+    );
+
+    let glossary_word_text_v: Vec<&str> = text_analysis.glossary_v.iter().map(|entry| entry.word_text.as_str()).collect();
+    assert_eq!(glossary_word_text_v, vec!["hier", "Das", "Haus", "ist", "groß", "müde"]);
+    let hier_example_sentence = text_analysis.glossary_v[0].example_sentence_o.as_ref().unwrap();
+    assert_eq!(hier_example_sentence.text, "Ich bin hier.");
+    assert_eq!(hier_example_sentence.translation_with_text_v[0].reference_lang_sentence_text, "I am here.");
+
+    // Translated example sentences are preferred over easier untranslated ones.
+    fixture.add_known_words(&["Das", "ist", "Satz", "ohne", "Übersetzung"])?;
+    let text_analysis = fixture.db_hub.analyze_text("ein Satz ohne Übersetzung")?;
+    let ein_example_sentence = text_analysis.glossary_v[0].example_sentence_o.as_ref().unwrap();
+    assert_eq!(ein_example_sentence.text, "Ich habe ein Haus.");
+    assert_eq!(ein_example_sentence.translation_with_text_v[0].reference_lang_sentence_text, "I have a house.");
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_induce_glosses() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    assert!(!fixture.db_hub.has_glosses()?);
+    // Glosses count as induced even if none were found.
+    fixture.db_hub.induce_glosses(&GlossInduction { min_cooccurrence_count: 100, ..GlossInduction::default() })?;
+    assert!(fixture.db_hub.has_glosses()?);
+    assert!(fixture.db_hub.query_gloss_v(fixture.word_rowid("Ich")?)?.is_empty());
+    fixture.db_hub.induce_glosses(&GlossInduction::default())?;
+
+    let gloss_summary_v = |word_text| -> Result<Vec<(String, f64)>> {
+        Ok(fixture.db_hub
+            .query_gloss_v(fixture.word_rowid(word_text)?)?
+            .into_iter()
+            .map(|gloss| (gloss.reference_word_text, gloss.score))
+            .collect())
+    };
+    // "Ich" and "I" occur in the same 4 sentence pairs, "am" in 2 of them.
+    assert_eq!(gloss_summary_v("Ich")?, vec![("I".to_string(), 1.0), ("am".to_string(), 2.0 * 2.0 / 6.0)]);
+    assert_eq!(gloss_summary_v("müde")?[0], ("tired".to_string(), 1.0));
+    // "Krankenhaus" only occurs once, so there's too little evidence.
+    assert!(gloss_summary_v("Krankenhaus")?.is_empty());
+
+    let sentence_membership_with_text_etc_v = fixture.db_hub.query_sentence_membership_with_text_etc_v(1)?;
+    let mude = sentence_membership_with_text_etc_v.iter().find(|m| m.word_text == "müde").unwrap();
+    assert_eq!(mude.gloss_v[0].reference_word_text, "tired");
+
+    let concordance_member_v = fixture.db_hub.query_sentences_containing_word(
+        fixture.word_rowid("Haus")?,
+        &ConcordanceQuery::new().with_translations(),
+    )?;
+    assert_eq!(concordance_member_v.len(), 2);
+    for concordance_member in concordance_member_v.iter() {
+        assert_eq!(concordance_member.aligned_gloss_v[0].reference_word_text, "house");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_query_sentence_tokens() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    assert!(fixture.db_hub.has_sentence_tokens()?);
+    {
+        // Corpus DBs created by older versions don't record which tables have been computed.
+        rusqlite::Connection::open(CorpusDb::db_path_from("deu")?)?.execute("DROP TABLE computed_tables", [])?;
+        let mut db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+        assert!(!db_hub.has_sentence_tokens()?);
+        assert!(!db_hub.has_word_classes()?);
+        db_hub.compute_sentence_tokens()?;
+        assert!(db_hub.has_sentence_tokens()?);
+    }
+    fixture.add_known_words(&["Haus"])?;
+
+    let sentence_token_row_v = fixture.db_hub.query_sentence_token_v(5)?;
+    assert_eq!(
+        sentence_token_row_v.iter().map(|row| (row.token_index, row.byte_offset, row.surface.as_str())).collect::<Vec<_>>(),
+        vec![(0, 0, "Das"), (1, 4, "Haus"), (2, 9, "ist"), (3, 13, "groß")],
+    );
+    assert_eq!(sentence_token_row_v[1].word_rowid, fixture.word_rowid("Haus")?);
+
+    let span_summary_v = |sentence_rowid| -> Result<Vec<(String, Option<bool>)>> {
+        Ok(fixture.db_hub
+            .query_sentence_tokens(sentence_rowid)?
+            .into_iter()
+            .map(|span| (span.text, span.token_o.map(|token| token.word_is_known)))
+            .collect())
+    };
+    assert_eq!(
+        span_summary_v(5)?,
+        vec![
+            ("Das".to_string(), Some(false)),
+            (" ".to_string(), None),
+            ("Haus".to_string(), Some(true)),
+            (" ".to_string(), None),
+            ("ist".to_string(), Some(false)),
+            (" ".to_string(), None),
+            ("groß".to_string(), Some(false)),
+            (".".to_string(), None),
+        ],
+    );
+    let sentence_span_v = fixture.db_hub.query_sentence_tokens(7)?;
+    assert_eq!(sentence_span_v.iter().map(|span| span.text.as_str()).collect::<String>(), "Wo ist das Krankenhaus?");
+    assert_eq!(sentence_span_v.last().map(|span| span.byte_offset), Some(22));
+
+    // Recomputing gives the same tokens.
+    fixture.db_hub.compute_sentence_tokens()?;
+    assert_eq!(fixture.db_hub.query_sentence_token_v(5)?.len(), 4);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_ngrams() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    // The fixture corpus is too small for the default selection.
+    assert!(fixture.db_hub.query_ngram_v(10)?.is_empty());
+
+    // "Ich bin" is the only n-gram occurring twice; its PMI is log2((2/24) / ((4/33) * (2/33))).
+    let ngram_selection = NgramSelection { min_freq: 2, min_pmi: 0.0, ..NgramSelection::default() };
+    assert_eq!(fixture.db_hub.compute_ngrams(&ngram_selection)?, 1);
+    let ngram_with_known_v = fixture.db_hub.query_ngram_v(10)?;
+    assert_eq!(ngram_with_known_v.len(), 1);
+    let ich_bin = &ngram_with_known_v[0].ngram_row;
+    assert_eq!((ich_bin.text.as_str(), ich_bin.word_count, ich_bin.freq), ("Ich bin", 2, 2));
+    assert!((ich_bin.pmi - 3.5038).abs() < 1e-3, "pmi = {}", ich_bin.pmi);
+    assert!(!ngram_with_known_v[0].ngram_is_known);
+    assert_eq!(fixture.db_hub.query_sentence_ngram_v(2)?.len(), 1);
+    assert!(fixture.db_hub.query_sentence_ngram_v(3)?.is_empty());
+    assert_eq!(
+        fixture.db_hub.compute_ngrams(&NgramSelection { min_pmi: 4.0, ..ngram_selection.clone() })?,
+        0,
+    );
+    assert_eq!(
+        fixture.db_hub.compute_ngrams(&NgramSelection { min_freq: 1, min_pmi: 5.0, max_word_count: 2 })?,
+        // The bigrams whose words each occur only once: "Du bist", "das Krankenhaus",
+        // "liebe dich", "Satz ohne" and "ohne Übersetzung".
+        5,
+    );
+
+    // Recomputing keeps the rowid of an n-gram that is selected again.
+    fixture.db_hub.compute_ngrams(&ngram_selection)?;
+    let ich_bin_rowid = fixture.db_hub.query_ngram_v(10)?[0].ngram_row.ngrams_rowid;
+    fixture.db_hub.add_known_ngram(ich_bin_rowid)?;
+    fixture.db_hub.compute_ngrams(&ngram_selection)?;
+    let known_ngram_v = fixture.db_hub.query_known_ngram_v()?;
+    assert_eq!(known_ngram_v.len(), 1);
+    assert_eq!(known_ngram_v[0].ngram_row.ngrams_rowid, ich_bin_rowid);
+    assert!(fixture.db_hub.query_sentence_ngram_v(1)?[0].ngram_is_known);
+    // A known n-gram that a stricter selection drops stays known as the same n-gram, and its
+    // rowid isn't reused by the n-grams that a later selection adds.
+    assert_eq!(fixture.db_hub.compute_ngrams(&NgramSelection { min_pmi: 4.0, ..ngram_selection.clone() })?, 0);
+    assert!(fixture.db_hub.query_ngram_v(10)?.is_empty());
+    assert!(fixture.db_hub.query_sentence_ngram_v(1)?.is_empty());
+    let known_ngram_v = fixture.db_hub.query_known_ngram_v()?;
+    assert_eq!((known_ngram_v[0].ngram_row.text.as_str(), known_ngram_v[0].ngram_row.freq), ("Ich bin", 0));
+    fixture.db_hub.compute_ngrams(&NgramSelection { min_freq: 1, min_pmi: 5.0, max_word_count: 2 })?;
+    let ngram_with_known_v = fixture.db_hub.query_ngram_v(10)?;
+    assert_eq!(ngram_with_known_v.len(), 5);
+    assert!(ngram_with_known_v.iter().all(|ngram_with_known| !ngram_with_known.ngram_is_known));
+    let known_ngram_v = fixture.db_hub.query_known_ngram_v()?;
+    assert_eq!(known_ngram_v.len(), 1);
+    assert_eq!(known_ngram_v[0].ngram_row.text, "Ich bin");
+    fixture.db_hub.compute_ngrams(&ngram_selection)?;
+    assert_eq!(fixture.db_hub.query_ngram_v(10)?[0].ngram_row.ngrams_rowid, ich_bin_rowid);
+    assert!(fixture.db_hub.query_sentence_ngram_v(1)?[0].ngram_is_known);
+
+    // Known n-grams only cover words in the frontier when asked to.
+    fixture.add_known_words(&["müde", "hier"])?;
+    let sentences_rowid_v = |frontier_query: FrontierQuery| -> Result<Vec<i32>> {
+        Ok(fixture.db_hub
+            .query_word_frontier_v(&frontier_query.order(Order::Unordered))?
+            .into_iter()
+            .map(|member| member.sentences_rowid)
+            .collect())
+    };
+    assert!(sentences_rowid_v(FrontierQuery::new(Range(0, 0)))?.is_empty());
+    let mut covered_sentences_rowid_v = sentences_rowid_v(FrontierQuery::new(Range(0, 0)).known_ngrams_cover_words(true))?;
+    covered_sentences_rowid_v.sort_unstable();
+    assert_eq!(covered_sentences_rowid_v, vec![1, 2]);
+    // Sentence 6 has "Ich" but not "Ich bin", so "Ich" stays unknown there.
+    assert!(sentences_rowid_v(FrontierQuery::new(Range(4, 4)).known_ngrams_cover_words(true))?.contains(&6));
+
+    fixture.db_hub.remove_known_ngram(ich_bin_rowid)?;
+    assert!(sentences_rowid_v(FrontierQuery::new(Range(0, 0)).known_ngrams_cover_words(true))?.is_empty());
+
+    // Use a corpus in which a word also occurs outside a known n-gram; the sentences keep the
+    // fixture's translations.
+    std::fs::remove_file(CorpusDb::db_path_from("deu")?)?;
+    CorpusDb::open(LangsDb::open()?.query_lang_row("deu")?)?.populate_from_sentences_tsv("\
+1\tdeu\tGuten Morgen!
+2\tdeu\tGuten Morgen, Tom.
+3\tdeu\tMorgen sage ich Guten Morgen.
+")?;
+    let mut db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+    assert_eq!(db_hub.compute_ngrams(&ngram_selection)?, 1);
+    let guten_morgen = db_hub.query_ngram_v(1)?.remove(0).ngram_row;
+    assert_eq!((guten_morgen.text.as_str(), guten_morgen.freq), ("Guten Morgen", 3));
+    db_hub.add_known_ngram(guten_morgen.ngrams_rowid)?;
+    for word_text in ["Tom", "sage", "ich"].iter() {
+        let word_rowid: i32 = rusqlite::Connection::open(CorpusDb::db_path_from("deu")?)?.query_row(
+            "SELECT words_rowid FROM words WHERE text = ?1",
+            rusqlite::params![word_text],
+            |row| row.get(0),
+        )?;
+        db_hub.add_known_word(word_rowid)?;
+    }
+    let frontier_query = FrontierQuery::new(Range(0, 0)).known_ngrams_cover_words(true).order(Order::Unordered);
+    let mut covered_sentences_rowid_v: Vec<i32> = db_hub
+        .query_word_frontier_v(&frontier_query)?
+        .into_iter()
+        .map(|member| member.sentences_rowid)
+        .collect();
+    covered_sentences_rowid_v.sort_unstable();
+    // The first "Morgen" of sentence 3 isn't part of "Guten Morgen".
+    assert_eq!(covered_sentences_rowid_v, vec![1, 2]);
+    let frontier_query = FrontierQuery::new(Range(1, 1)).known_ngrams_cover_words(true).order(Order::Unordered);
+    let member_v = db_hub.query_word_frontier_v(&frontier_query)?;
+    assert_eq!(member_v.len(), 1);
+    assert_eq!(member_v[0].sentences_rowid, 3);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_word_classes() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    // German capitalizes all nouns, so proper nouns are found via the translations instead,
+    // which the corpus import doesn't have.
+    assert!(!fixture.db_hub.has_word_classes()?);
+    assert_eq!(fixture.db_hub.query_word_class_o(fixture.word_rowid("Tom")?)?, None);
+    fixture.db_hub.compute_word_classes()?;
+    assert!(fixture.db_hub.has_word_classes()?);
+    // "Tom ist hier." is translated as "Tom is here.", but "Das Haus ist groß." as "The house
+    // is big.".
+    assert_eq!(fixture.db_hub.query_word_class_o(fixture.word_rowid("Tom")?)?, Some(WordClass::LikelyProperNoun));
+    assert_eq!(fixture.db_hub.query_word_class_o(fixture.word_rowid("Haus")?)?, None);
+    assert_eq!(fixture.db_hub.query_word_class_o(fixture.word_rowid("Ich")?)?, None);
+
+    // Use an English corpus with names, numbers and symbols as the target language instead.
+    std::fs::remove_file(CorpusDb::db_path_from("eng")?)?;
+    CorpusDb::open(LangsDb::open()?.query_lang_row("eng")?)?.populate_from_sentences_tsv("\
+201\teng\tTom was born in 1984.
+202\teng\tI saw Tom and Mary.
+203\teng\tMary likes Tom.
+204\teng\tTom loves Mary.
+205\teng\tAsk Tom — he knows.
+206\teng\tIs Boston big?
+")?;
+    TranslationsDb::open("eng", "deu")?.populate_from_links_tsv("201\t1\n202\t2\n203\t3\n204\t4\n205\t5\n206\t6\n")?;
+    let db_hub = DbHub::from_config(DbHubConfig::new("eng", "deu", None)?)?;
+    let word_rowid = |word_text: &str| -> Result<i32> {
+        Ok(rusqlite::Connection::open(CorpusDb::db_path_from("eng")?)?.query_row(
+            "SELECT words_rowid FROM words WHERE text = ?1",
+            rusqlite::params![word_text],
+            |row| row.get(0),
+        )?)
+    };
+
+    assert!(db_hub.has_word_classes()?);
+    assert_eq!(db_hub.query_word_class_o(word_rowid("1984")?)?, Some(WordClass::Number));
+    assert_eq!(db_hub.query_word_class_o(word_rowid("—")?)?, Some(WordClass::Symbol));
+    assert_eq!(db_hub.query_word_class_o(word_rowid("Tom")?)?, Some(WordClass::LikelyProperNoun));
+    assert_eq!(db_hub.query_word_class_o(word_rowid("Mary")?)?, Some(WordClass::LikelyProperNoun));
+    // Boston is only capitalized mid-sentence once, which isn't enough evidence, and single
+    // letters like "I" are never proper nouns.
+    assert_eq!(db_hub.query_word_class_o(word_rowid("Boston")?)?, None);
+    assert_eq!(db_hub.query_word_class_o(word_rowid("I")?)?, None);
+
+    for word_text in ["was", "born", "in", "I", "saw", "and", "likes", "loves", "Ask", "he", "knows", "Is", "big"].iter() {
+        db_hub.add_known_word(word_rowid(word_text)?)?;
+    }
+    let sentences_rowid_v = |frontier_query: FrontierQuery| -> Result<Vec<i32>> {
+        let mut sentences_rowid_v: Vec<i32> = db_hub
+            .query_word_frontier_v(&frontier_query.order(Order::Unordered))?
+            .into_iter()
+            .map(|member| member.sentences_rowid)
+            .collect();
+        sentences_rowid_v.sort_unstable();
+        Ok(sentences_rowid_v)
+    };
+    assert!(sentences_rowid_v(FrontierQuery::new(Range(0, 0)))?.is_empty());
+    let frontier_query = FrontierQuery::new(Range(0, 0))
+        .auto_known_word_class(WordClass::Number)
+        .auto_known_word_class(WordClass::Symbol)
+        .auto_known_word_class(WordClass::LikelyProperNoun);
+    assert_eq!(sentences_rowid_v(frontier_query.clone())?, vec![201, 202, 203, 204, 205]);
+    assert_eq!(sentences_rowid_v(frontier_query.clone().auto_known_word_class(WordClass::Name))?, vec![201, 202, 203, 204, 205]);
+
+    db_hub.add_name(word_rowid("Boston")?)?;
+    assert_eq!(db_hub.query_word_class_o(word_rowid("Boston")?)?, Some(WordClass::Name));
+    assert_eq!(db_hub.query_name_with_text_v()?.iter().map(|name| name.word_text.as_str()).collect::<Vec<_>>(), vec!["Boston"]);
+    assert_eq!(sentences_rowid_v(frontier_query.clone())?, vec![201, 202, 203, 204, 205]);
+    assert_eq!(sentences_rowid_v(frontier_query.auto_known_word_class(WordClass::Name))?, vec![201, 202, 203, 204, 205, 206]);
+    let sentence_membership_with_text_etc_v = db_hub.query_sentence_membership_with_text_etc_v(206)?;
+    assert_eq!(
+        sentence_membership_with_text_etc_v.iter().find(|m| m.word_text == "Boston").and_then(|m| m.word_class_o),
+        Some(WordClass::Name),
+    );
+
+    db_hub.remove_name(word_rowid("Boston")?)?;
+    assert!(db_hub.query_name_with_text_v()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_split_compounds() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    assert!(!fixture.db_hub.has_compounds()?);
+    // Compounds count as split even if none were found.
+    assert_eq!(fixture.db_hub.split_compounds(&CompoundSplitting::default())?, 0);
+    assert!(fixture.db_hub.has_compounds()?);
+
+    // Use a corpus with compounds instead; the sentences keep the fixture's translations.
+    std::fs::remove_file(CorpusDb::db_path_from("deu")?)?;
+    CorpusDb::open(LangsDb::open()?.query_lang_row("deu")?)?.populate_from_sentences_tsv("\
+1\tdeu\tIch bin krank.
+2\tdeu\tDas Haus ist alt.
+3\tdeu\tIch war im Krankenhaus.
+4\tdeu\tDer Krankenhausaufenthalt war lang.
+5\tdeu\tDas Haustier schläft.
+6\tdeu\tDas Tier ist klein.
+7\tdeu\tDie Arbeitszeit ist lang.
+8\tdeu\tDie Arbeit braucht Zeit.
+9\tdeu\tDer Aufenthalt war kurz.
+")?;
+    let mut db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+    // Every component occurs only once here.
+    let compound_splitting = CompoundSplitting { min_component_freq: 1, ..CompoundSplitting::default() };
+    assert_eq!(db_hub.split_compounds(&compound_splitting)?, 4);
+    assert!(db_hub.has_compounds()?);
+
+    let component_text_v = |word_text: &str| -> Result<Vec<String>> {
+        Ok(db_hub
+            .query_compound_component_with_text_v(fixture.word_rowid(word_text)?)?
+            .into_iter()
+            .map(|component| component.component_word_text)
+            .collect())
+    };
+    // Linking elements are dropped, and the fewest components win.
+    assert_eq!(component_text_v("Krankenhaus")?, vec!["krank", "Haus"]);
+    assert_eq!(component_text_v("Krankenhausaufenthalt")?, vec!["Krankenhaus", "Aufenthalt"]);
+    assert_eq!(component_text_v("Haustier")?, vec!["Haus", "Tier"]);
+    assert_eq!(component_text_v("Arbeitszeit")?, vec!["Arbeit", "Zeit"]);
+    assert!(component_text_v("schläft")?.is_empty());
+
+    for word_text in ["Ich", "bin", "krank", "Das", "Haus", "ist", "alt", "war", "im", "Der", "lang", "Tier", "klein", "Die", "Arbeit", "braucht", "Zeit"].iter() {
+        db_hub.add_known_word(fixture.word_rowid(word_text)?)?;
+    }
+    assert!(db_hub.query_compound_component_with_text_v(fixture.word_rowid("Arbeitszeit")?)?.iter().all(|component| component.component_is_known));
+    let sentences_rowid_v = |db_hub: &DbHub, frontier_query: FrontierQuery| -> Result<Vec<i32>> {
+        let mut sentences_rowid_v: Vec<i32> = db_hub
+            .query_word_frontier_v(&frontier_query.order(Order::Unordered))?
+            .into_iter()
+            .map(|member| member.sentences_rowid)
+            .collect();
+        sentences_rowid_v.sort_unstable();
+        Ok(sentences_rowid_v)
+    };
+    assert_eq!(sentences_rowid_v(&db_hub, FrontierQuery::new(Range(0, 0)))?, vec![1, 2, 6, 8]);
+    // Krankenhausaufenthalt's component Krankenhaus is only known by its own components, which
+    // doesn't count.
+    assert_eq!(
+        sentences_rowid_v(&db_hub, FrontierQuery::new(Range(0, 0)).compounds_known_by_components(true))?,
+        vec![1, 2, 3, 6, 7, 8],
+    );
+    db_hub.add_known_word(fixture.word_rowid("Krankenhaus")?)?;
+    db_hub.add_known_word(fixture.word_rowid("Aufenthalt")?)?;
+    assert_eq!(
+        sentences_rowid_v(&db_hub, FrontierQuery::new(Range(0, 0)).compounds_known_by_components(true))?,
+        vec![1, 2, 3, 4, 6, 7, 8],
+    );
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_word_notes() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let haus_rowid = fixture.word_rowid("Haus")?;
+    let muede_rowid = fixture.word_rowid("müde")?;
+
+    assert!(fixture.db_hub.query_word_note_o(haus_rowid)?.is_none());
+    let word_note_row = fixture.db_hub.set_word_note(haus_rowid, &WordNote {
+        note: "Like the English \"house\"; plural Häuser".into(),
+        user_gloss: "house".into(),
+        tag_v: vec!["noun".into(), "neuter".into()],
+    })?;
+    assert_eq!(word_note_row.word_rowid, haus_rowid);
+    assert_eq!(fixture.db_hub.query_word_note_o(haus_rowid)?, Some(word_note_row.clone()));
+    fixture.db_hub.set_word_note(muede_rowid, &WordNote {
+        note: "sounds like 'mood'".into(),
+        user_gloss: "tired".into(),
+        tag_v: vec!["adjective".into()],
+    })?;
+    assert!(fixture.db_hub.set_word_note(muede_rowid, &WordNote { tag_v: vec!["two words".into()], ..WordNote::default() }).is_err());
+
+    // Updating keeps the same row.
+    let updated_word_note_row = fixture.db_hub.set_word_note(haus_rowid, &WordNote {
+        user_gloss: "house, home".into(),
+        ..word_note_row.word_note.clone()
+    })?;
+    assert_eq!(updated_word_note_row.word_notes_rowid, word_note_row.word_notes_rowid);
+    assert_eq!(updated_word_note_row.created_at, word_note_row.created_at);
+    assert_eq!(updated_word_note_row.word_note.user_gloss, "house, home");
+
+    let word_text_v = |word_note_with_text_v: Vec<wordfrontier::WordNoteWithText>| -> Vec<String> {
+        word_note_with_text_v.into_iter().map(|word_note_with_text| word_note_with_text.word_text).collect()
+    };
+    assert_eq!(word_text_v(fixture.db_hub.query_word_note_with_text_v(Some("noun"))?), vec!["Haus"]);
+    assert_eq!(word_text_v(fixture.db_hub.query_word_note_with_text_v(Some("neu"))?), Vec::<String>::new());
+    assert_eq!(fixture.db_hub.query_word_note_with_text_v(None)?.len(), 2);
+
+    // Search ignores case and accents, matches the last word as a prefix, and covers glosses
+    // and tags as well as notes.
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("HAUSER")?), vec!["Haus"]);
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("plural hä")?), vec!["Haus"]);
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("tired")?), vec!["müde"]);
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("adjective")?), vec!["müde"]);
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("\"mood\" OR")?), Vec::<String>::new());
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("'mood'")?), vec!["müde"]);
+    assert!(fixture.db_hub.search_word_notes("  ")?.is_empty());
+    // The index follows updates.
+    assert_eq!(word_text_v(fixture.db_hub.search_word_notes("home")?), vec!["Haus"]);
+
+    fixture.add_known_words(&["Haus"])?;
+    let known_word_with_text_v = fixture.db_hub.query_known_word_with_text_v()?;
+    assert_eq!(known_word_with_text_v[0].word_note_row_o.as_ref().map(|row| row.word_note.user_gloss.as_str()), Some("house, home"));
+    let sentence_membership_with_text_etc_v = fixture.db_hub.query_sentence_membership_with_text_etc_v(1)?;
+    let muede_membership = sentence_membership_with_text_etc_v.iter().find(|m| m.word_rowid == muede_rowid).unwrap();
+    assert_eq!(muede_membership.word_note_row_o.as_ref().unwrap().word_note.tag_v, vec!["adjective"]);
+
+    assert!(fixture.db_hub.delete_word_note(muede_rowid)?);
+    assert!(!fixture.db_hub.delete_word_note(muede_rowid)?);
+    assert!(fixture.db_hub.search_word_notes("tired")?.is_empty());
+    assert!(fixture.db_hub.query_word_note_o(muede_rowid)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_import_dictionary() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    let dictionary_dir = std::env::current_dir()?;
+
+    std::fs::write("deu-eng.tsv", "# headword\tgloss\nhaus\thouse\tbuilding\nmüde\ttired\nzu Hause\tat home\n")?;
+    let report = fixture.db_hub.import_dictionary("tsv", &dictionary_dir.join("deu-eng.tsv"), DictionaryFormat::Tsv)?;
+    assert_eq!((report.entry_count, report.matched_entry_count, report.matched_word_count), (3, 2, 2));
+
+    std::fs::write("deu-eng.tei", r#"<?xml version="1.0" encoding="UTF-8"?>
+<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body>
+<entry><form><orth>Krankenhaus</orth></form><sense>
+  <cit type="trans"><quote>hospital</quote></cit>
+  <cit type="example"><quote>im Krankenhaus</quote><cit type="trans"><quote>in hospital</quote></cit></cit>
+</sense></entry>
+<entry><form><orth>groß</orth></form><trans><tr>big</tr><tr>large</tr></trans></entry>
+</body></text></TEI>"#)?;
+    let report = fixture.db_hub.import_dictionary("tei", &dictionary_dir.join("deu-eng.tei"), DictionaryFormat::FreeDictTei)?;
+    assert_eq!((report.entry_count, report.matched_word_count), (2, 2));
+
+    // StarDict .idx records are a NUL-terminated word, then u32 BE offset and size into .dict.
+    std::fs::write("deu-eng.ifo", "StarDict's dict ifo file\nversion=2.4.2\nwordcount=2\nsametypesequence=m\n")?;
+    let mut idx_byte_v = Vec::new();
+    for (word, offset, size) in [("ich", 0u32, 1u32), ("liebe", 1, 4)].iter() {
+        idx_byte_v.extend_from_slice(word.as_bytes());
+        idx_byte_v.push(0);
+        idx_byte_v.extend_from_slice(&offset.to_be_bytes());
+        idx_byte_v.extend_from_slice(&size.to_be_bytes());
+    }
+    std::fs::write("deu-eng.idx", idx_byte_v)?;
+    std::fs::write("deu-eng.dict", "Ilove")?;
+    let report = fixture.db_hub.import_dictionary("stardict", &dictionary_dir.join("deu-eng.ifo"), DictionaryFormat::StarDict)?;
+    assert_eq!((report.entry_count, report.matched_word_count), (2, 2));
+
+    // Inflected forms are matched to their lemma's entry.
+    std::fs::write(
+        "deu.jsonl",
+        r#"{"word": "sein", "pos": "verb", "senses": [{"glosses": ["to be"]}], "forms": [{"form": "bin", "tags": ["first-person"]}, {"form": "ist"}, {"form": "bist"}, {"form": "de-conj", "tags": ["inflection-template"]}]}"#,
+    )?;
+    let report = fixture.db_hub.import_dictionary("wiktionary", &dictionary_dir.join("deu.jsonl"), DictionaryFormat::WiktionaryJsonl)?;
+    assert_eq!((report.entry_count, report.matched_entry_count, report.matched_word_count), (1, 1, 3));
+
+    let gloss_summary_v = |sentence_rowid| -> Result<Vec<(String, Vec<String>)>> {
+        Ok(fixture.db_hub
+            .query_sentence_membership_with_text_etc_v(sentence_rowid)?
+            .into_iter()
+            .map(|m| (m.word_text, m.dictionary_gloss_v.into_iter().map(|g| g.gloss).collect()))
+            .collect())
+    };
+    let mut gloss_summary_v_1 = gloss_summary_v(1)?;
+    gloss_summary_v_1.sort();
+    assert_eq!(
+        gloss_summary_v_1,
+        vec![
+            ("Ich".to_string(), vec!["I".to_string()]),
+            ("bin".to_string(), vec!["to be".to_string()]),
+            ("müde".to_string(), vec!["tired".to_string()]),
+        ]
+    );
+    let krankenhaus_gloss_v = fixture.db_hub.query_dictionary_gloss_v(fixture.word_rowid("Krankenhaus")?)?;
+    assert_eq!(krankenhaus_gloss_v.len(), 1);
+    assert_eq!((krankenhaus_gloss_v[0].dictionary_name.as_str(), krankenhaus_gloss_v[0].gloss.as_str()), ("tei", "hospital"));
+    assert_eq!(fixture.db_hub.query_dictionary_gloss_v(fixture.word_rowid("groß")?)?[0].gloss, "big; large");
+
+    fixture.add_known_words(&["Haus"])?;
+    let known_word_with_text_v = fixture.db_hub.query_known_word_with_text_v()?;
+    assert_eq!(known_word_with_text_v[0].dictionary_gloss_v[0].gloss, "house; building");
+
+    // Re-importing under the same name replaces the dictionary.
+    std::fs::write("deu-eng.tsv", "Haus\tdwelling\n")?;
+    fixture.db_hub.import_dictionary("tsv", &dictionary_dir.join("deu-eng.tsv"), DictionaryFormat::Tsv)?;
+    assert_eq!(fixture.db_hub.query_dictionary_v()?.len(), 4);
+    assert_eq!(fixture.db_hub.query_dictionary_gloss_v(fixture.word_rowid("Haus")?)?[0].gloss, "dwelling");
+    assert!(fixture.db_hub.query_dictionary_gloss_v(fixture.word_rowid("müde")?)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_undo_redo() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let known_word_text_v = |db_hub: &DbHub| -> Result<Vec<String>> {
+        let mut word_text_v: Vec<String> = db_hub.query_known_word_with_text_v()?.into_iter().map(|known_word| known_word.word_text).collect();
+        word_text_v.sort();
+        Ok(word_text_v)
+    };
+
+    assert_eq!(fixture.db_hub.undo()?, None);
+    fixture.add_known_words(&["Haus", "müde"])?;
+    // Adding an already known word changes nothing, so it isn't journaled.
+    fixture.add_known_words(&["Haus"])?;
+    fixture.db_hub.remove_known_word(fixture.word_rowid("Haus")?)?;
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["müde"]);
+
+    let journal_entry_v = fixture.db_hub.query_journal_entry_v(10)?;
+    assert_eq!(journal_entry_v.len(), 3);
+    assert_eq!(journal_entry_v[0].description, "Remove known word");
+    assert_eq!(journal_entry_v[0].change_v[0].word_text, "Haus");
+    assert!(!journal_entry_v[0].change_v[0].is_added);
+    assert_eq!(fixture.db_hub.query_journal_entry_v(1)?.len(), 1);
+
+    let undone_journal_entry = fixture.db_hub.undo()?.unwrap();
+    assert_eq!(undone_journal_entry.description, "Remove known word");
+    assert!(undone_journal_entry.is_undone);
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "müde"]);
+    fixture.db_hub.undo()?;
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus"]);
+
+    // The journal survives into the next session.
+    let db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+    assert_eq!(db_hub.redo()?.unwrap().change_v[0].word_text, "müde");
+    assert_eq!(known_word_text_v(&db_hub)?, vec!["Haus", "müde"]);
+    db_hub.undo()?;
+    db_hub.undo()?;
+    assert!(known_word_text_v(&db_hub)?.is_empty());
+    assert_eq!(db_hub.undo()?, None);
+    db_hub.redo()?;
+    assert_eq!(known_word_text_v(&db_hub)?, vec!["Haus"]);
+
+    // A new change discards what could have been redone.
+    db_hub.add_known_word(fixture.word_rowid("Tom")?)?;
+    assert_eq!(db_hub.redo()?, None);
+    assert_eq!(known_word_text_v(&db_hub)?, vec!["Haus", "Tom"]);
+    let journal_entry_v = db_hub.query_journal_entry_v(10)?;
+    assert_eq!(journal_entry_v.len(), 2);
+    assert!(journal_entry_v.iter().all(|journal_entry| !journal_entry.is_undone));
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_batch_known_words() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let known_word_text_v = || -> Result<Vec<String>> {
+        let mut word_text_v: Vec<String> = fixture.db_hub.query_known_word_with_text_v()?.into_iter().map(|known_word| known_word.word_text).collect();
+        word_text_v.sort();
+        Ok(word_text_v)
+    };
+
+    // "Das Haus ist groß."
+    fixture.add_known_words(&["Haus"])?;
+    assert_eq!(fixture.db_hub.add_sentence_words_to_known_words(5)?, 3);
+    assert_eq!(known_word_text_v()?, vec!["Das", "Haus", "groß", "ist"]);
+    // The whole batch is a single journal entry.
+    let journal_entry = fixture.db_hub.query_journal_entry_v(1)?.pop().unwrap();
+    assert_eq!(journal_entry.description, "Add sentence words");
+    assert_eq!(journal_entry.change_v.len(), 3);
+    fixture.db_hub.undo()?;
+    assert_eq!(known_word_text_v()?, vec!["Haus"]);
+
+    assert_eq!(fixture.db_hub.add_most_frequent_words_to_known_words(2)?, 2);
+    assert_eq!(known_word_text_v()?, vec!["Haus", "Ich", "ist"]);
+    // Nothing changes, so nothing is journaled (and the undone entry was discarded above).
+    assert_eq!(fixture.db_hub.add_most_frequent_words_to_known_words(2)?, 0);
+    assert_eq!(fixture.db_hub.query_journal_entry_v(10)?.len(), 2);
+
+    // "das" matches both "das" and "Das", and words not in the corpus are ignored.
+    assert_eq!(fixture.db_hub.add_listed_words_to_known_words(&["das", "Du", "Zebra", "HAUS"])?, 3);
+    assert_eq!(known_word_text_v()?, vec!["Das", "Du", "Haus", "Ich", "das", "ist"]);
+    assert_eq!(fixture.db_hub.remove_listed_words_from_known_words(&["das"])?, 2);
+    assert_eq!(fixture.db_hub.remove_most_frequent_words_from_known_words(2)?, 2);
+    assert_eq!(fixture.db_hub.remove_sentence_words_from_known_words(5)?, 1);
+    assert_eq!(known_word_text_v()?, vec!["Du"]);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_placement_test() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    let known_word_rowid_v = || -> Result<Vec<i32>> {
+        let mut word_rowid_v: Vec<i32> = fixture.db_hub.query_known_word_with_text_v()?.into_iter().map(|known_word| known_word.word_rowid).collect();
+        word_rowid_v.sort_unstable();
+        Ok(word_rowid_v)
+    };
+
+    // The fixture's 21 words make bands of ranks 1-5, 6-10 and 11-21.  The user knows all of the
+    // first band and none of the second, so the third isn't asked about.
+    let config = PlacementTestConfig {
+        band_top_word_count_v: vec![5, 10],
+        min_questions_per_band: 2,
+        max_questions_per_band: 5,
+        target_margin: 0.3,
+        stop_below_known_fraction: 0.3,
+        seed: 1,
+    };
+    let mut placement_test = fixture.db_hub.start_placement_test(config.clone())?;
+    let first_question_o = placement_test.next_question_o().cloned();
+    let mut known_question_word_rowid_v = Vec::new();
+    while let Some(question) = placement_test.next_question_o().cloned() {
+        assert!(question.band_index < 2);
+        if question.band_index == 0 {
+            known_question_word_rowid_v.push(question.word_rowid);
+        }
+        placement_test.answer(question.band_index == 0);
+    }
+    assert!(placement_test.is_finished());
+    assert_eq!(placement_test.answer_count(), 10);
+    // The same seed samples the same words.
+    assert_eq!(fixture.db_hub.start_placement_test(config)?.next_question_o(), first_question_o.as_ref());
+
+    let placement_estimate = placement_test.estimate();
+    let band_estimate_v = &placement_estimate.band_estimate_v;
+    assert_eq!(band_estimate_v.len(), 3);
+    assert_eq!((band_estimate_v[2].rank_range.0, band_estimate_v[2].rank_range.1, band_estimate_v[2].word_count), (11, 21, 11));
+    assert_eq!(band_estimate_v.iter().map(|band_estimate| band_estimate.estimated_known_word_count).collect::<Vec<_>>(), vec![5, 0, 0]);
+    assert!((band_estimate_v[0].known_fraction_low - 0.5655).abs() < 1e-4);
+    assert!((band_estimate_v[1].known_fraction_high - 0.4345).abs() < 1e-4);
+    // The unasked band is bounded by the one before it.
+    assert_eq!(band_estimate_v[2].answer_count, 0);
+    assert_eq!(band_estimate_v[2].known_fraction_high, band_estimate_v[1].known_fraction_high);
+    assert_eq!(
+        (placement_estimate.estimated_known_word_count, placement_estimate.estimated_known_word_count_low, placement_estimate.estimated_known_word_count_high),
+        (5, 3, 12),
+    );
+
+    assert_eq!(fixture.db_hub.apply_placement_test(&placement_test)?, 5);
+    known_question_word_rowid_v.sort_unstable();
+    assert_eq!(known_word_rowid_v()?, known_question_word_rowid_v);
+    assert_eq!(fixture.db_hub.undo()?.unwrap().description, "Apply placement test");
+    assert!(known_word_rowid_v()?.is_empty());
+
+    // With a single band, 3 of 4 answers known estimates 16 of 21 words known: the 3, and the 13
+    // most frequent words not answered "no".
+    let mut placement_test = fixture.db_hub.start_placement_test(PlacementTestConfig {
+        band_top_word_count_v: Vec::new(),
+        min_questions_per_band: 4,
+        max_questions_per_band: 4,
+        ..PlacementTestConfig::default()
+    })?;
+    let unknown_word_rowid = placement_test.next_question_o().unwrap().word_rowid;
+    for &knows_word in [false, true, true, true].iter() {
+        placement_test.answer(knows_word);
+    }
+    assert_eq!(placement_test.next_question_o(), None);
+    assert_eq!(placement_test.estimate().estimated_known_word_count, 16);
+    assert_eq!(fixture.db_hub.apply_placement_test(&placement_test)?, 16);
+    assert!(!known_word_rowid_v()?.contains(&unknown_word_rowid));
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_export_and_import_user_data() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    fixture.db_hub.compute_ngrams(&NgramSelection { min_freq: 2, min_pmi: 0.0, ..NgramSelection::default() })?;
+    let ich_bin_rowid = fixture.db_hub.query_ngram_v(1)?[0].ngram_row.ngrams_rowid;
+    fixture.db_hub.add_known_ngram(ich_bin_rowid)?;
+    fixture.add_known_words(&["Haus", "müde"])?;
+    fixture.db_hub.remove_known_word(fixture.word_rowid("müde")?)?;
+    fixture.db_hub.add_name(fixture.word_rowid("Tom")?)?;
+    let word_note = WordNote { note: "plural Häuser".into(), user_gloss: "house".into(), tag_v: vec!["noun".into()] };
+    fixture.db_hub.set_word_note(fixture.word_rowid("Haus")?, &word_note)?;
+    fixture.db_hub.create_word_set("Lesson 1", &[fixture.word_rowid("Haus")?, fixture.word_rowid("Krankenhaus")?])?;
+    // "Ich bin müde."
+    fixture.db_hub.mark_sentence_seen(1)?;
+
+    let user_data_bundle = fixture.db_hub.export_user_data()?;
+    assert_eq!(user_data_bundle.lang_short, "deu");
+    assert_eq!(user_data_bundle.known_word_text_v, vec!["Haus"]);
+    assert_eq!(user_data_bundle.known_word_event_v.len(), 3);
+    assert_eq!(user_data_bundle.seen_word_v.len(), 3);
+    assert_eq!(user_data_bundle.word_set_v[0].word_text_v, vec!["Haus", "Krankenhaus"]);
+    assert_eq!(user_data_bundle.word_note_v[0].word_note, word_note);
+    assert_eq!(user_data_bundle.name_word_text_v, vec!["Tom"]);
+    assert_eq!(user_data_bundle.known_ngram_text_v, vec!["Ich bin"]);
+    user_data_bundle.write_to_file(std::path::Path::new("user-data.json"))?;
+    assert_eq!(UserDataBundle::read_from_file(std::path::Path::new("user-data.json"))?, user_data_bundle);
+    assert!(UserDataBundle::from_json(&serde_json::json!({ "format": "something-else", "version": 1 })).is_err());
+    let mut newer_json = user_data_bundle.to_json();
+    newer_json["version"] = serde_json::json!(wordfrontier::USER_DATA_BUNDLE_VERSION + 1);
+    assert!(UserDataBundle::from_json(&newer_json).is_err());
+
+    // Start over with empty user data and a different corpus, in which the words have different
+    // rowids and there's no "Krankenhaus".
+    std::fs::remove_file(UserDb::db_path())?;
+    UserDb::create_and_populate_if_missing()?;
+    std::fs::remove_file(CorpusDb::db_path_from("deu")?)?;
+    CorpusDb::open(LangsDb::open()?.query_lang_row("deu")?)?.populate_from_sentences_tsv("\
+1\tdeu\tDas Haus ist groß.
+2\tdeu\tIch bin müde.
+3\tdeu\tIch bin hier.
+4\tdeu\tTom ist hier.
+")?;
+    let mut db_hub = DbHub::from_config(DbHubConfig::new("deu", "eng", None)?)?;
+    db_hub.compute_ngrams(&NgramSelection { min_freq: 2, min_pmi: 0.0, ..NgramSelection::default() })?;
+
+    let expected_report = UserDataImportReport {
+        known_word_count: 1,
+        known_word_event_count: 3,
+        seen_word_count: 3,
+        word_set_member_count: 1,
+        word_note_count: 1,
+        name_count: 1,
+        known_ngram_count: 1,
+        unmatched_word_text_v: vec!["Krankenhaus".into()],
+        unmatched_ngram_text_v: Vec::new(),
+    };
+    assert_eq!(db_hub.import_user_data(&user_data_bundle)?, expected_report);
+    let known_word_with_text_v = db_hub.query_known_word_with_text_v()?;
+    assert_eq!(known_word_with_text_v.len(), 1);
+    assert_eq!(known_word_with_text_v[0].word_text, "Haus");
+    assert_eq!(known_word_with_text_v[0].word_note_row_o.as_ref().unwrap().word_note, word_note);
+    assert_eq!(db_hub.query_name_with_text_v()?[0].word_text, "Tom");
+    assert!(db_hub.query_known_ngram_v()?[0].ngram_is_known);
+    assert_eq!(db_hub.query_known_word_timeline_v()?.last().unwrap().known_word_count, 1);
+    // Exporting again gives the same data, apart from what didn't match.
+    let reexported_user_data_bundle = db_hub.export_user_data()?;
+    assert_eq!(reexported_user_data_bundle.known_word_event_v, user_data_bundle.known_word_event_v);
+    assert_eq!(reexported_user_data_bundle.word_set_v[0].word_text_v, vec!["Haus"]);
+
+    // Importing again changes nothing.
+    assert_eq!(db_hub.import_user_data(&user_data_bundle)?, UserDataImportReport {
+        unmatched_word_text_v: vec!["Krankenhaus".into()],
+        ..UserDataImportReport::default()
+    });
+    // The known words that the import added are journaled, without adding known word events.
+    let journal_entry_v = db_hub.query_journal_entry_v(10)?;
+    assert_eq!(journal_entry_v.len(), 1);
+    assert_eq!(journal_entry_v[0].description, "Import user data");
+    assert_eq!(
+        journal_entry_v[0].change_v.iter().map(|change| (change.word_text.as_str(), change.is_added)).collect::<Vec<_>>(),
+        vec![("Haus", true)],
+    );
+    db_hub.undo()?;
+    assert!(db_hub.query_known_word_with_text_v()?.is_empty());
+    db_hub.redo()?;
+    // Bundles are specific to a language.
+    assert!(db_hub.import_user_data(&UserDataBundle { lang_short: "eng".into(), ..user_data_bundle }).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_merge_user_data() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    let known_word_text_v = |db_hub: &DbHub| -> Result<Vec<String>> {
+        Ok(db_hub.export_user_data()?.known_word_text_v)
+    };
+    fixture.add_known_words(&["Haus", "hier", "Ich"])?;
+    let haus_rowid = fixture.word_rowid("Haus")?;
+    fixture.db_hub.set_word_note(haus_rowid, &WordNote { note: "das Haus".into(), ..WordNote::default() })?;
+    let now = fixture.db_hub.export_user_data()?.exported_at;
+
+    // The other machine added "müde" and removed "hier" after this one's changes, but removed
+    // "Haus" before this one added it.  It knows "Tom" without any history.
+    let known_word_event = |word_text: &str, is_added: bool, occurred_at: i64| wordfrontier::BundleKnownWordEvent {
+        word_text: word_text.into(),
+        is_added,
+        occurred_at,
+    };
+    let other_word_note = WordNote { note: "das Haus, die Häuser".into(), ..WordNote::default() };
+    let other_user_data_bundle = UserDataBundle {
+        lang_short: "deu".into(),
+        exported_at: now + 2000,
+        known_word_text_v: vec!["Ich".into(), "Tom".into(), "müde".into()],
+        known_word_event_v: vec![
+            known_word_event("Haus", false, 1),
+            known_word_event("müde", true, now + 1000),
+            known_word_event("hier", false, now + 1000),
+        ],
+        seen_word_v: Vec::new(),
+        word_set_v: Vec::new(),
+        word_note_v: vec![wordfrontier::BundleWordNote {
+            word_text: "Haus".into(),
+            word_note: other_word_note.clone(),
+            created_at: 1,
+            updated_at: now + 1000,
+        }],
+        name_word_text_v: Vec::new(),
+        known_ngram_text_v: Vec::new(),
+    };
+    let expected_merge_report = UserDataMergeReport {
+        is_dry_run: true,
+        added_known_word_text_v: vec!["Tom".into(), "müde".into()],
+        removed_known_word_text_v: vec!["hier".into()],
+        import_report: UserDataImportReport {
+            known_word_count: 2,
+            known_word_event_count: 3,
+            word_note_count: 1,
+            ..UserDataImportReport::default()
+        },
+    };
+
+    // A dry run changes nothing.
+    assert_eq!(fixture.db_hub.merge_user_data(&other_user_data_bundle, true)?, expected_merge_report);
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "hier"]);
+    assert_eq!(fixture.db_hub.export_user_data()?.known_word_event_v.len(), 3);
+
+    assert_eq!(
+        fixture.db_hub.merge_user_data(&other_user_data_bundle, false)?,
+        UserDataMergeReport { is_dry_run: false, ..expected_merge_report },
+    );
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "Tom", "müde"]);
+    assert_eq!(fixture.db_hub.export_user_data()?.known_word_event_v.len(), 6);
+    assert_eq!(fixture.db_hub.query_word_note_o(haus_rowid)?.unwrap().word_note, other_word_note);
+    // The known word changes can be undone as a whole.
+    let journal_entry = fixture.db_hub.query_journal_entry_v(1)?.remove(0);
+    assert_eq!(journal_entry.description, "Merge user data");
+    assert_eq!(journal_entry.change_v.len(), 3);
+    fixture.db_hub.undo()?;
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "hier"]);
+    fixture.db_hub.redo()?;
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "Tom", "müde"]);
+    // Merging again changes nothing.
+    assert_eq!(fixture.db_hub.merge_user_data(&other_user_data_bundle, false)?, UserDataMergeReport::default());
+
+    // Merging another user DB file, here a copy from before "Tom" was removed here.
+    let corpus_db_p = CorpusDb::db_path_from("deu")?;
+    let corpus_db_p = std::path::Path::new(&corpus_db_p);
+    std::fs::copy(UserDb::db_path(), "desktop-user.db")?;
+    fixture.db_hub.remove_known_word(fixture.word_rowid("Tom")?)?;
+    assert_eq!(
+        fixture.db_hub.export_user_data_from_user_db(std::path::Path::new("desktop-user.db"), corpus_db_p)?.known_word_text_v,
+        vec!["Haus", "Ich", "Tom", "müde"],
+    );
+    assert_eq!(fixture.db_hub.merge_user_db(std::path::Path::new("desktop-user.db"), corpus_db_p, false)?, UserDataMergeReport::default());
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "müde"]);
+    assert!(fixture.db_hub.merge_user_db(std::path::Path::new("laptop-user.db"), corpus_db_p, true).is_err());
+
+    // Merging a user DB file from a machine with a different corpus version, in which the
+    // words have different rowids; it knows "müde" and "dich", without any history.
+    std::fs::rename(corpus_db_p, "this-deu.db")?;
+    CorpusDb::open(LangsDb::open()?.query_lang_row("deu")?)?.populate_from_sentences_tsv("\
+1\tdeu\tDu bist müde.
+2\tdeu\tTom ist hier.
+3\tdeu\tIch liebe dich.
+")?;
+    std::fs::rename(corpus_db_p, "laptop-deu.db")?;
+    std::fs::rename("this-deu.db", corpus_db_p)?;
+    std::fs::copy(UserDb::db_path(), "laptop-user.db")?;
+    {
+        let conn = rusqlite::Connection::open("laptop-user.db")?;
+        conn.execute("ATTACH DATABASE 'laptop-deu.db' AS laptop_corpus_db", [])?;
+        conn.execute_batch("
+            DELETE FROM known_word_events;
+            DELETE FROM known_words;
+            INSERT INTO known_words (lang_rowid, word_rowid)
+            SELECT lang_rowid, words_rowid FROM laptop_corpus_db.words WHERE text IN ('müde', 'dich');
+        ")?;
+        // The rowid of "dich" there is that of another word here.
+        assert_ne!(
+            conn.query_row("SELECT words_rowid FROM laptop_corpus_db.words WHERE text = 'dich'", [], |row| row.get::<_, i32>(0))?,
+            fixture.word_rowid("dich")?,
+        );
+    }
+    let merge_report = fixture.db_hub.merge_user_db(
+        std::path::Path::new("laptop-user.db"),
+        std::path::Path::new("laptop-deu.db"),
+        false,
+    )?;
+    assert_eq!(merge_report.added_known_word_text_v, vec!["dich"]);
+    assert!(merge_report.removed_known_word_text_v.is_empty());
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "Ich", "dich", "müde"]);
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_import_word_list() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut fixture = Fixture::new()?;
+    let known_word_text_v = |db_hub: &DbHub| -> Result<Vec<String>> {
+        Ok(db_hub.export_user_data()?.known_word_text_v)
+    };
+
+    std::fs::write("words.csv", "#deck: Deutsch\nEnglish,German\ntired,<b>müde</b>\nhouse,&nbsp;Haus [sound:haus.mp3]\nfrog,Frosch\nfrog,Frosch\n")?;
+    let word_list_import = WordListImport::new(WordListFormat::Csv, "German".parse()?).header_row(true);
+    let report = fixture.db_hub.import_word_list(std::path::Path::new("words.csv"), &word_list_import)?;
+    assert_eq!((report.entry_count, report.matched_entry_count, report.added_word_count), (4, 2, 2));
+    assert_eq!(report.unmatched_entry_v, vec!["Frosch"]);
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Haus", "müde"]);
+    // The import is a single journal entry.
+    assert_eq!(fixture.db_hub.undo()?.unwrap().change_v.len(), 2);
+    assert!(known_word_text_v(&fixture.db_hub)?.is_empty());
+
+    // Multi-word entries only match word by word when asked to.
+    std::fs::write("words.tsv", "ICH\tI\ndas Haus\tthe house\n")?;
+    let word_list_import = WordListImport::new(WordListFormat::Tsv, WordListField::Index(0)).word_set("Vokabeln");
+    let report = fixture.db_hub.import_word_list(std::path::Path::new("words.tsv"), &word_list_import)?;
+    assert_eq!((report.matched_entry_count, report.added_word_count), (1, 1));
+    assert_eq!(report.unmatched_entry_v, vec!["das Haus"]);
+    // Words are compared via normalize_word, so "das" matches both "Das" and "das".
+    let report = fixture.db_hub.import_word_list(std::path::Path::new("words.tsv"), &word_list_import.match_each_word(true))?;
+    assert_eq!((report.matched_entry_count, report.added_word_count), (2, 3));
+    let word_set_v = fixture.db_hub.query_word_set_v()?;
+    assert_eq!(word_set_v.len(), 1);
+    let mut word_set_member_text_v: Vec<String> = fixture.db_hub
+        .query_word_set_member_with_text_v(word_set_v[0].word_sets_rowid)?
+        .into_iter()
+        .map(|word_set_member| word_set_member.word_text)
+        .collect();
+    word_set_member_text_v.sort();
+    assert_eq!(word_set_member_text_v, vec!["Das", "Haus", "Ich", "das"]);
+    assert!(known_word_text_v(&fixture.db_hub)?.is_empty());
+
+    // Anki packages are zip archives of an SQLite collection, whose notes' fields are separated
+    // by '\x1f'.  The legacy format keeps the note types' field names in col.models, while the
+    // current one has a fields table and is zstd-compressed.
+    let write_anki_package = |package_name: &str, collection_name: &str, is_legacy: bool| -> Result<()> {
+        let collection_p = std::path::Path::new("collection.tmp");
+        {
+            let conn = rusqlite::Connection::open(collection_p)?;
+            conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, mid INTEGER NOT NULL, flds TEXT NOT NULL)")?;
+            if is_legacy {
+                conn.execute_batch(r#"
+                    CREATE TABLE col (id INTEGER PRIMARY KEY, models TEXT NOT NULL);
+                    INSERT INTO col (id, models) VALUES (1, '{"1001": {"flds": [{"name": "Back", "ord": 1}, {"name": "Front", "ord": 0}]}}');
+                "#)?;
+            } else {
+                conn.execute_batch("
+                    CREATE TABLE fields (ntid INTEGER NOT NULL, ord INTEGER NOT NULL, name TEXT NOT NULL);
+                    INSERT INTO fields (ntid, ord, name) VALUES (1001, 0, 'Front'), (1001, 1, 'Back');
+                ")?;
+            }
+            conn.execute_batch("INSERT INTO notes (id, mid, flds) VALUES (1, 1001, 'liebe' || char(31) || 'love'), (2, 1001, 'Tom' || char(31) || 'Tom')")?;
+        }
+        let collection_byte_v = std::fs::read(collection_p)?;
+        std::fs::remove_file(collection_p)?;
+        let mut zip_writer = zip::ZipWriter::new(std::fs::File::create(package_name)?);
+        zip_writer.start_file(collection_name, zip::write::FileOptions::default())?;
+        if is_legacy {
+            std::io::Write::write_all(&mut zip_writer, &collection_byte_v)?;
+        } else {
+            std::io::Write::write_all(&mut zip_writer, &zstd::encode_all(collection_byte_v.as_slice(), 0)?)?;
+        }
+        zip_writer.start_file("media", zip::write::FileOptions::default())?;
+        std::io::Write::write_all(&mut zip_writer, b"{}")?;
+        zip_writer.finish()?;
+        Ok(())
+    };
+    write_anki_package("legacy.apkg", "collection.anki2", true)?;
+    write_anki_package("current.colpkg", "collection.anki21b", false)?;
+
+    let report = fixture.db_hub.import_word_list(
+        std::path::Path::new("legacy.apkg"),
+        &WordListImport::new(WordListFormat::Anki, "Front".parse()?),
+    )?;
+    assert_eq!((report.entry_count, report.added_word_count), (2, 2));
+    assert_eq!(known_word_text_v(&fixture.db_hub)?, vec!["Tom", "liebe"]);
+    let report = fixture.db_hub.import_word_list(
+        std::path::Path::new("current.colpkg"),
+        &WordListImport::new(WordListFormat::Anki, "Back".parse()?),
+    )?;
+    assert_eq!((report.entry_count, report.matched_entry_count, report.added_word_count), (2, 1, 0));
+    assert_eq!(report.unmatched_entry_v, vec!["love"]);
+    assert!(fixture.db_hub.import_word_list(
+        std::path::Path::new("current.colpkg"),
+        &WordListImport::new(WordListFormat::Anki, "Notes".parse()?),
+    ).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_export_anki_tsv() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "bin", "Du", "bist"])?;
+    fixture.db_hub.set_word_note(fixture.word_rowid("müde")?, &WordNote { user_gloss: "tired <adj.>".into(), ..WordNote::default() })?;
+    let frontier_query = FrontierQuery::new(Range(1, 1)).order(Order::Unordered);
+    let word_frontier_member_v: Vec<_> = fixture.db_hub
+        .query_word_frontier_v(&frontier_query)?
+        .into_iter()
+        .filter(|word_frontier_member| word_frontier_member.sentences_rowid <= 3)
+        .collect();
+    assert_eq!(word_frontier_member_v.len(), 3);
+
+    let anki_tsv_p = std::env::current_dir()?.join("anki.txt");
+    fixture.db_hub.export_anki_tsv(&anki_tsv_p, &frontier_query, &word_frontier_member_v)?;
+    let anki_tsv = std::fs::read_to_string(&anki_tsv_p)?;
+    let line_v: Vec<&str> = anki_tsv.lines().collect();
+    assert_eq!(
+        line_v[..5],
+        [
+            "#separator:tab",
+            "#html:true",
+            "#guid column:1",
+            "#tags column:7",
+            "#columns:GUID\tSentence\tUnknown Words\tTranslation\tGloss\tAttribution\tTags",
+        ],
+    );
+    assert_eq!(line_v.len(), 8);
+    let field_v: Vec<&str> = line_v.iter().find(|line| line.starts_with("wordfrontier-deu-1\t")).unwrap().split('\t').collect();
+    assert_eq!(
+        field_v,
+        vec![
+            wordfrontier::anki_note_guid("deu", 1).as_str(),
+            "Ich bin <b>müde</b>.",
+            "müde",
+            "I am tired.",
+            "<b>müde</b>: tired &lt;adj.&gt;",
+            "Sentences <a href='https://tatoeba.org/en/sentences/show/1'>#1</a>, <a href='https://tatoeba.org/en/sentences/show/101'>#101</a> \
+                from <a href='https://tatoeba.org'>Tatoeba</a>, licensed under <a href='https://creativecommons.org/licenses/by/2.0/fr/'>CC BY 2.0 FR</a>",
+            "wordfrontier deu",
+        ],
+    );
+
+    // The GUIDs only depend on the sentences, so re-exports update the same notes.
+    fixture.add_known_words(&["hier"])?;
+    fixture.db_hub.export_anki_tsv(&anki_tsv_p, &frontier_query, &word_frontier_member_v)?;
+    let reexported_anki_tsv = std::fs::read_to_string(&anki_tsv_p)?;
+    let guid_v = |anki_tsv: &str| -> Vec<String> {
+        anki_tsv.lines().skip(5).map(|line| line.split('\t').next().unwrap().to_string()).collect()
+    };
+    assert_eq!(guid_v(&reexported_anki_tsv), guid_v(&anki_tsv));
+    assert!(reexported_anki_tsv.contains("\tIch bin hier.\t\tI am here.\t"));
+
+    Ok(())
+}
+
+#[test]
+#[serial_test::serial]
+fn test_db_hub_render_reading_packet() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let fixture = Fixture::new()?;
+    fixture.add_known_words(&["Ich", "ist", "hier", "Wo", "das", "ein"])?;
+    fixture.db_hub.set_word_note(fixture.word_rowid("Haus")?, &WordNote { user_gloss: "house".into(), ..WordNote::default() })?;
+    let frontier_query = FrontierQuery::new(Range(1, 1)).order(Order::Unordered);
+    let word_frontier_member_v: Vec<_> = fixture.db_hub
+        .query_word_frontier_v(&frontier_query)?
+        .into_iter()
+        .filter(|word_frontier_member| [4, 7].contains(&word_frontier_member.sentences_rowid))
+        .collect();
+    assert_eq!(word_frontier_member_v.len(), 2);
+
+    let markdown = fixture.db_hub.render_reading_packet("Lesen *1*", &frontier_query, &word_frontier_member_v, ReadingPacketFormat::Markdown)?;
+    assert!(markdown.starts_with("# Lesen \\*1\\*\n"));
+    for expected in [
+        "1. **Tom** ist hier.\n2. Wo ist das **Krankenhaus**?\n",
+        "## Glossary\n\n- **Krankenhaus**\n- **Tom**\n",
+        "## Answer Key\n\n1. Tom is here.\n2. Where is the hospital?\n",
+        "licensed under [CC BY 2.0 FR](https://creativecommons.org/licenses/by/2.0/fr/)",
+        "1. [#4](https://tatoeba.org/en/sentences/show/4), [#104](https://tatoeba.org/en/sentences/show/104)\n",
+    ].iter() {
+        assert!(markdown.contains(expected), "{:?} not in {:?}", expected, markdown);
+    }
+
+    // Words count as unknown as they do for the frontier query, which here treats names as
+    // known.
+    fixture.db_hub.add_name(fixture.word_rowid("Tom")?)?;
+    let frontier_query = FrontierQuery::new(Range(0, 1)).auto_known_word_class(WordClass::Name).order(Order::Unordered);
+    let markdown = fixture.db_hub.render_reading_packet("Lesen", &frontier_query, &word_frontier_member_v, ReadingPacketFormat::Markdown)?;
+    for expected in [
+        "1. Tom ist hier.\n2. Wo ist das **Krankenhaus**?\n",
+        "## Glossary\n\n- **Krankenhaus**\n\n",
+    ].iter() {
+        assert!(markdown.contains(expected), "{:?} not in {:?}", expected, markdown);
+    }
+
+    // The glossary is sorted case-insensitively, with the glosses that there are.
+    let frontier_query = FrontierQuery::new(Range(1, 2)).order(Order::Unordered);
+    let word_frontier_member_v: Vec<_> = fixture.db_hub
+        .query_word_frontier_v(&frontier_query)?
+        .into_iter()
+        .filter(|word_frontier_member| word_frontier_member.sentences_rowid == 6)
+        .collect();
+    let html = fixture.db_hub.render_reading_packet("Lesen <1>", &frontier_query, &word_frontier_member_v, ReadingPacketFormat::Html)?;
+    for expected in [
+        "<title>Lesen &lt;1&gt;</title>",
+        "<li>Ich <mark>habe</mark> ein <mark>Haus</mark>.</li>",
+        "<dt>habe</dt><dd></dd>\n<dt>Haus</dt><dd>house</dd>\n",
+        "<section class=\"answer-key\">\n<h2>Answer Key</h2>\n<ol>\n<li>I have a house.</li>\n</ol>",
+        "<a href=\"https://creativecommons.org/licenses/by/2.0/fr/\">CC BY 2.0 FR</a>",
+        "<li><a href=\"https://tatoeba.org/en/sentences/show/6\">#6</a>, <a href=\"https://tatoeba.org/en/sentences/show/106\">#106</a></li>",
+    ].iter() {
+        assert!(html.contains(expected), "{:?} not in {:?}", expected, html);
+    }
+    assert_eq!("markdown".parse::<ReadingPacketFormat>()?, ReadingPacketFormat::Markdown);
+
+    Ok(())
+}
+
+#[test]
+fn test_keyword_in_context() {
+    let keyword_in_context = KeywordInContext::new("Wo ist das Krankenhaus?", "Krankenhaus", 4).unwrap();
+    assert_eq!(keyword_in_context.to_string(), "…das [Krankenhaus]?");
+    assert_eq!(keyword_in_context.to_aligned_line(8), "   …das [Krankenhaus]?");
+    let keyword_in_context = KeywordInContext::new("Ich bin müde.", "bin", 10).unwrap();
+    assert_eq!(keyword_in_context, KeywordInContext { left: "Ich ".into(), keyword: "bin".into(), right: " müde.".into() });
+    // Only whole words match.
+    assert_eq!(KeywordInContext::new("Wo ist das Krankenhaus?", "Haus", 4), None);
+}
+
+#[test]
+fn test_tokenize_and_fold_text() {
+    let word_v: Vec<&str> = tokenize("¿Wo ist \"das\" Krankenhaus? ... Tom's").collect();
+    assert_eq!(word_v, vec!["Wo", "ist", "das", "Krankenhaus", "Tom's"]);
+    assert_eq!(fold_text("Müde CAFÉ"), "mude cafe");
+    let sentence_v: Vec<&str> = split_sentences("Er sagte: \"Hallo!\" Dann ging er... Wohin?\nNach Hause 3.5 km").collect();
+    assert_eq!(sentence_v, vec!["Er sagte: \"Hallo!\"", "Dann ging er...", "Wohin?", "Nach Hause 3.5 km"]);
+}
+
 #[tokio::test]
 #[serial_test::serial]
 async fn test_translations_db_create_and_populate_from_download() -> Result<()> {